@@ -3,20 +3,291 @@
 
 mod request;
 
-pub use request::{RawRequest, Request, RequestHandler, RequestKind, RequestMethod};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{mpsc, Arc};
+
+pub use request::{RawRequest, Request, RequestError, RequestHandler, RequestKind, RequestMethod};
+use uuid::Uuid;
+
+/// Strategy for picking the servers to deactivate when scaling down
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ScaleDownPolicy {
+    /// Deactivate the most recently activated servers
+    #[default]
+    HighestIndex,
+    /// Deactivate the servers with the fewest reservations, they terminate sooner
+    LeastLoaded,
+}
+
+impl FromStr for ScaleDownPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "highest-index" => Ok(Self::HighestIndex),
+            "least-loaded" => Ok(Self::LeastLoaded),
+            _ => {
+                Err(format!(
+                    "unknown scale down policy {s}, expected highest-index or least-loaded"
+                ))
+            }
+        }
+    }
+}
+
+/// Strategy for picking the server of a customer without one
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ServerSelection {
+    /// Pick a random non-terminating server
+    #[default]
+    Random,
+    /// Pick the non-terminating server with the fewest reservations
+    LeastLoaded,
+    /// Pick the same non-terminating server for the same customer, as long as it
+    /// exists (consistent hashing of the customer's id)
+    CustomerHash,
+}
+
+impl FromStr for ServerSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "random" => Ok(Self::Random),
+            "least-loaded" => Ok(Self::LeastLoaded),
+            "customer-hash" => Ok(Self::CustomerHash),
+            _ => {
+                Err(format!(
+                    "unknown server selection {s}, expected random, least-loaded, or \
+                     customer-hash"
+                ))
+            }
+        }
+    }
+}
+
+/// Strategy for deciding how many tickets a server allocates from the database
+/// when it runs out of tickets
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AllocationStrategy {
+    /// The square root of the tickets in the database
+    #[default]
+    Sqrt,
+    /// A fixed number of tickets
+    Fixed(u32),
+    /// The given fraction of the tickets in the database
+    Fraction(f64),
+}
+
+impl AllocationStrategy {
+    /// Get the number of tickets to allocate if the database holds `available`
+    /// tickets (at least one)
+    pub fn batch_size(self, available: u32) -> u32 {
+        let size = match self {
+            Self::Sqrt => (available as f64).sqrt() as u32,
+            Self::Fixed(size) => size,
+            Self::Fraction(fraction) => (available as f64 * fraction) as u32,
+        };
+        size.max(1)
+    }
+}
+
+impl FromStr for AllocationStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || {
+            format!(
+                "unknown allocation strategy {s}, expected sqrt, fixed:<tickets>, or \
+                 fraction:<fraction>"
+            )
+        };
+        match s.split_once(':') {
+            None if s == "sqrt" => Ok(Self::Sqrt),
+            Some(("fixed", size)) => size.parse().map(Self::Fixed).map_err(|_| err()),
+            Some(("fraction", fraction)) => {
+                match fraction.parse() {
+                    Ok(fraction) if (0.0..=1.0).contains(&fraction) => Ok(Self::Fraction(fraction)),
+                    _ => Err(err()),
+                }
+            }
+            _ => Err(err()),
+        }
+    }
+}
 
 /// Configuration of the ticket sales system
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub struct Config {
     /// Amount of initially available tickets
     pub tickets: u32,
     /// Timeout in seconds after which reservations expire
     pub timeout: u32,
+    /// Maximal number of seconds randomly added to the timeout of each reservation,
+    /// so reservations made in a burst do not all expire at once (0 means none)
+    pub timeout_jitter: u32,
+    /// Time in seconds a server remembers a completed buy, so a retried buy request
+    /// of the same ticket succeeds again instead of failing (0 disables this)
+    pub idempotency_window: u32,
     /// Number of initial servers
     pub initial_servers: u32,
+    /// Minimal number of servers, requests for fewer servers are clamped to it
+    pub min_servers: u32,
+    /// Maximal number of servers, requests for more servers are clamped to it
+    pub max_servers: u32,
     /// Time in seconds the estimator takes to contact all servers
     pub estimator_roundtrip_time: u32,
+    /// Weight of the latest measurement in the estimator's exponentially smoothed
+    /// ticket count of each server (must be in (0, 1], 1 means no smoothing)
+    pub estimator_smoothing: f64,
+    /// Poll all servers at once in each estimator roundtrip instead of one after
+    /// another, so all servers learn the ticket counts of the same moment
+    pub estimator_parallel: bool,
+    /// Run the estimator (if disabled, e.g., for a single server, servers report
+    /// their own tickets plus the ones in the database as available)
+    pub estimator_enabled: bool,
+    /// Number of servers below which the load balancer counts the available tickets
+    /// exactly on each request instead of relying on the estimate (0 disables this)
+    pub exact_below_servers: u32,
+    /// Strategy for picking the servers to deactivate when scaling down
+    pub scale_down_policy: ScaleDownPolicy,
+    /// Strategy for picking the server of a customer without one
+    pub server_selection: ServerSelection,
+    /// Strategy for deciding how many tickets a server allocates at once
+    pub allocation_strategy: AllocationStrategy,
+    /// Maximal number of tickets a server allocates at once, capping the allocation
+    /// strategy
+    pub max_batch: u32,
+    /// Maximal number of requests waiting for a server, further requests for it are
+    /// rejected as busy instead of blocking the load balancer (must not be 0)
+    pub server_queue_capacity: u32,
+    /// Allow the debugging command making a server sleep (for testing only)
+    pub allow_debug_sleep: bool,
+    /// Allow the debugging command losing one of a server's tickets (for testing
+    /// only)
+    pub allow_debug_orphan: bool,
+    /// Allocate the tickets with the lowest ids first (by default, the database
+    /// allocates last in, first out)
+    pub ordered_allocation: bool,
+    /// Chance in units of 1/10000 that a bonus server hands a request of a customer
+    /// without a reservation to a random server, to spread the load (must be below
+    /// 10000)
+    pub reassign_rate: u32,
+    /// Number of requests of a customer within the request window after which a
+    /// bonus server keeps the customer instead of handing its requests to a random
+    /// server
+    pub keep_server_after: u32,
+    /// Number of seconds a bonus server counts the requests of a customer, customers
+    /// without a request within this window are forgotten
+    pub request_window: u32,
+    /// Probability in `[0, 1]` that the bonus balancer keeps a customer without an
+    /// active session on their previous server instead of handing them to a random
+    /// one (customers sending more than `keep_server_after` requests within the
+    /// request window are always kept). It applies before the servers' own
+    /// `reassign_rate`, so with 1 and a reassign rate of 0, customers only change
+    /// their server once it terminates.
+    pub session_affinity: f64,
+    /// Maximal number of requests of a customer without an active session since
+    /// their last session ended in the bonus implementation, further ones are
+    /// rejected (0 means unlimited)
+    pub max_requests_per_customer: u32,
+    /// Token admin requests must carry in their `Authorization` header (if
+    /// [`None`], admin requests are open to everyone)
+    pub admin_token: Option<String>,
+    /// Called with a summary of the system once it is launched
+    pub on_launch: Option<fn(&LaunchInfo)>,
+    /// Seed for generating reproducible server ids (if [`None`], the ids are random)
+    pub server_id_seed: Option<u64>,
+    /// Receives the customer id of every expired reservation (for testing only)
+    pub expiry_sender: Option<mpsc::Sender<Uuid>>,
+    /// Called with every routing decision of the load balancer
+    pub routing_logger: Option<RoutingLogger>,
+    /// File the database state is restored from at launch (if it exists) and
+    /// written to on shutdown, reservations are not persisted
+    pub state_path: Option<PathBuf>,
 
     /// Run the implementation for the bonus exercise
     pub bonus: bool,
 }
+
+/// Routing decision of the load balancer, see [`Config::routing_logger`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoutingEvent {
+    /// A customer without a server was assigned one
+    Assigned {
+        /// Id of the customer
+        customer: Uuid,
+        /// Id of the assigned server
+        server: Uuid,
+    },
+    /// The server of a customer no longer exists, so the request was rejected and
+    /// the customer assigned a new server
+    ServerGone {
+        /// Id of the customer
+        customer: Uuid,
+        /// Id of the server that no longer exists
+        old: Uuid,
+    },
+    /// The server of a customer terminated while forwarding the request, so it was
+    /// forwarded to another server
+    Reassigned {
+        /// Id of the customer
+        customer: Uuid,
+        /// Id of the terminated server
+        old: Uuid,
+        /// Id of the server the request was forwarded to
+        new: Uuid,
+    },
+    /// A customer without an active session was handed to a random server, see
+    /// [`Config::session_affinity`]
+    Switched {
+        /// Id of the customer
+        customer: Uuid,
+        /// Id of the customer's previous server
+        old: Uuid,
+        /// Id of the server the request was forwarded to
+        new: Uuid,
+    },
+}
+
+/// Callback for the routing decisions of the load balancer
+///
+/// It is called on the balancer threads, possibly while they hold locks, so it
+/// should return quickly.
+#[derive(Clone)]
+pub struct RoutingLogger(Arc<dyn Fn(RoutingEvent) + Send + Sync>);
+
+impl RoutingLogger {
+    /// Create a new [`RoutingLogger`] calling `log` with every routing decision
+    pub fn new(log: impl Fn(RoutingEvent) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(log))
+    }
+
+    /// Report a routing decision
+    pub fn log(&self, event: RoutingEvent) {
+        (self.0)(event)
+    }
+}
+
+impl fmt::Debug for RoutingLogger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RoutingLogger")
+    }
+}
+
+/// Summary of a launched ticket sales system
+#[derive(Clone, Debug)]
+pub struct LaunchInfo {
+    /// Amount of initially available tickets
+    pub tickets: u32,
+    /// Timeout in seconds after which reservations expire
+    pub timeout: u32,
+    /// Time in seconds the estimator takes to contact all servers
+    pub estimator_roundtrip_time: u32,
+    /// Whether the implementation for the bonus exercise runs
+    pub bonus: bool,
+    /// Ids of the non-terminating servers
+    pub servers: Vec<Uuid>,
+}
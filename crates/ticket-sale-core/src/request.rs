@@ -49,6 +49,223 @@ pub enum RequestKind {
     ///
     /// 📌 Hint: You can process this request however you like.
     Debug,
+
+    /// Retrieve the reservation timeout in seconds
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    GetTimeout,
+
+    /// Reserve up to the provided number of tickets at once
+    ///
+    /// The response is the list of reserved ticket ids, which may be shorter
+    /// than requested if not enough tickets are left.
+    ///
+    /// 📌 Hint: Should be processed by a server.
+    ReserveMultiple,
+
+    /// Retrieve the number of sold tickets
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    GetSoldTickets,
+
+    /// Check whether the customer could currently reserve a ticket, without
+    /// reserving one
+    ///
+    /// The response is 1 if a reservation would currently succeed and 0
+    /// otherwise.
+    ///
+    /// 📌 Hint: Should be processed by a server.
+    CanReserve,
+
+    /// Activate the terminating server given by the server id header again
+    ///
+    /// The response is the number of servers after the activation.
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    ActivateServer,
+
+    /// Drain the non-terminating server given by the server id header: it refuses
+    /// new reservations but keeps serving the existing ones and terminates once they
+    /// are cleared
+    ///
+    /// The response is the number of servers after draining.
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    DrainServer,
+
+    /// Buy all tickets reserved by the customer at once
+    ///
+    /// The response is the list of bought ticket ids.
+    ///
+    /// 📌 Hint: Should be processed by a server.
+    BuyAll,
+
+    /// Retrieve the exact number of non-reserved tickets held by the server,
+    /// without the estimate for the other servers and the database
+    ///
+    /// 📌 Hint: Should be processed by a server.
+    LocalAvailableTickets,
+
+    /// Retrieve the number of tickets currently reserved across all servers
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    GetReservedCount,
+
+    /// Answer immediately without involving any server, for measuring the
+    /// overhead of the load balancer
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    Ping,
+
+    /// Abort all reservations of the customer at once
+    ///
+    /// The response is the list of released ticket ids.
+    ///
+    /// 📌 Hint: Should be processed by a server.
+    AbortAll,
+
+    /// Get the ids of the tickets currently reserved for the customer
+    ///
+    /// The response is the list of reserved ticket ids, empty if the customer
+    /// holds no reservation.
+    ///
+    /// 📌 Hint: Should be processed by a server.
+    GetMyReservations,
+
+    /// Transfer a reserved ticket of the customer to another customer
+    ///
+    /// The payload is the ticket id followed by the target customer id, separated
+    /// by whitespace (see [`Request::read_transfer()`]). The reservation keeps its
+    /// original time, i.e., the transfer does not extend it. The response is the
+    /// ticket id.
+    ///
+    /// 📌 Hint: Should be processed by a server.
+    TransferReservation,
+
+    /// Distribute the tickets of the database evenly to the non-terminating
+    /// servers ahead of the first reservations
+    ///
+    /// The response is the number of tickets handed out.
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    Warmup,
+
+    /// Retrieve the number of servers deactivated by a previous scale request
+    /// that did not terminate yet
+    ///
+    /// While it is non-zero, [`RequestKind::SetNumServers`] requests wait for the
+    /// servers to terminate. The response is `0` once scaling settled.
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    GetScalingState,
+
+    /// Retrieve the number of tickets reserved on each non-terminating server
+    ///
+    /// The response is a string with one line `<server id> <reserved tickets>` per
+    /// server.
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    GetServerLoad,
+
+    /// Retrieve which implementation is running, `standard` or `bonus` (`slug` for
+    /// the sequential reference implementation)
+    ///
+    /// Like [`RequestKind::Debug`], this is meant for testing and not part of the
+    /// graded interface.
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    GetVariant,
+
+    /// Retrieve the exact number of non-reserved tickets, i.e., the ones left in
+    /// the database plus the ones held by all servers
+    ///
+    /// Unlike [`RequestKind::NumAvailableTickets`], this does not rely on the
+    /// estimate of a server.
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    GetTotalAvailable,
+
+    /// Abort all reservations on all servers and return their tickets to the
+    /// database, e.g., to reset the system between the phases of a test
+    ///
+    /// The response is the number of released tickets.
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    ResetReservations,
+
+    /// Retrieve a list of all servers which are terminating but did not terminate
+    /// yet
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    GetTerminatingServers,
+
+    /// Retrieve the number of initially available tickets, e.g., to display how
+    /// many of them are sold together with [`RequestKind::GetSoldTickets`]
+    ///
+    /// 📌 Hint: Should be processed by the load balancer.
+    GetCapacity,
+}
+
+impl RequestKind {
+    /// All request kinds, in the order of their discriminants
+    pub const ALL: [RequestKind; 29] = {
+        use RequestKind::*;
+        [
+            GetNumServers,
+            SetNumServers,
+            GetServers,
+            NumAvailableTickets,
+            ReserveTicket,
+            BuyTicket,
+            AbortPurchase,
+            Debug,
+            GetTimeout,
+            ReserveMultiple,
+            GetSoldTickets,
+            CanReserve,
+            ActivateServer,
+            DrainServer,
+            BuyAll,
+            LocalAvailableTickets,
+            GetReservedCount,
+            Ping,
+            AbortAll,
+            GetMyReservations,
+            TransferReservation,
+            Warmup,
+            GetScalingState,
+            GetServerLoad,
+            GetVariant,
+            GetTotalAvailable,
+            ResetReservations,
+            GetTerminatingServers,
+            GetCapacity,
+        ]
+    };
+
+    /// Whether the request administrates the system rather than serving a
+    /// customer
+    ///
+    /// If an admin token is configured, only requests carrying it may be of
+    /// such a kind.
+    pub fn is_admin(&self) -> bool {
+        use RequestKind::*;
+        matches!(
+            self,
+            GetNumServers
+                | SetNumServers
+                | GetServers
+                | ActivateServer
+                | DrainServer
+                | Warmup
+                | GetScalingState
+                | GetServerLoad
+                | GetVariant
+                | ResetReservations
+                | GetTerminatingServers
+                | Debug
+        )
+    }
 }
 
 /// Request sent from a web browser
@@ -59,6 +276,12 @@ pub struct Request {
     kind: RequestKind,
     customer: Uuid,
     server: Option<Uuid>,
+    request_id: Option<String>,
+    /// Integer payload read by [`Self::peek_u32()`] but not yet by
+    /// [`Self::read_u32()`]
+    peeked: Option<Option<u32>>,
+    /// Called right before the response is sent
+    on_respond: Option<Box<dyn FnOnce() + Send>>,
     raw: Box<dyn RawRequest + Send>,
 }
 
@@ -68,6 +291,7 @@ impl std::fmt::Debug for Request {
             .field("kind", &self.kind)
             .field("customer", &self.customer)
             .field("server", &self.server)
+            .field("request_id", &self.request_id)
             .field("raw", &format_args!(".."))
             .finish()
     }
@@ -80,6 +304,54 @@ pub enum RequestMethod {
     Get,
     /// POST request, may have a payload
     Post,
+    /// PUT request, may have a payload (only for debugging commands)
+    Put,
+    /// DELETE request (only for debugging commands)
+    Delete,
+}
+
+/// Kind of an error response, deciding its HTTP status code
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub enum RequestError {
+    /// The request is invalid, e.g., a ticket id is missing (400)
+    #[default]
+    BadRequest,
+    /// The request lacks the admin token (401), see
+    /// [`Request::respond_with_unauthorized()`]
+    Unauthorized,
+    /// The request conflicts with the state of the customer's reservation, e.g.,
+    /// there is none (409)
+    Conflict,
+    /// The customer's server no longer exists or accepts reservations, the
+    /// request should be retried with the newly assigned server (410)
+    ServerGone,
+    /// The customer sent too many requests (429)
+    TooManyRequests,
+    /// The ticket sales system cannot process requests right now, e.g., because
+    /// there are no servers (503)
+    Unavailable,
+}
+
+impl RequestError {
+    /// Get the HTTP status code of the error
+    pub fn status_code(self) -> u16 {
+        match self {
+            Self::BadRequest => 400,
+            Self::Unauthorized => 401,
+            Self::Conflict => 409,
+            Self::ServerGone => 410,
+            Self::TooManyRequests => 429,
+            Self::Unavailable => 503,
+        }
+    }
+
+    /// Whether sending the request again may succeed without changing it
+    pub fn is_retryable(self) -> bool {
+        matches!(
+            self,
+            Self::ServerGone | Self::TooManyRequests | Self::Unavailable
+        )
+    }
 }
 
 /// Interface for handling requests from a web browser
@@ -107,6 +379,10 @@ pub trait RawRequest {
     fn url(&self) -> &str;
     /// Get the request method
     fn method(&self) -> RequestMethod;
+    /// Get the value of the `Authorization` header, if present
+    fn authorization(&self) -> Option<&str>;
+    /// Get all headers as (name, value) pairs
+    fn headers(&self) -> Vec<(String, String)>;
 
     /// Read the request body as bytes
     fn read_bytes(&mut self) -> io::Result<Vec<u8>>;
@@ -116,15 +392,36 @@ pub trait RawRequest {
     fn read_u32(&mut self) -> Option<u32>;
 
     /// Respond with an error message
-    fn respond_with_err(self: Box<Self>, err: String, customer: Uuid, server: Option<Uuid>);
+    fn respond_with_err(
+        self: Box<Self>,
+        kind: RequestError,
+        err: String,
+        customer: Uuid,
+        server: Option<Uuid>,
+    );
+    /// Respond with an error of kind [`RequestError::ServerGone`] telling the
+    /// client to send the request again to the server `server`
+    fn respond_with_redirect(self: Box<Self>, err: String, customer: Uuid, server: Uuid);
+    /// Respond with an error indicating a missing or wrong admin token
+    fn respond_with_unauthorized(self: Box<Self>, customer: Uuid, server: Option<Uuid>);
     /// Respond with a integer
     fn respond_with_int(self: Box<Self>, int: u32, customer: Uuid, server: Option<Uuid>);
+    /// Respond with a reserved ticket expiring in `ttl_secs` seconds
+    fn respond_with_reservation(
+        self: Box<Self>,
+        ticket: u32,
+        ttl_secs: u32,
+        customer: Uuid,
+        server: Option<Uuid>,
+    );
     /// Respond with a string
     fn respond_with_string(self: Box<Self>, s: String, customer: Uuid, server: Option<Uuid>);
     /// Respond with “SOLD OUT”
     fn respond_with_sold_out(self: Box<Self>, customer: Uuid, server: Option<Uuid>);
     /// Respond with a server list
     fn respond_with_server_list(self: Box<Self>, servers: &[Uuid]);
+    /// Respond with a list of integers
+    fn respond_with_int_list(self: Box<Self>, ints: &[u32], customer: Uuid, server: Option<Uuid>);
 }
 
 impl Request {
@@ -155,6 +452,16 @@ impl Request {
         self.customer
     }
 
+    /// Get the id for tracing the request (the value of the `X-Request-Id`
+    /// header), if any
+    ///
+    /// 📌 Hint: This method is only relevant for debugging.
+    #[inline]
+    #[allow(unused)]
+    pub fn request_id(&self) -> Option<&str> {
+        self.request_id.as_deref()
+    }
+
     /// Get the request URL
     ///
     /// 📌 Hint: This method is only relevant if you want to implement custom
@@ -176,6 +483,24 @@ impl Request {
         self.raw.method()
     }
 
+    /// Get the value of the `Authorization` header, if present
+    ///
+    /// 📌 Hint: This method is only relevant if an admin token is configured.
+    #[inline]
+    pub fn authorization(&self) -> Option<&str> {
+        self.raw.authorization()
+    }
+
+    /// Get all headers of the request as (name, value) pairs
+    ///
+    /// 📌 Hint: This method is only relevant if you want to implement custom
+    /// debugging commands. It copies the headers, so only call it for those.
+    #[inline]
+    #[allow(unused)]
+    pub fn headers(&self) -> Vec<(String, String)> {
+        self.raw.headers()
+    }
+
     /// Read an integer provided by the web browser (e.g., a ticket id or number
     /// of servers).
     ///
@@ -186,7 +511,37 @@ impl Request {
     /// request.
     #[inline]
     pub fn read_u32(&mut self) -> Option<u32> {
-        self.raw.read_u32()
+        match self.peeked.take() {
+            Some(int) => int,
+            None => self.raw.read_u32(),
+        }
+    }
+
+    /// Get the integer provided by the web browser without consuming it, i.e.,
+    /// a subsequent [`Self::read_u32()`] returns the same value
+    ///
+    /// This is useful, e.g., for logging the payload before processing the
+    /// request.
+    #[inline]
+    pub fn peek_u32(&mut self) -> Option<u32> {
+        match self.peeked {
+            Some(int) => int,
+            None => *self.peeked.insert(self.raw.read_u32()),
+        }
+    }
+
+    /// Read the ticket id and the target customer id of a
+    /// [`RequestKind::TransferReservation`] request
+    ///
+    /// Returns [`None`] if the payload is not a ticket id followed by a customer
+    /// id. Like [`Self::read_u32()`], this method has side effects and should be
+    /// called only once per request.
+    pub fn read_transfer(&mut self) -> Option<(u32, Uuid)> {
+        let payload = self.raw.read_string().ok()?;
+        let mut parts = payload.split_whitespace();
+        let ticket = parts.next()?.parse().ok()?;
+        let target = Uuid::parse_str(parts.next()?).ok()?;
+        parts.next().is_none().then_some((ticket, target))
     }
 
     /// Read the payload provided by the web browser as bytes
@@ -225,8 +580,42 @@ impl Request {
     /// This method blocks until the response has been sent.
     #[inline]
     pub fn respond_with_err(self, err: impl Into<String>) {
+        self.respond_with_err_kind(RequestError::BadRequest, err);
+    }
+
+    /// Respond with an error of the given kind to the client
+    ///
+    /// This method blocks until the response has been sent.
+    #[inline]
+    pub fn respond_with_err_kind(self, kind: RequestError, err: impl Into<String>) {
+        Self::responded(self.on_respond);
+        self.raw
+            .respond_with_err(kind, err.into(), self.customer, self.server);
+    }
+
+    /// Respond with an error of kind [`RequestError::ServerGone`] and the message
+    /// `err`, telling the client to send the request again to `new_server`
+    ///
+    /// Clients that do not know about redirects see the same error as before,
+    /// with `new_server` as the server id.
+    ///
+    /// This method blocks until the response has been sent.
+    #[inline]
+    pub fn respond_with_redirect(mut self, new_server: Uuid, err: impl Into<String>) {
+        Self::responded(self.on_respond);
+        self.server = Some(new_server);
+        self.raw
+            .respond_with_redirect(err.into(), self.customer, new_server);
+    }
+
+    /// Respond with an error indicating that the request lacks the admin token
+    ///
+    /// This method blocks until the response has been sent.
+    #[inline]
+    pub fn respond_with_unauthorized(self) {
+        Self::responded(self.on_respond);
         self.raw
-            .respond_with_err(err.into(), self.customer, self.server);
+            .respond_with_unauthorized(self.customer, self.server);
     }
 
     /// Respond with an integer, e.g., a ticket number or the number of servers.
@@ -234,14 +623,27 @@ impl Request {
     /// This method blocks until the response has been sent.
     #[inline]
     pub fn respond_with_int(self, int: u32) {
+        Self::responded(self.on_respond);
         self.raw.respond_with_int(int, self.customer, self.server);
     }
 
+    /// Respond with a reserved ticket, telling the client that the reservation
+    /// expires in `ttl_secs` seconds
+    ///
+    /// This method blocks until the response has been sent.
+    #[inline]
+    pub fn respond_with_reservation(self, ticket: u32, ttl_secs: u32) {
+        Self::responded(self.on_respond);
+        self.raw
+            .respond_with_reservation(ticket, ttl_secs, self.customer, self.server);
+    }
+
     /// Respond with an arbitrary string
     ///
     /// This method blocks until the response has been sent.
     #[inline]
     pub fn respond_with_string(self, s: impl Into<String>) {
+        Self::responded(self.on_respond);
         self.raw
             .respond_with_string(s.into(), self.customer, self.server);
     }
@@ -254,6 +656,7 @@ impl Request {
     /// This method blocks until the response has been sent.
     #[inline]
     pub fn respond_with_sold_out(self) {
+        Self::responded(self.on_respond);
         self.raw.respond_with_sold_out(self.customer, self.server);
     }
 
@@ -262,7 +665,35 @@ impl Request {
     /// Use this method to send a list of server ids to the client.
     #[inline]
     pub fn respond_with_server_list(self, servers: &[Uuid]) {
-        self.raw.respond_with_server_list(servers)
+        Self::responded(self.on_respond);
+        self.raw.respond_with_server_list(servers);
+    }
+
+    /// Responds with a list of integers, e.g., ticket numbers
+    ///
+    /// This method blocks until the response has been sent.
+    #[inline]
+    pub fn respond_with_int_list(self, ints: &[u32]) {
+        Self::responded(self.on_respond);
+        self.raw
+            .respond_with_int_list(ints, self.customer, self.server);
+    }
+
+    /// Set a callback to be called right before the response is sent, e.g., to
+    /// measure the latency of the request
+    ///
+    /// Replaces any previously set callback.
+    #[inline]
+    pub fn on_respond(&mut self, callback: impl FnOnce() + Send + 'static) {
+        self.on_respond = Some(Box::new(callback));
+    }
+
+    /// Call the callback set by [`Self::on_respond()`], if any
+    #[inline]
+    fn responded(on_respond: Option<Box<dyn FnOnce() + Send>>) {
+        if let Some(callback) = on_respond {
+            callback();
+        }
     }
 
     /// Create a new request from a [`RawRequest`]
@@ -274,12 +705,16 @@ impl Request {
         kind: RequestKind,
         customer: Uuid,
         server: Option<Uuid>,
+        request_id: Option<String>,
         raw: Box<dyn RawRequest + Send>,
     ) -> Self {
         Self {
             kind,
             customer,
             server,
+            request_id,
+            peeked: None,
+            on_respond: None,
             raw,
         }
     }
@@ -1,11 +1,135 @@
 //! Implementation of the balancer
+#[cfg(feature = "persist")]
+use std::io;
+#[cfg(feature = "persist")]
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam::channel::{bounded, Sender};
 use ticket_sale_core::{Request, RequestHandler};
 
 use crate::balancer_bonus::BalancerBonus;
 use crate::balancer_standard::BalancerStandard;
+use crate::enums::HighPriorityServerRequest;
+
+/// Reconciliation of the tickets after the system has been shut down
+#[derive(Clone, Copy, Debug)]
+pub struct ShutdownReport {
+    /// Number of initially available tickets
+    pub tickets: u32,
+    /// Number of tickets in the database after all servers returned theirs
+    pub available: u32,
+    /// Number of sold tickets
+    pub sold: u32,
+}
+
+impl ShutdownReport {
+    /// Whether every ticket is either available or sold
+    pub fn is_consistent(&self) -> bool {
+        self.available as u64 + self.sold as u64 == self.tickets as u64
+    }
+
+    /// Number of tickets that are neither available nor sold, e.g., because they got
+    /// stuck in a terminated server
+    pub fn orphaned(&self) -> u32 {
+        self.tickets
+            .saturating_sub(self.available.saturating_add(self.sold))
+    }
+
+    /// Print a warning if the report is not consistent
+    pub fn warn_if_inconsistent(&self) {
+        if !self.is_consistent() {
+            eprintln!(
+                "Our warning: {} tickets available and {} sold, but {} tickets in total.",
+                self.available, self.sold, self.tickets
+            );
+        }
+    }
+}
+
+/// Time a dropped [`Balancer`] waits for the system to shut down
+const DROP_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Send the request built by `make` to each of the servers and collect their
+/// answers
+///
+/// Servers that terminated in the meantime are skipped. The caller must not hold
+/// the coordinator lock, as servers may need it while processing requests.
+pub(crate) fn ask_servers<T>(
+    senders: Vec<Sender<HighPriorityServerRequest>>,
+    make: impl Fn(Sender<T>) -> HighPriorityServerRequest,
+) -> Vec<T> {
+    let mut answers = Vec::new();
+    for sender in senders {
+        let (answer_sender, answer_receiver) = bounded(1);
+        if sender.send(make(answer_sender)).is_ok() {
+            if let Ok(answer) = answer_receiver.recv() {
+                answers.push(answer);
+            }
+        }
+    }
+    answers
+}
+
+/// Interval for checking whether the servers drained before a shutdown
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Wait until no server is left draining, but at most `drain_secs` seconds
+///
+/// `remaining` must not be called with the coordinator lock held, as draining
+/// servers need it.
+pub(crate) fn wait_drained(drain_secs: u32, remaining: impl Fn() -> u32) {
+    let deadline = Instant::now() + Duration::from_secs(drain_secs as u64);
+    while remaining() > 0 && Instant::now() < deadline {
+        thread::sleep(DRAIN_POLL_INTERVAL);
+    }
+}
+
+/// Gate turning requests away once the system shuts down
+///
+/// Requests hold an [`Admission`] while they are handled, so the shutdown can wait
+/// until all admitted requests reached their server before shutting it down.
+#[derive(Default)]
+pub(crate) struct ShutdownGate {
+    closed: AtomicBool,
+    admitted: AtomicU32,
+}
+
+/// Proof that a request passed the [`ShutdownGate`], releases it when dropped
+pub(crate) struct Admission<'a>(&'a ShutdownGate);
+
+impl ShutdownGate {
+    /// Admit a request, unless the gate is closed
+    pub fn enter(&self) -> Option<Admission<'_>> {
+        self.admitted.fetch_add(1, Ordering::SeqCst);
+        if self.closed.load(Ordering::SeqCst) {
+            self.admitted.fetch_sub(1, Ordering::SeqCst);
+            return None;
+        }
+        Some(Admission(self))
+    }
+
+    /// Close the gate and wait until all admitted requests were handled
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        while self.admitted.load(Ordering::SeqCst) > 0 {
+            thread::yield_now();
+        }
+    }
+}
+
+impl Drop for Admission<'_> {
+    fn drop(&mut self) {
+        self.0.admitted.fetch_sub(1, Ordering::SeqCst);
+    }
+}
 
 pub struct Balancer {
-    // May contain the regular balancer or the one used for the bonus
+    // May contain the regular balancer or the one used for the bonus, both are taken
+    // when shutting down
     balancer_standard: Option<BalancerStandard>,
     balancer_bonus: Option<BalancerBonus>,
 
@@ -28,6 +152,92 @@ impl Balancer {
     }
 }
 
+impl Balancer {
+    /// Whether the implementation for the bonus exercise is running
+    pub fn is_bonus(&self) -> bool {
+        self.bonus
+    }
+
+    /// Persist the state of the system to the file at `path`, see
+    /// [`launch_from_snapshot`](crate::launch_from_snapshot)
+    ///
+    /// The state is only consistent if no requests are processed meanwhile.
+    #[cfg(feature = "persist")]
+    pub fn persist(&self, path: &Path) -> io::Result<()> {
+        // Forward to the appropriate balancer
+        if !self.bonus {
+            match &self.balancer_standard {
+                Some(balancer) => balancer.persist(path),
+                None => panic!("Our panic: Standard balancer not found in persist."),
+            }
+        } else {
+            match &self.balancer_bonus {
+                Some(balancer) => balancer.persist(path),
+                None => panic!("Our panic: Bonus balancer not found in persist."),
+            }
+        }
+    }
+
+    /// Drain all servers before shutting down, so reserved tickets can still be bought
+    ///
+    /// New reservations are refused right away. The servers shut down once their
+    /// reservations are bought, aborted, or expired, but after at most `drain_secs`
+    /// seconds (e.g., the reservation timeout). Afterwards, the system is in the same
+    /// state as after [`Self::begin_shutdown`]: the estimator and all servers are
+    /// stopped, every request is answered with an error, and the system still has to
+    /// be shut down via [`RequestHandler::shutdown`], which reconciles the tickets.
+    pub fn graceful_shutdown(&self, drain_secs: u32) {
+        // Forward to the appropriate balancer
+        if !self.bonus {
+            match &self.balancer_standard {
+                Some(balancer) => balancer.graceful_shutdown(drain_secs),
+                None => panic!("Our panic: Standard balancer not found in graceful shutdown."),
+            }
+        } else {
+            match &self.balancer_bonus {
+                Some(balancer) => balancer.graceful_shutdown(drain_secs),
+                None => panic!("Our panic: Bonus balancer not found in graceful shutdown."),
+            }
+        }
+    }
+
+    /// Stop accepting requests and shut the servers down
+    ///
+    /// Waits until the requests being handled reached their servers. Afterwards,
+    /// every request is answered with an error, and the system still has to be shut
+    /// down via [`RequestHandler::shutdown`], which reconciles the tickets.
+    pub fn begin_shutdown(&self) {
+        // Forward to the appropriate balancer
+        if !self.bonus {
+            match &self.balancer_standard {
+                Some(balancer) => balancer.begin_shutdown(),
+                None => panic!("Our panic: Standard balancer not found in shutdown."),
+            }
+        } else {
+            match &self.balancer_bonus {
+                Some(balancer) => balancer.begin_shutdown(),
+                None => panic!("Our panic: Bonus balancer not found in shutdown."),
+            }
+        }
+    }
+
+    /// Shut down the system and reconcile the tickets afterwards
+    pub fn shutdown_report(mut self) -> ShutdownReport {
+        // Forward to the appropriate balancer
+        if !self.bonus {
+            match self.balancer_standard.take() {
+                Some(balancer) => balancer.shutdown_report(),
+                None => panic!("Our panic: Standard balancer not found in shutdown."),
+            }
+        } else {
+            match self.balancer_bonus.take() {
+                Some(balancer) => balancer.shutdown_report(),
+                None => panic!("Our panic: Bonus balancer not found in shutdown."),
+            }
+        }
+    }
+}
+
 impl RequestHandler for Balancer {
     /// Handle a given request
     fn handle(&self, rq: Request) {
@@ -47,17 +257,35 @@ impl RequestHandler for Balancer {
 
     /// Shut down the system
     fn shutdown(self) {
-        // Forward to the appropriate balancer
-        if !self.bonus {
-            match self.balancer_standard {
-                Some(balancer) => balancer.shutdown(),
-                None => panic!("Our panic: Standard balancer not found in shutdown."),
+        self.shutdown_report().warn_if_inconsistent();
+    }
+}
+
+impl Drop for Balancer {
+    /// Shut the system down if [`RequestHandler::shutdown`] was not called, so its
+    /// threads do not keep running
+    fn drop(&mut self) {
+        let balancer_standard = self.balancer_standard.take();
+        let balancer_bonus = self.balancer_bonus.take();
+        // Already shut down
+        if balancer_standard.is_none() && balancer_bonus.is_none() {
+            return;
+        }
+        eprintln!("Our warning: Balancer dropped without shutting it down, shutting down now.");
+
+        // Shut down in another thread, so a stuck server cannot block the drop forever
+        let (done_sender, done_receiver) = mpsc::channel();
+        thread::spawn(move || {
+            if let Some(balancer) = balancer_standard {
+                balancer.shutdown_report().warn_if_inconsistent();
             }
-        } else {
-            match self.balancer_bonus {
-                Some(balancer) => balancer.shutdown(),
-                None => panic!("Our panic: Bonus balancer not found in shutdown."),
+            if let Some(balancer) = balancer_bonus {
+                balancer.shutdown_report().warn_if_inconsistent();
             }
+            let _ = done_sender.send(());
+        });
+        if done_receiver.recv_timeout(DROP_SHUTDOWN_TIMEOUT).is_err() {
+            eprintln!("Our warning: Shutting down the dropped balancer timed out.");
         }
     }
 }
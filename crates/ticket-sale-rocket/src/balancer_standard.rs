@@ -1,126 +1,608 @@
 //! Implementation of the standard balancer
 
+#![allow(clippy::too_many_arguments)]
+#[cfg(feature = "persist")]
+use std::io;
+#[cfg(feature = "persist")]
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{mpsc, Arc};
 use std::thread::JoinHandle;
+use std::time::Instant;
 
-use parking_lot::{Mutex, MutexGuard, RawMutex};
-use ticket_sale_core::{Request, RequestHandler, RequestKind};
+use crossbeam::channel::TrySendError;
+use parking_lot::{Mutex, MutexGuard};
+use ticket_sale_core::{
+    Request, RequestError, RequestHandler, RequestKind, RoutingEvent, RoutingLogger,
+};
 use uuid::Uuid;
 
+use super::balancer::{ask_servers, wait_drained, ShutdownGate, ShutdownReport};
 use super::coordinator_standard::CoordinatorStandard;
+use super::database::Database;
+use super::debug;
+use super::enums::{HighPriorityServerRequest, ServerStats};
+use super::metrics::Metrics;
+#[cfg(feature = "persist")]
+use super::persist::SystemState;
+use super::snapshot::ServerSnapshot;
 
 pub struct BalancerStandard {
     coordinator: Arc<Mutex<CoordinatorStandard>>,
+    active_servers: Arc<ServerSnapshot>,
+    database: Arc<Mutex<Database>>,
+
+    // Number of initially available tickets
+    tickets: u32,
+
+    // The reservation timeout
+    reservation_timeout: u32,
+
+    // Number of servers below which the available tickets are counted exactly
+    exact_below_servers: u32,
+
+    // Whether the debugging command making a server sleep is allowed
+    allow_debug_sleep: bool,
+
+    // Whether the debugging command losing a ticket is allowed
+    allow_debug_orphan: bool,
+
+    // Token admin requests must carry, if any
+    admin_token: Option<String>,
+
+    // Called with every routing decision, if any
+    routing_logger: Option<RoutingLogger>,
 
     // Sender for telling the estimator to shut down
     estimator_shutdown_sender: mpsc::Sender<()>,
 
-    // Thread the estimator runs in
-    estimator_thread: JoinHandle<()>,
+    // Thread the estimator runs in, if it is enabled and still running
+    estimator_thread: Mutex<Option<JoinHandle<()>>>,
+
+    // Number of passes the estimator completed over all servers
+    estimator_passes: Arc<AtomicU32>,
+
+    // Latencies of the handled requests
+    metrics: Arc<Metrics>,
+
+    // File the database is written to on shutdown, if any
+    state_path: Option<PathBuf>,
+
+    // Turns requests away once the system is shutting down
+    gate: ShutdownGate,
 }
 
 impl BalancerStandard {
     /// Create a new [`BalancerStandard`]
     pub fn new(
         coordinator: Arc<Mutex<CoordinatorStandard>>,
+        active_servers: Arc<ServerSnapshot>,
+        database: Arc<Mutex<Database>>,
+        tickets: u32,
+        reservation_timeout: u32,
+        exact_below_servers: u32,
+        allow_debug_sleep: bool,
+        allow_debug_orphan: bool,
+        admin_token: Option<String>,
+        routing_logger: Option<RoutingLogger>,
         estimator_shutdown_sender: mpsc::Sender<()>,
-        estimator_thread: JoinHandle<()>,
+        estimator_thread: Option<JoinHandle<()>>,
+        estimator_passes: Arc<AtomicU32>,
+        state_path: Option<PathBuf>,
     ) -> Self {
         Self {
             coordinator,
+            active_servers,
+            database,
+            tickets,
+            reservation_timeout,
+            exact_below_servers,
+            allow_debug_sleep,
+            allow_debug_orphan,
+            admin_token,
+            routing_logger,
             estimator_shutdown_sender,
-            estimator_thread,
+            estimator_thread: Mutex::new(estimator_thread),
+            estimator_passes,
+            state_path,
+            metrics: Arc::new(Metrics::new()),
+            gate: ShutdownGate::default(),
+        }
+    }
+
+    /// Report a routing decision to the logger, if any
+    fn log_routing(&self, event: RoutingEvent) {
+        if let Some(logger) = &self.routing_logger {
+            logger.log(event);
+        }
+    }
+
+    /// Check whether the request carries the admin token, if one is configured
+    fn is_authorized(&self, rq: &Request) -> bool {
+        match &self.admin_token {
+            None => true,
+            Some(token) => {
+                rq.authorization()
+                    .and_then(|header| header.strip_prefix("Bearer "))
+                    == Some(token.as_str())
+            }
+        }
+    }
+
+    /// Get the ticket counts of each non-terminating server as JSON array
+    fn server_stats(&self) -> String {
+        let senders = self.coordinator.lock().get_active_high_priority_senders();
+        let stats: Vec<_> = ask_servers(senders, |sender| {
+            HighPriorityServerRequest::ReportStats { sender }
+        })
+        .into_iter()
+        .map(ServerStats::to_json)
+        .collect();
+
+        // One server per line for easier reading
+        format!("[\n{}\n]", stats.join(",\n"))
+    }
+
+    /// Get the number of reserved tickets summed over all servers
+    fn reserved_count(&self) -> u32 {
+        // Terminating servers may still hold reservations, so ask all of them
+        let senders = self.coordinator.lock().get_high_priority_senders();
+        ask_servers(senders, |sender| {
+            HighPriorityServerRequest::ReportReserved { sender }
+        })
+        .into_iter()
+        .sum()
+    }
+
+    /// Get the exact number of non-reserved tickets, summed over the database and
+    /// all servers
+    fn total_available(&self) -> u32 {
+        // Terminating servers may still hold tickets, so ask all of them
+        let senders = self.coordinator.lock().get_high_priority_senders();
+        let available: u32 = ask_servers(senders, |sender| {
+            HighPriorityServerRequest::ReportAvailable { sender }
+        })
+        .into_iter()
+        .sum();
+
+        // Servers need the database lock while processing requests, so it must not
+        // be held while waiting for them
+        available + self.database.lock().get_num_available()
+    }
+
+    /// Abort the reservations of all servers, returning the number of released
+    /// tickets
+    fn reset_reservations(&self) -> u32 {
+        // Terminating servers may still hold reservations, so clear all of them
+        let senders = self.coordinator.lock().get_high_priority_senders();
+        ask_servers(senders, |sender| {
+            HighPriorityServerRequest::ClearReservations { sender }
+        })
+        .into_iter()
+        .sum()
+    }
+
+    /// Whether few enough servers run to count the available tickets exactly
+    /// instead of relying on the estimate
+    fn counts_exactly(&self) -> bool {
+        self.active_servers.get().len() < self.exact_below_servers as usize
+    }
+
+    /// Answer a request for the available tickets with the exact total, assigning
+    /// a server to a customer without one like [`Self::forward`]
+    fn respond_with_total_available(&self, mut rq: Request) {
+        if rq.server_id().is_none() {
+            let server = self.coordinator.lock().select_server(rq.customer_id());
+            if let Some(server) = server {
+                self.log_routing(RoutingEvent::Assigned {
+                    customer: rq.customer_id(),
+                    server,
+                });
+                rq.set_server_id(server);
+            }
+        }
+        rq.respond_with_int(self.total_available());
+    }
+
+    /// Get the number of reserved tickets of each non-terminating server, one
+    /// `<server id> <reserved tickets>` line per server
+    fn server_load(&self) -> String {
+        let senders = self.coordinator.lock().get_active_high_priority_senders();
+        let load: Vec<_> = ask_servers(senders, |sender| {
+            HighPriorityServerRequest::ReportLoad { sender }
+        })
+        .into_iter()
+        .map(|(server, reserved)| format!("{server} {reserved}"))
+        .collect();
+        load.join("\n")
+    }
+
+    /// Persist the state of the system to the file at `path`
+    ///
+    /// The state is only consistent if no requests are processed meanwhile.
+    #[cfg(feature = "persist")]
+    pub fn persist(&self, path: &Path) -> io::Result<()> {
+        // Terminated servers no longer answer, but they hold no tickets either
+        let senders = self.coordinator.lock().get_high_priority_senders();
+        let servers = ask_servers(senders, |sender| {
+            HighPriorityServerRequest::Persist { sender }
+        });
+
+        let database_guard = self.database.lock();
+        let state = SystemState {
+            tickets: self.tickets,
+            sold: database_guard.get_num_sold(),
+            available: database_guard.available_ids(),
+            servers,
+        };
+        drop(database_guard);
+        state.write_to(path)
+    }
+
+    /// Drain all servers and shut down once their reservations are cleared, but
+    /// after at most `drain_secs` seconds, see
+    /// [`Balancer::graceful_shutdown`](crate::Balancer::graceful_shutdown)
+    pub fn graceful_shutdown(&self, drain_secs: u32) {
+        self.coordinator.lock().drain_all();
+        wait_drained(drain_secs, || {
+            self.coordinator.lock().get_num_remaining_servers()
+        });
+        self.begin_shutdown();
+    }
+
+    /// Stop accepting requests and shut the servers down, see
+    /// [`Balancer::begin_shutdown`](crate::Balancer::begin_shutdown)
+    pub fn begin_shutdown(&self) {
+        // Stop accepting new requests and wait for the admitted ones
+        self.gate.close();
+        // Tell the estimator to shut down
+        let _ = self.estimator_shutdown_sender.send(());
+        // Wait for it to finish, unless it never started or already finished
+        if let Some(estimator_thread) = self.estimator_thread.lock().take() {
+            estimator_thread.join().unwrap();
+        }
+        // Tell servers to shut down, they answer the requests still queued
+        CoordinatorStandard::shutdown(&self.coordinator);
+    }
+
+    /// Shut down the system and reconcile the tickets afterwards
+    pub fn shutdown_report(self) -> ShutdownReport {
+        self.begin_shutdown();
+
+        // All servers returned their tickets to the database
+        let database_guard = self.database.lock();
+
+        // Write the database, so a restart continues the sale
+        if let Some(path) = &self.state_path {
+            if let Err(err) = database_guard.snapshot(path) {
+                eprintln!(
+                    "Our warning: Could not write the state to {}: {err}",
+                    path.display()
+                );
+            }
+        }
+
+        ShutdownReport {
+            tickets: self.tickets,
+            available: database_guard.get_num_available(),
+            sold: database_guard.get_num_sold(),
         }
     }
 
     /// Forward a user request to a given server
     fn send_to(
         &self,
-        server: Uuid,
+        mut server: Uuid,
         mut rq: Request,
         coordinator_guard: MutexGuard<CoordinatorStandard>,
     ) {
-        // Get the low priority sender channel for the server
-        let sender = coordinator_guard.get_low_priority_sender(server);
-        // Send the request
-        let response = sender.send(rq);
-        if let Err(senderr) = response {
-            let mut rq = senderr.into_inner();
-            let server = coordinator_guard.get_random_server();
-            rq.set_server_id(server);
-            self.send_to(server, rq, coordinator_guard);
+        loop {
+            // Get the low priority sender channel for the server
+            let sender = coordinator_guard.get_low_priority_sender(server);
+            // Send the request without blocking the balancer thread
+            match sender.try_send(rq) {
+                Ok(_) => return,
+                Err(TrySendError::Full(rq)) => {
+                    // Queue of the server is full => let the customer retry later
+                    rq.respond_with_err_kind(
+                        RequestError::Unavailable,
+                        "Our error: Server busy, retry later.",
+                    );
+                    return;
+                }
+                Err(TrySendError::Disconnected(senderr_rq)) => {
+                    // Server terminated in the meantime => assign a new one
+                    rq = senderr_rq;
+                    match coordinator_guard.select_server(rq.customer_id()) {
+                        Some(new_server) => {
+                            self.log_routing(RoutingEvent::Reassigned {
+                                customer: rq.customer_id(),
+                                old: server,
+                                new: new_server,
+                            });
+                            server = new_server;
+                        }
+                        None => {
+                            rq.respond_with_err_kind(
+                                RequestError::Unavailable,
+                                "Our error: No servers available.",
+                            );
+                            return;
+                        }
+                    }
+                    rq.set_server_id(server);
+                }
+            }
         }
     }
+
+    /// Forward a request that needs to be processed by a server
+    fn forward(&self, mut rq: Request) {
+        let mut coordinator_guard = self.coordinator.lock();
+        match rq.server_id() {
+            // Request already has a server
+            Some(server) => {
+                // Update non-terminating servers in the coordinator
+                coordinator_guard.update_servers();
+                // Make sure assigned server still exists afterwards
+                if !coordinator_guard.server_exists(server) {
+                    // If not, redirect the client to a new server
+                    let new_server = coordinator_guard.select_server(rq.customer_id());
+                    self.log_routing(RoutingEvent::ServerGone {
+                        customer: rq.customer_id(),
+                        old: server,
+                    });
+                    match new_server {
+                        Some(new_server) => {
+                            rq.respond_with_redirect(
+                                new_server,
+                                "Our error: Server no longer exists.",
+                            )
+                        }
+                        None => {
+                            rq.respond_with_err_kind(
+                                RequestError::ServerGone,
+                                "Our error: Server no longer exists.",
+                            )
+                        }
+                    }
+                } else {
+                    // If yes, forward the request to the server
+                    self.send_to(server, rq, coordinator_guard);
+                }
+            }
+            // Request doesn't have a server
+            None => {
+                // Assign a server and forward the request to the server
+                match coordinator_guard.select_server(rq.customer_id()) {
+                    Some(server) => {
+                        self.log_routing(RoutingEvent::Assigned {
+                            customer: rq.customer_id(),
+                            server,
+                        });
+                        rq.set_server_id(server);
+                        self.send_to(server, rq, coordinator_guard);
+                    }
+                    None => {
+                        rq.respond_with_err_kind(
+                            RequestError::Unavailable,
+                            "Our error: No servers available.",
+                        )
+                    }
+                }
+            }
+        };
+    }
 }
 
 impl RequestHandler for BalancerStandard {
     /// Handle a given request
     fn handle(&self, mut rq: Request) {
+        // Record the time until the response, wherever the request is processed
+        let start = Instant::now();
+        let kind = *rq.kind();
+        let metrics = self.metrics.clone();
+        rq.on_respond(move || metrics.record(kind, start.elapsed()));
+
+        // Answer requests arriving during shutdown instead of dropping them, the
+        // shutdown waits until the admitted ones are handed over to their server
+        let Some(_admission) = self.gate.enter() else {
+            rq.respond_with_err_kind(RequestError::Unavailable, "Our error: Shutting down.");
+            return;
+        };
+
+        // Reject admin requests without the admin token
+        if rq.kind().is_admin() && !self.is_authorized(&rq) {
+            rq.respond_with_unauthorized();
+            return;
+        }
+
         match rq.kind() {
             RequestKind::GetNumServers => {
                 // Get the number of non-terminating servers
                 rq.respond_with_int(self.coordinator.lock().get_num_active_servers());
             }
             RequestKind::GetServers => {
-                // Get the non-terminating servers
-                rq.respond_with_server_list(self.coordinator.lock().get_active_servers());
+                // Get the non-terminating servers without waiting for the coordinator
+                rq.respond_with_server_list(&self.active_servers.get());
+            }
+            RequestKind::GetTerminatingServers => {
+                let mut coordinator = self.coordinator.lock();
+                // Remove terminated servers
+                coordinator.update_servers();
+                rq.respond_with_server_list(&coordinator.get_terminating_servers());
             }
             RequestKind::SetNumServers => {
                 match rq.read_u32() {
                     Some(n) => {
-                        // Set number of active servers to n
-                        self.coordinator
-                            .lock()
-                            .scale_to(n, self.coordinator.clone());
-                        rq.respond_with_int(n);
+                        // Set number of active servers to n (within the configured bounds)
+                        match CoordinatorStandard::scale_to_in_batches(&self.coordinator, n) {
+                            Some(n) => rq.respond_with_int(n),
+                            None => {
+                                rq.respond_with_err_kind(
+                                    RequestError::Unavailable,
+                                    "Our error: Previous scaling still in progress.",
+                                );
+                            }
+                        }
                     }
                     None => {
                         rq.respond_with_err("Our error: No. of servers is None.");
                     }
                 };
             }
-            RequestKind::Debug => {
-                // 📌 Hint: You can use `rq.url()` and `rq.method()` to
-                // implement multiple debugging commands.
-                rq.respond_with_string("Happy Debugging! 🚫🐛");
+            RequestKind::ActivateServer => {
+                match rq.server_id() {
+                    Some(server) => {
+                        // Activate the server again if it is still terminating
+                        let mut coordinator_guard = self.coordinator.lock();
+                        if coordinator_guard.activate_server(server) {
+                            let n = coordinator_guard.get_num_active_servers();
+                            drop(coordinator_guard);
+                            rq.respond_with_int(n);
+                        } else if !coordinator_guard.server_exists(server) {
+                            drop(coordinator_guard);
+                            rq.respond_with_err_kind(
+                                RequestError::ServerGone,
+                                "Our error: Server no longer exists.",
+                            );
+                        } else {
+                            // The server exists but is in the wrong state or the bound
+                            // on the number of servers is reached
+                            let err = if !coordinator_guard.is_active(server) {
+                                "Our error: Maximal number of servers reached."
+                            } else {
+                                "Our error: Server is already non-terminating."
+                            };
+                            drop(coordinator_guard);
+                            rq.respond_with_err_kind(RequestError::Conflict, err);
+                        }
+                    }
+                    None => {
+                        rq.respond_with_err("Our error: No server id given.");
+                    }
+                }
             }
-            _ => {
-                let mut coordinator_guard = self.coordinator.lock();
+            RequestKind::DrainServer => {
                 match rq.server_id() {
-                    // Request already has a server
                     Some(server) => {
-                        // Update non-terminating servers in the coordinator
-                        coordinator_guard.update_servers();
-                        // Make sure assigned server still exists afterwards
-                        if !coordinator_guard.map_id_index.contains_key(&server) {
-                            // If not, assign a new server and respond with error
-                            let new_server = coordinator_guard.get_random_server();
-                            rq.set_server_id(new_server);
-                            rq.respond_with_err("Our error: Server no longer exists.");
+                        // Stop the server from taking new reservations
+                        let mut coordinator_guard = self.coordinator.lock();
+                        if coordinator_guard.drain_server(server) {
+                            let n = coordinator_guard.get_num_active_servers();
+                            drop(coordinator_guard);
+                            rq.respond_with_int(n);
+                        } else if !coordinator_guard.server_exists(server) {
+                            drop(coordinator_guard);
+                            rq.respond_with_err_kind(
+                                RequestError::ServerGone,
+                                "Our error: Server no longer exists.",
+                            );
                         } else {
-                            // If yes, forward the request to the server
-                            self.send_to(server, rq, coordinator_guard);
+                            // The server exists but is in the wrong state or the bound
+                            // on the number of servers is reached
+                            let err = if coordinator_guard.is_active(server) {
+                                "Our error: Minimal number of servers reached."
+                            } else {
+                                "Our error: Server is already terminating."
+                            };
+                            drop(coordinator_guard);
+                            rq.respond_with_err_kind(RequestError::Conflict, err);
                         }
                     }
-                    // Request doesn't have a server
                     None => {
-                        // Assign a server and forward the request to the server
-                        let server = coordinator_guard.get_random_server();
-                        rq.set_server_id(server);
-                        self.send_to(server, rq, coordinator_guard);
+                        rq.respond_with_err("Our error: No server id given.");
                     }
-                };
+                }
+            }
+            RequestKind::GetScalingState => {
+                // Get the number of servers that did not terminate after scaling down
+                rq.respond_with_int(self.coordinator.lock().get_num_settling_servers());
+            }
+            RequestKind::Warmup => {
+                // Hand the tickets of the database to the non-terminating servers
+                let tickets = self.coordinator.lock().warmup();
+                rq.respond_with_int(tickets);
+            }
+            RequestKind::GetTimeout => {
+                // Get the reservation timeout in seconds
+                rq.respond_with_int(self.reservation_timeout);
+            }
+            RequestKind::GetSoldTickets => {
+                // Get the number of sold tickets
+                rq.respond_with_int(self.database.lock().get_num_sold());
+            }
+            RequestKind::GetCapacity => {
+                // Get the number of initially available tickets
+                rq.respond_with_int(self.tickets);
             }
+            RequestKind::GetReservedCount => {
+                // Sum the reservations of all servers
+                rq.respond_with_int(self.reserved_count());
+            }
+            RequestKind::NumAvailableTickets if self.counts_exactly() => {
+                // Count exactly instead of asking a server for its estimate
+                self.respond_with_total_available(rq);
+            }
+            RequestKind::GetTotalAvailable => {
+                // Sum the tickets of the database and all servers
+                rq.respond_with_int(self.total_available());
+            }
+            RequestKind::ResetReservations => {
+                // Abort the reservations of all servers
+                rq.respond_with_int(self.reset_reservations());
+            }
+            RequestKind::GetServerLoad => {
+                // List the reservations of each non-terminating server
+                rq.respond_with_string(self.server_load());
+            }
+            RequestKind::Ping => {
+                // Answer right away, without touching the coordinator or any server
+                rq.respond_with_int(0);
+            }
+            RequestKind::GetVariant => {
+                // Tell which implementation runs, e.g., to check the test settings
+                rq.respond_with_string("standard");
+            }
+            RequestKind::Debug => {
+                // 📌 Hint: You can use `rq.url()` and `rq.method()` to
+                // implement multiple debugging commands.
+                if debug::path(rq.url()) == debug::ESTIMATOR_PASSES {
+                    rq.respond_with_int(self.estimator_passes.load(Ordering::Relaxed));
+                } else if debug::path(rq.url()) == debug::SERVER_STATS {
+                    rq.respond_with_string(self.server_stats());
+                } else if debug::path(rq.url()) == debug::METRICS {
+                    let reset = debug::has_query_param(rq.url(), "reset");
+                    rq.respond_with_string(self.metrics.report(reset));
+                } else if debug::path(rq.url()) == debug::ECHO {
+                    let headers = rq.headers();
+                    let lines: Vec<_> = headers
+                        .iter()
+                        .map(|(name, value)| format!("{name}: {value}"))
+                        .collect();
+                    rq.respond_with_string(lines.join("\n"));
+                } else if debug::path(rq.url()) == debug::DB_IDS {
+                    let ids = self.database.lock().available_ids();
+                    rq.respond_with_int_list(&ids);
+                } else if debug::path(rq.url()) == debug::SLEEP && !self.allow_debug_sleep {
+                    rq.respond_with_err("Our error: Debug sleep not allowed.");
+                } else if debug::path(rq.url()) == debug::ORPHAN_TICKET && !self.allow_debug_orphan
+                {
+                    rq.respond_with_err("Our error: Debug orphan ticket not allowed.");
+                } else if debug::is_server_command(rq.url()) {
+                    // Some debugging commands are processed by a server
+                    self.forward(rq);
+                } else {
+                    rq.respond_with_string("Happy Debugging! 🚫🐛");
+                }
+            }
+            _ => self.forward(rq),
         }
     }
 
     /// Shut down the system
     fn shutdown(self) {
-        // Tell the estimator to shut down
-        let _ = self.estimator_shutdown_sender.send(());
-        // Wait for it to finish
-        self.estimator_thread.join().unwrap();
-        // Tell servers to shut down
-        self.coordinator.lock().shutdown();
+        self.shutdown_report().warn_if_inconsistent();
     }
 }
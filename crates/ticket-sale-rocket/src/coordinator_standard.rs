@@ -1,29 +1,85 @@
 //! Implementation of the standard coordinator
 
-use std::collections::HashMap;
-use std::sync::Arc;
+#![allow(clippy::too_many_arguments)]
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Arc};
 use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 
-use crossbeam::channel::{unbounded, Receiver, Sender};
+use crossbeam::channel::{bounded, unbounded, Receiver, Sender};
 use parking_lot::Mutex;
-use rand::Rng;
-use ticket_sale_core::Request;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use ticket_sale_core::{AllocationStrategy, Request, ScaleDownPolicy, ServerSelection};
 use uuid::Uuid;
 
 use super::database::Database;
 use super::enums::EstimatorServerStatus;
 use super::enums::HighPriorityServerRequest;
+use super::enums::ServerState;
+use super::routing::hashed_server_index;
 use super::server_standard::ServerStandard;
+use super::snapshot::ServerSnapshot;
+/// Number of servers started while holding the coordinator lock when scaling up in
+/// batches
+const SCALE_BATCH: u32 = 16;
+
+/// Time a scale request waits at most for the previous one to settle
+const SETTLE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Interval for checking whether the previous scale request settled
+const SETTLE_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
 /// Coordinator orchestrating all the components of the system
 pub struct CoordinatorStandard {
     database: Arc<Mutex<Database>>,
 
+    /// Upper bound for the tickets that can still be sold, see
+    /// [`Database::max_available`]
+    max_available: Arc<AtomicU32>,
+
     /// The reservation timeout
     reservation_timeout: u32,
 
+    /// Maximal number of seconds randomly added to the timeout of each reservation
+    timeout_jitter: u32,
+
+    /// Time in seconds servers remember completed buys, to answer retried ones
+    idempotency_window: u32,
+
+    /// Bounds for the number of non-terminating servers
+    min_servers: u32,
+    max_servers: u32,
+
+    /// Strategy for picking the servers to deactivate when scaling down
+    scale_down_policy: ScaleDownPolicy,
+
+    /// Strategy for picking the server of a customer without one
+    server_selection: ServerSelection,
+
+    /// Strategy for deciding how many tickets a server allocates at once
+    allocation_strategy: AllocationStrategy,
+
+    /// Maximal number of tickets a server allocates at once
+    max_batch: u32,
+
+    /// Whether the estimator runs, otherwise the servers count the database instead
+    estimator_enabled: bool,
+
+    /// Maximal number of requests waiting in the low priority channel of a server
+    server_queue_capacity: u32,
+
     /// Number of non-terminating servers
     pub no_active_servers: u32,
 
+    /// Snapshot of the non-terminating servers, updated after scaling
+    active_servers: Arc<ServerSnapshot>,
+
+    /// Servers deactivated without reservations by a scale request, which terminate
+    /// shortly but did not report their termination yet
+    settling_servers: HashSet<Uuid>,
+
     /// Map between the id of a server and its index in the lists
     pub map_id_index: HashMap<Uuid, usize>,
 
@@ -34,15 +90,24 @@ pub struct CoordinatorStandard {
     high_priority_sender_list: Vec<Sender<HighPriorityServerRequest>>,
     thread_list: Vec<JoinHandle<()>>,
 
+    /// Number of reservations of each server, kept up to date by the servers
+    reservations_list: Vec<Arc<AtomicU32>>,
+
     /// Channel for notifying the coordinator of each server's termination
     coordinator_terminated_sender: Sender<Uuid>,
     coordinator_terminated_receiver: Receiver<Uuid>,
 
     /// Sender for servers to send their number of tickets to the estimator
-    estimator_tickets_sender: Sender<u32>,
+    estimator_tickets_sender: Sender<(Uuid, u32)>,
 
     /// Sender for servers to notify the estimator of their activation/termination
     estimator_scaling_sender: Sender<EstimatorServerStatus>,
+
+    /// Generator for the server ids if they should be reproducible
+    server_id_rng: Option<StdRng>,
+
+    /// Sender for servers to notify of the customers whose reservations expired
+    expiry_sender: Option<mpsc::Sender<Uuid>>,
 }
 
 impl CoordinatorStandard {
@@ -50,23 +115,52 @@ impl CoordinatorStandard {
     pub fn new(
         database: Arc<Mutex<Database>>,
         reservation_timeout: u32,
-        estimator_tickets_sender: Sender<u32>,
+        timeout_jitter: u32,
+        idempotency_window: u32,
+        min_servers: u32,
+        max_servers: u32,
+        scale_down_policy: ScaleDownPolicy,
+        server_selection: ServerSelection,
+        allocation_strategy: AllocationStrategy,
+        max_batch: u32,
+        estimator_enabled: bool,
+        server_queue_capacity: u32,
+        estimator_tickets_sender: Sender<(Uuid, u32)>,
         estimator_scaling_sender: Sender<EstimatorServerStatus>,
+        server_id_seed: Option<u64>,
+        expiry_sender: Option<mpsc::Sender<Uuid>>,
     ) -> Self {
         let (coordinator_terminated_sender, coordinator_terminated_receiver) = unbounded();
+        let max_available = database.lock().max_available();
         Self {
             database,
+            max_available,
             reservation_timeout,
+            timeout_jitter,
+            idempotency_window,
+            min_servers,
+            max_servers,
+            scale_down_policy,
+            server_selection,
+            allocation_strategy,
+            max_batch,
+            estimator_enabled,
+            server_queue_capacity,
             no_active_servers: 0,
+            active_servers: Arc::new(ServerSnapshot::default()),
+            settling_servers: HashSet::new(),
             map_id_index: HashMap::new(),
             server_id_list: Vec::new(),
             low_priority_sender_list: Vec::new(),
             high_priority_sender_list: Vec::new(),
             thread_list: Vec::new(),
+            reservations_list: Vec::new(),
             coordinator_terminated_sender,
             coordinator_terminated_receiver,
             estimator_tickets_sender,
             estimator_scaling_sender,
+            server_id_rng: server_id_seed.map(StdRng::seed_from_u64),
+            expiry_sender,
         }
     }
 
@@ -80,10 +174,70 @@ impl CoordinatorStandard {
         &self.server_id_list[0..self.no_active_servers as usize]
     }
 
+    /// Get ids corresponding to terminating servers that were not removed yet
+    ///
+    /// Call [`Self::update_servers`] first to remove the terminated servers.
+    pub fn get_terminating_servers(&self) -> Vec<Uuid> {
+        self.server_id_list[self.no_active_servers as usize..].to_vec()
+    }
+
+    /// Get the snapshot of the non-terminating servers
+    pub fn get_active_servers_snapshot(&self) -> Arc<ServerSnapshot> {
+        self.active_servers.clone()
+    }
+
+    /// Whether the server with the given id is non-terminating
+    pub fn is_active(&self, id: Uuid) -> bool {
+        self.map_id_index
+            .get(&id)
+            .is_some_and(|&index| index < self.no_active_servers as usize)
+    }
+
+    /// Whether the server with the given id exists, i.e., is non-terminating or
+    /// terminating but not yet terminated
+    pub fn server_exists(&self, id: Uuid) -> bool {
+        self.map_id_index.contains_key(&id)
+    }
+
     /// Get the id of a random non-terminating server
-    pub fn get_random_server(&self) -> Uuid {
+    ///
+    /// Returns `None` if the system is scaled to zero servers.
+    pub fn get_random_server(&self) -> Option<Uuid> {
+        if self.no_active_servers == 0 {
+            return None;
+        }
         let mut rng = rand::thread_rng();
-        self.server_id_list[rng.gen_range(0..self.no_active_servers) as usize]
+        Some(self.server_id_list[rng.gen_range(0..self.no_active_servers) as usize])
+    }
+
+    /// Get the id of the non-terminating server with the fewest reservations
+    ///
+    /// Returns `None` if the system is scaled to zero servers.
+    pub fn get_least_loaded_server(&self) -> Option<Uuid> {
+        (0..self.no_active_servers as usize)
+            .min_by_key(|&i| self.reservations_list[i].load(Ordering::Relaxed))
+            .map(|i| self.server_id_list[i])
+    }
+
+    /// Get the id of the non-terminating server the given customer is routed to by
+    /// consistent hashing
+    ///
+    /// Returns `None` if the system is scaled to zero servers.
+    pub fn get_hashed_server(&self, customer: Uuid) -> Option<Uuid> {
+        let index = hashed_server_index(customer, self.get_active_servers())?;
+        Some(self.server_id_list[index])
+    }
+
+    /// Get the id of a non-terminating server for the given customer picked by the
+    /// server selection strategy
+    ///
+    /// Returns `None` if the system is scaled to zero servers.
+    pub fn select_server(&self, customer: Uuid) -> Option<Uuid> {
+        match self.server_selection {
+            ServerSelection::Random => self.get_random_server(),
+            ServerSelection::LeastLoaded => self.get_least_loaded_server(),
+            ServerSelection::CustomerHash => self.get_hashed_server(customer),
+        }
     }
 
     /// Get the channel for sending user requests to the server with the given id
@@ -100,33 +254,112 @@ impl CoordinatorStandard {
         // While there is a server that just terminated
         while let Ok(uuid) = self.coordinator_terminated_receiver.try_recv() {
             // Find its position in the lists
-            let index = *self.map_id_index.get(&uuid).unwrap();
+            let mut index = *self.map_id_index.get(&uuid).unwrap();
             let n = self.server_id_list.len();
 
-            // If it is not the last one, swap it with the last one
-            if index != n - 1 {
-                self.server_id_list.swap(index, n - 1);
-                self.low_priority_sender_list.swap(index, n - 1);
-                self.high_priority_sender_list.swap(index, n - 1);
-                self.thread_list.swap(index, n - 1);
-                // Update the index of the swapped server
-                *self
-                    .map_id_index
-                    .get_mut(&self.server_id_list[index])
-                    .unwrap() = index;
+            // The server may have terminated right before it was activated again, so it
+            // is no longer non-terminating
+            if index < self.no_active_servers as usize {
+                self.no_active_servers -= 1;
+                self.swap_servers(index, self.no_active_servers as usize);
+                index = self.no_active_servers as usize;
+                self.active_servers.set(self.get_active_servers());
             }
 
+            // If it is not the last one, swap it with the last one
+            self.swap_servers(index, n - 1);
+
             // Remove the last server
             self.server_id_list.pop();
             self.low_priority_sender_list.pop();
             self.high_priority_sender_list.pop();
             self.thread_list.pop();
+            self.reservations_list.pop();
             self.map_id_index.remove(&uuid);
+            self.settling_servers.remove(&uuid);
+        }
+
+        // A server that got a reservation before processing its deactivation stays
+        // terminating until the reservation is cleared, so it no longer settles
+        self.settling_servers.retain(|id| {
+            let index = self.map_id_index[id];
+            self.reservations_list[index].load(Ordering::Relaxed) == 0
+        });
+    }
+
+    /// Get the number of servers deactivated by a previous scale request that did
+    /// not terminate yet
+    pub fn get_num_settling_servers(&mut self) -> u32 {
+        // Remove terminated servers
+        self.update_servers();
+
+        self.settling_servers.len() as u32
+    }
+
+    /// Swap the servers at the given indices in the lists
+    fn swap_servers(&mut self, i: usize, j: usize) {
+        if i == j {
+            return;
+        }
+        self.server_id_list.swap(i, j);
+        self.low_priority_sender_list.swap(i, j);
+        self.high_priority_sender_list.swap(i, j);
+        self.thread_list.swap(i, j);
+        self.reservations_list.swap(i, j);
+        // Update the indices of the swapped servers
+        *self.map_id_index.get_mut(&self.server_id_list[i]).unwrap() = i;
+        *self.map_id_index.get_mut(&self.server_id_list[j]).unwrap() = j;
+    }
+
+    /// Scale to the given number of servers, releasing the lock of `coordinator`
+    /// after every [`SCALE_BATCH`] started servers
+    ///
+    /// Unlike [`Self::scale_to`], requests can still be routed while many servers are
+    /// started. The number of servers actually scaled to is returned.
+    ///
+    /// Concurrent scale requests queue: before scaling, this waits until the servers
+    /// deactivated by previous ones have terminated. If they do not terminate within
+    /// [`SETTLE_TIMEOUT`], nothing is scaled and [`None`] is returned.
+    pub fn scale_to_in_batches(
+        coordinator: &Arc<Mutex<CoordinatorStandard>>,
+        num_servers: u32,
+    ) -> Option<u32> {
+        let start = Instant::now();
+        loop {
+            let mut guard = coordinator.lock();
+
+            // Wait without holding the lock, terminating servers need it
+            if guard.get_num_settling_servers() > 0 {
+                drop(guard);
+                if start.elapsed() > SETTLE_TIMEOUT {
+                    return None;
+                }
+                thread::sleep(SETTLE_POLL_INTERVAL);
+                continue;
+            }
+
+            let num_servers = num_servers.clamp(guard.min_servers, guard.max_servers);
+
+            // Start at most a batch of servers, scaling down happens at once
+            let batch = num_servers.min(guard.no_active_servers + SCALE_BATCH);
+            guard.scale_to(batch, coordinator.clone());
+            if batch == num_servers {
+                return Some(num_servers);
+            }
         }
     }
 
     /// Scale to the given number of servers
-    pub fn scale_to(&mut self, num_servers: u32, coordinator: Arc<Mutex<CoordinatorStandard>>) {
+    ///
+    /// The number is clamped to the configured bounds, the number of servers actually
+    /// scaled to is returned.
+    pub fn scale_to(
+        &mut self,
+        num_servers: u32,
+        coordinator: Arc<Mutex<CoordinatorStandard>>,
+    ) -> u32 {
+        let num_servers = num_servers.clamp(self.min_servers, self.max_servers);
+
         // Remove terminated servers
         self.update_servers();
 
@@ -139,6 +372,8 @@ impl CoordinatorStandard {
                 // Get the channel for the server activation and activate the server
                 let _ = self.high_priority_sender_list[self.no_active_servers as usize]
                     .send(HighPriorityServerRequest::Activate);
+                self.settling_servers
+                    .remove(&self.server_id_list[self.no_active_servers as usize]);
 
                 // Notify the estimator of the server activation
                 let _ = self
@@ -154,65 +389,262 @@ impl CoordinatorStandard {
 
             // We need to add more servers
             while self.no_active_servers < num_servers {
-                // Create channels for the new server
-                let (low_priority_sender, low_priority_receiver) = unbounded();
-                let (high_priority_sender, high_priority_receiver) = unbounded();
-
-                // Create the server
-                let mut server = ServerStandard::new(
-                    self.database.clone(),
-                    coordinator.clone(),
-                    self.reservation_timeout,
-                    low_priority_receiver,
-                    high_priority_receiver,
-                    self.coordinator_terminated_sender.clone(),
-                    self.estimator_tickets_sender.clone(),
-                    self.estimator_scaling_sender.clone(),
-                );
-                let server_id = server.id;
-
-                // Start the server
-                self.thread_list.push(thread::spawn(move || server.run()));
-
-                // Add everything to the lists
-                self.server_id_list.push(server_id);
-                self.low_priority_sender_list.push(low_priority_sender);
-                self.high_priority_sender_list.push(high_priority_sender);
-                self.map_id_index
-                    .insert(server_id, self.no_active_servers as usize);
-
-                // Notify the estimator of the server activation
-                let _ = self
-                    .estimator_scaling_sender
-                    .send(EstimatorServerStatus::Activated {
-                        server: self.server_id_list[self.no_active_servers as usize],
-                        sender: self.high_priority_sender_list[self.no_active_servers as usize]
-                            .clone(),
-                    });
-                self.no_active_servers += 1;
+                self.start_server(coordinator.clone(), None);
             }
         }
 
         // We need to deactivate servers
         if self.no_active_servers > num_servers {
             while self.no_active_servers > num_servers {
+                // With the least loaded policy, move the non-terminating server with the
+                // fewest reservations to the end of the non-terminating ones
+                if self.scale_down_policy == ScaleDownPolicy::LeastLoaded {
+                    let last = (self.no_active_servers - 1) as usize;
+                    let least_loaded = (0..=last)
+                        .min_by_key(|&i| self.reservations_list[i].load(Ordering::Relaxed))
+                        .unwrap();
+                    self.swap_servers(least_loaded, last);
+                }
+
                 // Get the channel for the server deactivation and deactivate the server
-                let _ = self.high_priority_sender_list[(self.no_active_servers - 1) as usize]
+                let last = (self.no_active_servers - 1) as usize;
+                let _ = self.high_priority_sender_list[last]
                     .send(HighPriorityServerRequest::Deactivate);
 
+                // Without reservations, the server terminates right away
+                if self.reservations_list[last].load(Ordering::Relaxed) == 0 {
+                    self.settling_servers.insert(self.server_id_list[last]);
+                }
+
                 self.no_active_servers -= 1;
             }
         }
+
+        // Publish the new set of non-terminating servers
+        self.active_servers.set(self.get_active_servers());
+
+        num_servers
     }
 
-    /// Shut down all servers
-    pub fn shutdown(&mut self) {
-        // Tell all servers to shut down
-        for sender in self.high_priority_sender_list.iter() {
-            let _ = sender.send(HighPriorityServerRequest::Shutdown);
+    /// Activate the terminating server with the given id again
+    ///
+    /// Returns `false` if there is no such server or the maximal number of servers is
+    /// reached.
+    pub fn activate_server(&mut self, id: Uuid) -> bool {
+        // Remove terminated servers
+        self.update_servers();
+
+        // Make sure the server is still terminating and we may activate it
+        let index = match self.map_id_index.get(&id) {
+            Some(&index) if index >= self.no_active_servers as usize => index,
+            _ => return false,
+        };
+        if self.no_active_servers >= self.max_servers {
+            return false;
+        }
+
+        // Move the server to the end of the non-terminating ones and activate it
+        let last = self.no_active_servers as usize;
+        self.swap_servers(index, last);
+        let _ = self.high_priority_sender_list[last].send(HighPriorityServerRequest::Activate);
+        self.settling_servers.remove(&id);
+
+        // Notify the estimator of the server activation
+        let _ = self
+            .estimator_scaling_sender
+            .send(EstimatorServerStatus::Activated {
+                server: id,
+                sender: self.high_priority_sender_list[last].clone(),
+            });
+        self.no_active_servers += 1;
+
+        // Publish the new set of non-terminating servers
+        self.active_servers.set(self.get_active_servers());
+
+        true
+    }
+
+    /// Drain the non-terminating server with the given id
+    ///
+    /// The server refuses new reservations but keeps its tickets until its
+    /// reservations are cleared, then it terminates. Returns `false` if there is no
+    /// such server or the minimal number of servers is reached.
+    pub fn drain_server(&mut self, id: Uuid) -> bool {
+        // Remove terminated servers
+        self.update_servers();
+
+        // Make sure the server is non-terminating and we may deactivate it
+        let index = match self.map_id_index.get(&id) {
+            Some(&index) if index < self.no_active_servers as usize => index,
+            _ => return false,
+        };
+        if self.no_active_servers <= self.min_servers {
+            return false;
+        }
+
+        // Move the server to the end of the non-terminating ones and drain it
+        let last = (self.no_active_servers - 1) as usize;
+        self.swap_servers(index, last);
+        let _ = self.high_priority_sender_list[last].send(HighPriorityServerRequest::Drain);
+        self.no_active_servers -= 1;
+
+        // Publish the new set of non-terminating servers
+        self.active_servers.set(self.get_active_servers());
+
+        true
+    }
+
+    /// Distribute the tickets of the database evenly to the non-terminating servers
+    ///
+    /// The servers allocate their share when processing the request. Returns the
+    /// number of tickets handed out.
+    pub fn warmup(&mut self) -> u32 {
+        // Remove terminated servers
+        self.update_servers();
+
+        let n = self.no_active_servers;
+        if n == 0 {
+            return 0;
         }
+        let available = self.database.lock().get_num_available();
+        for (i, sender) in self.high_priority_sender_list[0..n as usize]
+            .iter()
+            .enumerate()
+        {
+            // The first servers take the remainder
+            let count = available / n + u32::from((i as u32) < available % n);
+            let _ = sender.send(HighPriorityServerRequest::Prefill { count });
+        }
+        available
+    }
+
+    /// Start a new non-terminating server, restoring the given state if any
+    fn start_server(
+        &mut self,
+        coordinator: Arc<Mutex<CoordinatorStandard>>,
+        state: Option<ServerState>,
+    ) {
+        // Create channels for the new server
+        // The low priority channel is bounded, so a slow server cannot pile up requests
+        let (low_priority_sender, low_priority_receiver) =
+            bounded(self.server_queue_capacity as usize);
+        let (high_priority_sender, high_priority_receiver) = unbounded();
+        let reservations = Arc::new(AtomicU32::new(0));
+
+        // Create the server
+        let mut server = ServerStandard::new(
+            self.database.clone(),
+            self.max_available.clone(),
+            coordinator,
+            self.reservation_timeout,
+            self.timeout_jitter,
+            self.idempotency_window,
+            self.allocation_strategy,
+            self.max_batch,
+            self.estimator_enabled,
+            low_priority_receiver,
+            high_priority_receiver,
+            self.coordinator_terminated_sender.clone(),
+            self.estimator_tickets_sender.clone(),
+            self.estimator_scaling_sender.clone(),
+            reservations.clone(),
+            self.expiry_sender.clone(),
+        );
+        if let Some(rng) = &mut self.server_id_rng {
+            server.id = uuid::Builder::from_random_bytes(rng.gen()).into_uuid();
+        }
+        if let Some(state) = state {
+            server.restore(state);
+        }
+        let server_id = server.id;
+
+        // Start the server
+        self.thread_list.push(thread::spawn(move || server.run()));
+
+        // Add everything to the lists
+        self.server_id_list.push(server_id);
+        self.low_priority_sender_list.push(low_priority_sender);
+        self.high_priority_sender_list.push(high_priority_sender);
+        self.reservations_list.push(reservations);
+        self.map_id_index
+            .insert(server_id, self.no_active_servers as usize);
+
+        // Notify the estimator of the server activation
+        let _ = self
+            .estimator_scaling_sender
+            .send(EstimatorServerStatus::Activated {
+                server: self.server_id_list[self.no_active_servers as usize],
+                sender: self.high_priority_sender_list[self.no_active_servers as usize].clone(),
+            });
+        self.no_active_servers += 1;
+    }
+
+    /// Start a non-terminating server for each of the given restored states
+    pub fn restore(
+        &mut self,
+        servers: Vec<ServerState>,
+        coordinator: Arc<Mutex<CoordinatorStandard>>,
+    ) {
+        for state in servers {
+            self.start_server(coordinator.clone(), Some(state));
+        }
+
+        // Publish the new set of non-terminating servers
+        self.active_servers.set(self.get_active_servers());
+    }
+
+    /// Get the high priority senders of the non-terminating servers
+    pub fn get_active_high_priority_senders(&self) -> Vec<Sender<HighPriorityServerRequest>> {
+        self.high_priority_sender_list[0..self.no_active_servers as usize].to_vec()
+    }
+
+    /// Get the high priority senders of all servers, including terminating ones
+    pub fn get_high_priority_senders(&self) -> Vec<Sender<HighPriorityServerRequest>> {
+        self.high_priority_sender_list.clone()
+    }
+
+    /// Drain all non-terminating servers ahead of a shutdown
+    ///
+    /// Unlike [`Self::drain_server`], this ignores the minimal number of servers. The
+    /// servers refuse new reservations, but their customers can still buy or abort the
+    /// reserved tickets. Each server terminates once its reservations are cleared.
+    pub fn drain_all(&mut self) {
+        // Remove terminated servers
+        self.update_servers();
+
+        for sender in &self.high_priority_sender_list[..self.no_active_servers as usize] {
+            let _ = sender.send(HighPriorityServerRequest::Drain);
+        }
+        self.no_active_servers = 0;
+
+        // Publish the new set of non-terminating servers
+        self.active_servers.set(self.get_active_servers());
+    }
+
+    /// Get the number of servers, including the terminating ones, that did not
+    /// terminate yet
+    pub fn get_num_remaining_servers(&mut self) -> u32 {
+        // Remove terminated servers
+        self.update_servers();
+
+        self.server_id_list.len() as u32
+    }
+
+    /// Shut down all servers
+    ///
+    /// Waits for the servers without holding the lock, they may need it for the
+    /// requests they process before shutting down.
+    pub fn shutdown(coordinator: &Mutex<Self>) {
+        let threads = {
+            let mut coordinator = coordinator.lock();
+            // Tell all servers to shut down
+            for sender in coordinator.high_priority_sender_list.iter() {
+                let _ = sender.send(HighPriorityServerRequest::Shutdown);
+            }
+            std::mem::take(&mut coordinator.thread_list)
+        };
         // Wait for them to do so
-        for thread in self.thread_list.drain(..) {
+        for thread in threads {
             thread.join().unwrap();
         }
     }
@@ -1,17 +1,169 @@
 //! Implementation of the central database for tickets
 
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
 /// Implementation of the central database for tickets
-#[derive(Clone)]
 pub struct Database {
     /// List of available tickets that have not yet been allocated by any server
-    unallocated: Vec<u32>,
+    unallocated: VecDeque<u32>,
+
+    /// Whether [`Database::allocate`] hands out the lowest ids first
+    ordered: bool,
+
+    /// Total number of tickets
+    num_tickets: u32,
+
+    /// Number of tickets that have been sold
+    sold: u32,
+
+    /// Ids of the tickets that have been sold, which are never deallocated again
+    sold_tickets: HashSet<u32>,
+
+    /// Upper bound for the number of tickets that can still be sold, shared with the
+    /// servers so they can read it without locking the database
+    max_available: Arc<AtomicU32>,
+}
+
+impl Clone for Database {
+    fn clone(&self) -> Self {
+        Self {
+            unallocated: self.unallocated.clone(),
+            ordered: self.ordered,
+            num_tickets: self.num_tickets,
+            sold: self.sold,
+            sold_tickets: self.sold_tickets.clone(),
+            // The clone keeps its own bound, selling from it does not affect this one
+            max_available: Arc::new(AtomicU32::new(self.get_max_available())),
+        }
+    }
 }
 
 impl Database {
     /// Create a new [`Database`].
-    pub fn new(num_tickets: u32) -> Self {
-        let unallocated: Vec<u32> = (0..num_tickets).collect();
-        Self { unallocated }
+    ///
+    /// If `ordered` is set, [`Database::allocate`] behaves like
+    /// [`Database::allocate_ordered`].
+    pub fn new(num_tickets: u32, ordered: bool) -> Self {
+        let unallocated: VecDeque<u32> = (0..num_tickets).collect();
+        Self {
+            unallocated,
+            ordered,
+            num_tickets,
+            sold: 0,
+            sold_tickets: HashSet::new(),
+            max_available: Arc::new(AtomicU32::new(num_tickets)),
+        }
+    }
+
+    /// Restore a [`Database`] from a persisted state.
+    ///
+    /// The persisted state only contains the number of sold tickets, so tickets sold
+    /// before persisting are not guarded against being deallocated again.
+    #[cfg(feature = "persist")]
+    pub fn restore(num_tickets: u32, unallocated: Vec<u32>, sold: u32, ordered: bool) -> Self {
+        Self {
+            unallocated: unallocated.into(),
+            ordered,
+            num_tickets,
+            sold,
+            sold_tickets: HashSet::new(),
+            max_available: Arc::new(AtomicU32::new(num_tickets - sold)),
+        }
+    }
+
+    /// Write the state of the database to the file at `path`.
+    ///
+    /// The state is stored as text, one item per line:
+    ///
+    /// ```text
+    /// tickets <total number of tickets>
+    /// sold <number of sold tickets>
+    /// sold_tickets <ids of the sold tickets>
+    /// available <ids of the tickets in the database>
+    /// ```
+    ///
+    /// Tickets held by servers are not part of the state, so the database should be
+    /// written once the servers returned their tickets.
+    pub fn snapshot(&self, path: &Path) -> io::Result<()> {
+        let mut sold_tickets: Vec<u32> = self.sold_tickets.iter().copied().collect();
+        sold_tickets.sort_unstable();
+        let contents = format!(
+            "tickets {}\nsold {}\n{}{}",
+            self.num_tickets,
+            self.sold,
+            line("sold_tickets", sold_tickets.iter()),
+            line("available", self.unallocated.iter()),
+        );
+
+        // Write to a temporary file first, so a crash does not leave a partial state
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, contents)?;
+        fs::rename(&temporary, path)
+    }
+
+    /// Restore a [`Database`] from the file at `path` written by
+    /// [`Database::snapshot`].
+    ///
+    /// Returns [`None`] if the file does not exist and an error if it cannot be read
+    /// or is malformed.
+    pub fn restore_from(path: &Path, ordered: bool) -> io::Result<Option<Self>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err),
+        };
+        match Self::parse_state(&contents, ordered) {
+            Some(database) => Ok(Some(database)),
+            None => {
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "malformed state file",
+                ))
+            }
+        }
+    }
+
+    /// Parse the contents of a file written by [`Database::snapshot`]
+    fn parse_state(contents: &str, ordered: bool) -> Option<Self> {
+        let mut num_tickets = None;
+        let mut sold = None;
+        let mut sold_tickets = HashSet::new();
+        let mut unallocated = VecDeque::new();
+        for line in contents.lines() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("tickets") => num_tickets = Some(words.next()?.parse().ok()?),
+                Some("sold") => sold = Some(words.next()?.parse().ok()?),
+                Some("sold_tickets") => sold_tickets = parse_tickets(words)?,
+                Some("available") => unallocated = parse_tickets(words)?,
+                Some(_) => return None,
+                None => {}
+            }
+        }
+        let (num_tickets, sold) = (num_tickets?, sold?);
+        Some(Self {
+            unallocated,
+            ordered,
+            num_tickets,
+            sold,
+            sold_tickets,
+            max_available: Arc::new(AtomicU32::new(num_tickets.checked_sub(sold)?)),
+        })
+    }
+
+    /// Get the total number of tickets.
+    pub fn get_num_tickets(&self) -> u32 {
+        self.num_tickets
+    }
+
+    /// Get the tickets that have not yet been allocated by any server.
+    pub fn available_ids(&self) -> Vec<u32> {
+        self.unallocated.iter().copied().collect()
     }
 
     /// Get the number of available tickets.
@@ -19,26 +171,95 @@ impl Database {
         self.unallocated.len() as u32
     }
 
+    /// Get the number of sold tickets.
+    pub fn get_num_sold(&self) -> u32 {
+        self.sold
+    }
+
+    /// Get an upper bound for the number of tickets that can still be sold.
+    ///
+    /// This is the total number of tickets minus the ones already sold.
+    pub fn get_max_available(&self) -> u32 {
+        self.num_tickets - self.sold
+    }
+
+    /// Get the upper bound of [`Database::get_max_available`] as a shared counter,
+    /// which stays up to date and can be read without locking the database.
+    pub fn max_available(&self) -> Arc<AtomicU32> {
+        self.max_available.clone()
+    }
+
+    /// Record that the ticket with the given id has been sold.
+    pub fn mark_sold(&mut self, ticket: u32) {
+        self.sold += 1;
+        self.sold_tickets.insert(ticket);
+        self.max_available
+            .store(self.get_max_available(), Ordering::Relaxed);
+    }
+
     /// Allocate `num_tickets` many tickets.
     ///
     /// The tickets are removed from the database.
     pub fn allocate(&mut self, num_tickets: u32) -> Vec<u32> {
-        let mut tickets = Vec::with_capacity(num_tickets as usize);
+        if self.ordered {
+            return self.allocate_ordered(num_tickets);
+        }
 
         if num_tickets >= self.unallocated.len() as u32 {
-            return std::mem::take(&mut self.unallocated);
+            return std::mem::take(&mut self.unallocated).into();
         }
 
         let split = self.unallocated.len() - num_tickets as usize;
-        tickets.extend_from_slice(&self.unallocated[split..]);
-        self.unallocated.truncate(split);
-        tickets
+        self.unallocated.split_off(split).into()
+    }
+
+    /// Allocate `num_tickets` many tickets, taking the ones at the front first.
+    ///
+    /// On a fresh database, these are the lowest ids in ascending order. The tickets
+    /// are removed from the database.
+    pub fn allocate_ordered(&mut self, num_tickets: u32) -> Vec<u32> {
+        let num_tickets = (num_tickets as usize).min(self.unallocated.len());
+        self.unallocated.drain(..num_tickets).collect()
+    }
+
+    /// Allocate the ticket with the given id.
+    ///
+    /// Returns whether the ticket was available, in which case it is removed from the
+    /// database.
+    pub fn allocate_specific(&mut self, id: u32) -> bool {
+        match self.unallocated.iter().position(|&ticket| ticket == id) {
+            Some(index) => {
+                self.unallocated.remove(index);
+                true
+            }
+            None => false,
+        }
     }
 
     /// Deallocate `tickets`.
     ///
-    /// The tickets are added to the database.
+    /// The tickets are added to the database, except for sold ones.
     pub fn deallocate(&mut self, tickets: &[u32]) {
-        self.unallocated.extend_from_slice(tickets);
+        let sold_tickets = &self.sold_tickets;
+        self.unallocated.extend(
+            tickets
+                .iter()
+                .filter(|ticket| !sold_tickets.contains(ticket)),
+        );
+    }
+}
+
+/// Format a line with the given prefix followed by the tickets
+fn line<'a>(prefix: &str, tickets: impl Iterator<Item = &'a u32>) -> String {
+    let mut line = String::from(prefix);
+    for ticket in tickets {
+        line.push_str(&format!(" {ticket}"));
     }
+    line.push('\n');
+    line
+}
+
+/// Parse the ticket ids of a line
+fn parse_tickets<'a, C: FromIterator<u32>>(words: impl Iterator<Item = &'a str>) -> Option<C> {
+    words.map(|word| word.parse().ok()).collect()
 }
@@ -0,0 +1,59 @@
+//! URLs of the custom debugging commands
+
+/// Reserve the ticket at the position given in the payload of a server's ticket list
+/// (counted from the front)
+pub const RESERVE_POSITION: &str = "/api/debug/reserve_position";
+
+/// Deliberately lose one of a server's tickets (simulating a bug for testing the
+/// reconciliation on shutdown), only allowed if enabled in the configuration
+pub const ORPHAN_TICKET: &str = "/api/debug/orphan_ticket";
+
+/// Make a server sleep for the number of milliseconds given in the `ms` query parameter
+/// (simulating a slow server), only allowed if enabled in the configuration
+pub const SLEEP: &str = "/api/debug/sleep";
+
+/// Get the number of passes the estimator completed over all servers
+pub const ESTIMATOR_PASSES: &str = "/api/debug/estimator_passes";
+
+/// Get the ticket counts of each non-terminating server as JSON array
+pub const SERVER_STATS: &str = "/api/debug/server_stats";
+
+/// Get the ids of the tickets in the database, i.e., not allocated by any server
+pub const DB_IDS: &str = "/api/debug/db_ids";
+
+/// Get the latency histograms of the requests as plain text, cleared afterwards if
+/// the `reset` query parameter is given
+pub const METRICS: &str = "/api/debug/metrics";
+
+/// Reflect the headers of the request, one `<name>: <value>` line per header
+pub const ECHO: &str = "/api/debug/echo";
+
+/// Get the path of the given URL, i.e., without the query
+pub fn path(url: &str) -> &str {
+    url.split_once('?').map_or(url, |(path, _)| path)
+}
+
+/// Get the value of the query parameter `name` of the given URL
+pub fn query_param<'a>(url: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|param| param.split_once('='))
+        .find_map(|(key, value)| (key == name).then_some(value))
+}
+
+/// Whether the given URL has the query parameter `name`, with or without a value
+pub fn has_query_param(url: &str, name: &str) -> bool {
+    let Some((_, query)) = url.split_once('?') else {
+        return false;
+    };
+    query
+        .split('&')
+        .any(|param| param.split_once('=').map_or(param, |(key, _)| key) == name)
+}
+
+/// Whether the debugging command with the given URL needs to be processed by a server
+pub fn is_server_command(url: &str) -> bool {
+    let path = path(url);
+    path == RESERVE_POSITION || path == ORPHAN_TICKET || path == SLEEP
+}
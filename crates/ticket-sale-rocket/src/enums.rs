@@ -11,17 +11,106 @@ pub enum EstimatorServerStatus {
     },
 }
 
+/// Transition of a customer's session on a bonus server
+pub enum UserSessionStatus {
+    Activated,
+    Deactivated,
+}
+
 pub enum HighPriorityServerRequest {
     Activate,
     Deactivate,
+    /// Refuse new reservations and deactivate once the reservations are cleared
+    Drain,
     Shutdown,
-    Estimate { tickets: u32 },
+    /// Allocate the given number of tickets from the database ahead of the first
+    /// reservations
+    Prefill {
+        count: u32,
+    },
+    Estimate {
+        tickets: u32,
+    },
+    /// Send the server's ticket counts for the debugging statistics
+    ReportStats {
+        sender: Sender<ServerStats>,
+    },
+    /// Send the server's number of reserved tickets
+    ReportReserved {
+        sender: Sender<u32>,
+    },
+    /// Send the server's number of non-reserved tickets
+    ReportAvailable {
+        sender: Sender<u32>,
+    },
+    /// Abort all reservations, returning their tickets to the database, and send
+    /// the number of released tickets
+    ClearReservations {
+        sender: Sender<u32>,
+    },
+    /// Send the server's id along with its number of reserved tickets
+    ReportLoad {
+        sender: Sender<(Uuid, u32)>,
+    },
+    /// Send the server's state for persisting it
+    #[cfg(feature = "persist")]
+    Persist {
+        sender: Sender<ServerState>,
+    },
+}
+
+/// Ticket counts of a server, reported for debugging
+#[derive(Clone, Copy, Debug)]
+pub struct ServerStats {
+    /// The server's ID
+    pub id: Uuid,
+    /// Number of non-reserved tickets
+    pub available: u32,
+    /// Number of reserved tickets
+    pub reserved: u32,
+    /// Estimate of the tickets in the other servers
+    pub estimate: u32,
+    /// Number of customers whose recent requests the server counts (only bonus
+    /// servers count them)
+    pub customers: u32,
+}
+
+impl ServerStats {
+    /// Format the counts as JSON object
+    pub fn to_json(self) -> String {
+        format!(
+            "{{\"server_id\":\"{}\",\"available\":{},\"reserved\":{},\"estimate\":{},\"customers\":{}}}",
+            self.id, self.available, self.reserved, self.estimate, self.customers
+        )
+    }
+}
+
+/// State of a server, used for persisting and restoring the system
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServerState {
+    /// The server's ID
+    pub id: Uuid,
+    /// Non-reserved tickets
+    pub tickets: Vec<u32>,
+    /// Reserved tickets of each customer
+    pub reservations: Vec<(Uuid, Vec<u32>)>,
 }
 
 #[derive(PartialEq)]
 pub enum ServerStatus {
     Active,
+    Draining,
     Terminating,
     Terminated,
     Shutdown,
 }
+
+/// Selection of the ticket to reserve
+pub enum TicketSelection {
+    /// The last of the server's non-reserved tickets
+    Last,
+    /// The ticket at the given position (counted from the front)
+    Position(usize),
+    /// The ticket with the given id
+    Id(u32),
+}
@@ -1,5 +1,8 @@
 //! Implementation of the bonus estimator
 
+#![allow(clippy::too_many_arguments)]
+
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{mpsc, Arc};
 use std::{collections::HashMap, time::Duration};
 
@@ -18,20 +21,29 @@ pub struct EstimatorBonus {
     /// Number of seconds each loop should take
     roundtrip_secs: u32,
 
-    /// Number of tickets known to be in each server
-    server_tickets: HashMap<Uuid, u32>,
+    /// Weight of the latest measurement in the smoothed ticket counts
+    smoothing: f64,
+
+    /// Whether all servers are polled at once instead of one after another
+    parallel: bool,
+
+    /// Number of tickets known to be in each server, exponentially smoothed
+    server_tickets: HashMap<Uuid, f64>,
 
     /// High priority senders for each server
     server_senders: HashMap<Uuid, Sender<HighPriorityServerRequest>>,
 
-    /// Receiver for receiving the number of tickets from each server
-    estimator_tickets_receiver: Receiver<u32>,
+    /// Receiver for receiving the number of tickets from each server along with its id
+    estimator_tickets_receiver: Receiver<(Uuid, u32)>,
 
     /// Receiver for being notified of each server's activation/termination
     estimator_scaling_receiver: Receiver<EstimatorServerStatus>,
 
     /// Receiver for being told to shut down
     estimator_shutdown_receiver: mpsc::Receiver<()>,
+
+    /// Number of completed passes over all servers
+    passes: Arc<AtomicU32>,
 }
 
 impl EstimatorBonus {
@@ -39,18 +51,24 @@ impl EstimatorBonus {
     pub fn new(
         database: Arc<Mutex<Database>>,
         roundtrip_secs: u32,
-        estimator_tickets_receiver: Receiver<u32>,
+        smoothing: f64,
+        parallel: bool,
+        estimator_tickets_receiver: Receiver<(Uuid, u32)>,
         estimator_scaling_receiver: Receiver<EstimatorServerStatus>,
         estimator_shutdown_receiver: mpsc::Receiver<()>,
+        passes: Arc<AtomicU32>,
     ) -> Self {
         Self {
             database,
             roundtrip_secs,
+            smoothing,
+            parallel,
             server_tickets: HashMap::new(),
             server_senders: HashMap::new(),
             estimator_tickets_receiver,
             estimator_scaling_receiver,
             estimator_shutdown_receiver,
+            passes,
         }
     }
 
@@ -65,7 +83,7 @@ impl EstimatorBonus {
                     EstimatorServerStatus::Activated { server, sender } => {
                         // Add the newly activated server
                         self.server_senders.insert(server, sender);
-                        self.server_tickets.insert(server, 0);
+                        self.server_tickets.insert(server, 0.0);
                     }
                     EstimatorServerStatus::Deactivated { server } => {
                         // Remove the newly terminated server
@@ -75,20 +93,43 @@ impl EstimatorBonus {
                 }
             }
 
+            // Without servers there is nothing to estimate, so wait for the whole
+            // roundtrip time, but stop if shutdown signal is received
+            if self.server_senders.is_empty() {
+                if self
+                    .estimator_shutdown_receiver
+                    .recv_timeout(Duration::from_secs(self.roundtrip_secs as u64))
+                    .is_ok()
+                {
+                    break;
+                }
+                self.passes.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+
             // Get the number of tickets in the database
             let tickets = self.database.lock().get_num_available();
 
-            // Calculate the sleep time between servers
-            let time_seconds = (self.roundtrip_secs as f64) / (self.server_senders.len() as f64);
-            let time_miliseconds = (time_seconds * 1000f64).floor() as u64;
-
             // Calculate the total number of tickets known to be in the servers from previous
             // iterations
-            let mut sum = 0;
-            for (_, tickets) in &self.server_tickets {
+            let mut sum = 0.0;
+            for tickets in self.server_tickets.values() {
                 sum += tickets;
             }
 
+            // Poll all servers at once if configured, otherwise one after another
+            if self.parallel {
+                if self.poll_parallel(tickets, sum) {
+                    break;
+                }
+                self.passes.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+
+            // Calculate the sleep time between servers
+            let time_seconds = (self.roundtrip_secs as f64) / (self.server_senders.len() as f64);
+            let time_miliseconds = (time_seconds * 1000f64).floor() as u64;
+
             // Current iteration loop
             for (server, sender) in &self.server_senders {
                 // Make sum the number of tickets known to be in the other servers
@@ -96,18 +137,21 @@ impl EstimatorBonus {
 
                 // Send the number of tickets in the other servers + the database
                 let aux = sender.send(HighPriorityServerRequest::Estimate {
-                    tickets: sum + tickets,
+                    tickets: sum.round() as u32 + tickets,
                 });
+                let estimate = self.server_tickets.get_mut(server).unwrap();
                 match aux {
                     Ok(_) => {
                         // Message was sent => server not terminated => wait for response
-                        *self.server_tickets.get_mut(server).unwrap() =
-                            self.estimator_tickets_receiver.recv().unwrap();
+                        // and smooth it into the estimate
+                        let (_, measured) = self.estimator_tickets_receiver.recv().unwrap();
+                        let measured = measured as f64;
+                        *estimate = self.smoothing * measured + (1.0 - self.smoothing) * *estimate;
                     }
                     Err(_) => {
                         // Message not sent => server terminated mid loop =>
                         // it should've cleared all tickets so it has 0 left
-                        *self.server_tickets.get_mut(server).unwrap() = 0;
+                        *estimate = 0.0;
                     }
                 }
 
@@ -129,6 +173,43 @@ impl EstimatorBonus {
             if stop {
                 break;
             }
+            self.passes.fetch_add(1, Ordering::Relaxed);
         }
     }
+
+    /// Poll all servers at once, returning whether the estimator needs to shut down
+    ///
+    /// Every server is sent the number of tickets in the other servers as known
+    /// before this pass, then the replies of all servers are collected. Afterwards,
+    /// the estimator waits for the whole roundtrip time.
+    fn poll_parallel(&mut self, tickets: u32, sum: f64) -> bool {
+        let mut pending = 0;
+        for (server, sender) in &self.server_senders {
+            // Send the number of tickets in the other servers + the database
+            let others = sum - self.server_tickets[server];
+            let aux = sender.send(HighPriorityServerRequest::Estimate {
+                tickets: others.round() as u32 + tickets,
+            });
+            match aux {
+                // Message was sent => server not terminated => expect a response
+                Ok(_) => pending += 1,
+                // Message not sent => server terminated mid loop =>
+                // it should've cleared all tickets so it has 0 left
+                Err(_) => *self.server_tickets.get_mut(server).unwrap() = 0.0,
+            }
+        }
+
+        // Collect the responses and smooth them into the estimates
+        for _ in 0..pending {
+            let (server, measured) = self.estimator_tickets_receiver.recv().unwrap();
+            if let Some(estimate) = self.server_tickets.get_mut(&server) {
+                *estimate = self.smoothing * measured as f64 + (1.0 - self.smoothing) * *estimate;
+            }
+        }
+
+        // Wait for the roundtrip time, but stop if shutdown signal is received
+        self.estimator_shutdown_receiver
+            .recv_timeout(Duration::from_secs(self.roundtrip_secs as u64))
+            .is_ok()
+    }
 }
@@ -0,0 +1,46 @@
+//! Cache of recently completed buys, so retried buy requests succeed again
+
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use super::window::WindowCounter;
+
+/// Maximal number of buys a server remembers, older ones are forgotten early
+const MAX_RECENT_BUYS: usize = 10_000;
+
+/// Buys a server completed within the last `window`
+///
+/// A browser retrying a buy request may send it again after the first one already
+/// bought the ticket. The server then answers the duplicate with the original
+/// success instead of reporting a missing reservation.
+pub struct RecentBuys {
+    /// Whether the cache is enabled, i.e., the window is not zero
+    enabled: bool,
+
+    /// Remembered buys as (customer id, ticket id)
+    buys: WindowCounter<(Uuid, u32)>,
+}
+
+impl RecentBuys {
+    /// Create a new [`RecentBuys`] remembering buys for `window_secs` seconds
+    pub fn new(window_secs: u32) -> Self {
+        let window = Duration::from_secs(window_secs as u64);
+        Self {
+            enabled: window_secs > 0,
+            buys: WindowCounter::new(window, MAX_RECENT_BUYS),
+        }
+    }
+
+    /// Remember that the customer bought the ticket
+    pub fn record(&mut self, customer: Uuid, ticket: u32) {
+        if self.enabled && !self.contains(customer, ticket) {
+            self.buys.record((customer, ticket));
+        }
+    }
+
+    /// Whether the customer bought the ticket within the window
+    pub fn contains(&mut self, customer: Uuid, ticket: u32) -> bool {
+        self.buys.count(&(customer, ticket)) > 0
+    }
+}
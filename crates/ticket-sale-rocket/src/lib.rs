@@ -9,6 +9,11 @@
 //! communication.
 
 #![allow(rustdoc::private_intra_doc_links)]
+#[cfg(feature = "persist")]
+use std::io;
+#[cfg(feature = "persist")]
+use std::path::Path;
+use std::sync::atomic::AtomicU32;
 use std::sync::{mpsc, Arc};
 use std::thread;
 
@@ -18,7 +23,8 @@ use crossbeam::channel::unbounded;
 use estimator_bonus::EstimatorBonus;
 use estimator_standard::EstimatorStandard;
 use parking_lot::Mutex;
-use ticket_sale_core::Config;
+use ticket_sale_core::{Config, LaunchInfo};
+use uuid::Uuid;
 
 mod balancer;
 mod balancer_bonus;
@@ -26,16 +32,28 @@ mod balancer_standard;
 mod coordinator_bonus;
 mod coordinator_standard;
 mod database;
+mod debug;
 mod enums;
 mod estimator_bonus;
 mod estimator_standard;
+mod idempotency;
+mod metrics;
+#[cfg(feature = "persist")]
+mod persist;
+mod rate_limit;
+mod recent_requests;
+mod routing;
 mod server_bonus;
 mod server_standard;
+mod snapshot;
+mod window;
 
-pub use balancer::Balancer;
+pub use balancer::{Balancer, ShutdownReport};
 use coordinator_bonus::CoordinatorBonus;
 use coordinator_standard::CoordinatorStandard;
-use database::Database;
+pub use database::Database;
+use enums::ServerState;
+use rate_limit::RateLimiter;
 
 /// Entrypoint of your implementation
 ///
@@ -44,73 +62,222 @@ use database::Database;
 ///
 /// :warning: This functions must not be renamed and its signature must not be changed.
 pub fn launch(config: &Config) -> Balancer {
-    // Create the database
-    let database = Arc::new(Mutex::new(Database::new(config.tickets)));
+    // Restore the database from the state file, if there is one (callers should
+    // check the file beforehand with `Database::restore_from`, as starting from
+    // scratch instead could sell the sold tickets again)
+    let restored = config.state_path.as_deref().and_then(|path| {
+        Database::restore_from(path, config.ordered_allocation).unwrap_or_else(|err| {
+            eprintln!(
+                "Our warning: Could not restore the state from {}, starting without it: {err}",
+                path.display()
+            );
+            None
+        })
+    });
+    // Otherwise, create the database
+    let database =
+        restored.unwrap_or_else(|| Database::new(config.tickets, config.ordered_allocation));
+    launch_with_database(config, Arc::new(Mutex::new(database)))
+}
+
+/// Launch the ticket sales system with the given database instead of a new one
+///
+/// The database stays shared with the caller, e.g., to seed it with specific
+/// tickets beforehand or to inspect it after a run. The number of tickets is taken
+/// from the database, which is not restored from the state file of `config`.
+pub fn launch_with_database(config: &Config, database: Arc<Mutex<Database>>) -> Balancer {
+    let config = Config {
+        tickets: database.lock().get_num_tickets(),
+        ..config.clone()
+    };
+    launch_with(&config, database, Vec::new())
+}
+
+/// Launch the ticket sales system from a snapshot written by [`Balancer::persist`]
+///
+/// The number of tickets is taken from the snapshot, everything else from `config`.
+/// Each persisted server is restored with its id, tickets, and reservations.
+#[cfg(feature = "persist")]
+pub fn launch_from_snapshot(config: &Config, path: &Path) -> io::Result<Balancer> {
+    let state = persist::SystemState::read_from(path)?;
+    let config = Config {
+        tickets: state.tickets,
+        ..config.clone()
+    };
+    let database = Database::restore(
+        state.tickets,
+        state.available,
+        state.sold,
+        config.ordered_allocation,
+    );
+    Ok(launch_with(
+        &config,
+        Arc::new(Mutex::new(database)),
+        state.servers,
+    ))
+}
 
+/// Launch the ticket sales system with the given database and restored servers
+fn launch_with(
+    config: &Config,
+    database: Arc<Mutex<Database>>,
+    servers: Vec<ServerState>,
+) -> Balancer {
     // Create estimator channels
     let (estimator_tickets_sender, estimator_tickets_receiver) = unbounded();
     let (estimator_scaling_sender, estimator_scaling_receiver) = unbounded();
     let (estimator_shutdown_sender, estimator_shutdown_receiver) = mpsc::channel();
+    let estimator_passes = Arc::new(AtomicU32::new(0));
 
     if !config.bonus {
         // Create the coordinator and scale to initial number of servers
         let coordinator = Arc::new(Mutex::new(CoordinatorStandard::new(
             database.clone(),
             config.timeout,
+            config.timeout_jitter,
+            config.idempotency_window,
+            config.min_servers,
+            config.max_servers,
+            config.scale_down_policy,
+            config.server_selection,
+            config.allocation_strategy,
+            config.max_batch,
+            config.estimator_enabled,
+            config.server_queue_capacity,
             estimator_tickets_sender,
             estimator_scaling_sender,
+            config.server_id_seed,
+            config.expiry_sender.clone(),
         )));
+        coordinator.lock().restore(servers, coordinator.clone());
         coordinator
             .lock()
             .scale_to(config.initial_servers, coordinator.clone());
 
-        // Create the estimator and start it
-        let mut estimator = EstimatorStandard::new(
-            database.clone(),
-            config.estimator_roundtrip_time,
-            estimator_tickets_receiver,
-            estimator_scaling_receiver,
-            estimator_shutdown_receiver,
-        );
-        let estimator_thread = thread::spawn(move || {
-            estimator.run();
+        // Create the estimator and start it, unless it is disabled
+        let estimator_thread = config.estimator_enabled.then(|| {
+            let mut estimator = EstimatorStandard::new(
+                database.clone(),
+                config.estimator_roundtrip_time,
+                config.estimator_smoothing,
+                config.estimator_parallel,
+                estimator_tickets_receiver,
+                estimator_scaling_receiver,
+                estimator_shutdown_receiver,
+                estimator_passes.clone(),
+            );
+            thread::spawn(move || {
+                estimator.run();
+            })
         });
 
         // Create the standard balancer
-        let balancer_standard =
-            BalancerStandard::new(coordinator, estimator_shutdown_sender, estimator_thread);
+        let active_servers = coordinator.lock().get_active_servers_snapshot();
+        report_launch(config, active_servers.get().to_vec());
+        let balancer_standard = BalancerStandard::new(
+            coordinator,
+            active_servers,
+            database,
+            config.tickets,
+            config.timeout,
+            config.exact_below_servers,
+            config.allow_debug_sleep,
+            config.allow_debug_orphan,
+            config.admin_token.clone(),
+            config.routing_logger.clone(),
+            estimator_shutdown_sender,
+            estimator_thread,
+            estimator_passes,
+            config.state_path.clone(),
+        );
 
         // Create the balancer
         Balancer::new(Some(balancer_standard), None, false)
     } else {
         // Create the coordinator and scale to initial number of servers
+        let rate_limiter = Arc::new(RateLimiter::new(config.max_requests_per_customer));
         let coordinator = Arc::new(Mutex::new(CoordinatorBonus::new(
             database.clone(),
             config.timeout,
+            config.timeout_jitter,
+            config.idempotency_window,
+            config.min_servers,
+            config.max_servers,
+            config.scale_down_policy,
+            config.server_selection,
+            config.allocation_strategy,
+            config.max_batch,
+            config.estimator_enabled,
+            config.server_queue_capacity,
+            config.reassign_rate,
+            config.keep_server_after,
+            config.request_window,
+            rate_limiter.clone(),
             estimator_tickets_sender,
             estimator_scaling_sender,
+            config.server_id_seed,
+            config.expiry_sender.clone(),
         )));
+        coordinator.lock().restore(servers, coordinator.clone());
         coordinator
             .lock()
             .scale_to(config.initial_servers, coordinator.clone());
 
-        // Create the estimator and start it
-        let mut estimator = EstimatorBonus::new(
-            database.clone(),
-            config.estimator_roundtrip_time,
-            estimator_tickets_receiver,
-            estimator_scaling_receiver,
-            estimator_shutdown_receiver,
-        );
-        let estimator_thread = thread::spawn(move || {
-            estimator.run();
+        // Create the estimator and start it, unless it is disabled
+        let estimator_thread = config.estimator_enabled.then(|| {
+            let mut estimator = EstimatorBonus::new(
+                database.clone(),
+                config.estimator_roundtrip_time,
+                config.estimator_smoothing,
+                config.estimator_parallel,
+                estimator_tickets_receiver,
+                estimator_scaling_receiver,
+                estimator_shutdown_receiver,
+                estimator_passes.clone(),
+            );
+            thread::spawn(move || {
+                estimator.run();
+            })
         });
 
         // Create the bonus balancer
-        let balancer_bonus =
-            BalancerBonus::new(coordinator, estimator_shutdown_sender, estimator_thread);
+        let active_servers = coordinator.lock().get_active_servers_snapshot();
+        report_launch(config, active_servers.get().to_vec());
+        let balancer_bonus = BalancerBonus::new(
+            coordinator,
+            active_servers,
+            database,
+            config.tickets,
+            config.timeout,
+            config.exact_below_servers,
+            config.allow_debug_sleep,
+            config.allow_debug_orphan,
+            config.admin_token.clone(),
+            config.routing_logger.clone(),
+            estimator_shutdown_sender,
+            estimator_thread,
+            estimator_passes,
+            config.state_path.clone(),
+            rate_limiter,
+            config.session_affinity,
+            config.keep_server_after,
+            config.request_window,
+        );
 
         // Create the balancer
         Balancer::new(None, Some(balancer_bonus), true)
     }
 }
+
+/// Call the launch callback of `config`, if any, with a summary of the system
+fn report_launch(config: &Config, servers: Vec<Uuid>) {
+    if let Some(on_launch) = config.on_launch {
+        on_launch(&LaunchInfo {
+            tickets: config.tickets,
+            timeout: config.timeout,
+            estimator_roundtrip_time: config.estimator_roundtrip_time,
+            bonus: config.bonus,
+            servers,
+        });
+    }
+}
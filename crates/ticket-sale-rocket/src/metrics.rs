@@ -0,0 +1,101 @@
+//! Latency histograms of the requests handled by the balancer
+
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use ticket_sale_core::RequestKind;
+
+/// Number of buckets of a histogram
+///
+/// Bucket `i > 0` counts latencies in `[2^(i - 1), 2^i)` microseconds, bucket 0 the
+/// ones below a microsecond. The last bucket also counts all longer latencies.
+const BUCKETS: usize = 40;
+
+/// Latency histogram with power of two buckets
+struct Histogram {
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl Histogram {
+    /// Create a new empty [`Histogram`]
+    fn new() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+
+    /// Count the given latency
+    fn record(&self, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        let bucket = (u64::BITS - micros.leading_zeros()) as usize;
+        self.buckets[bucket.min(BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Get the counts of the buckets, resetting them to zero if `reset`
+    fn counts(&self, reset: bool) -> [u64; BUCKETS] {
+        std::array::from_fn(|i| {
+            if reset {
+                self.buckets[i].swap(0, Ordering::Relaxed)
+            } else {
+                self.buckets[i].load(Ordering::Relaxed)
+            }
+        })
+    }
+}
+
+/// Get the upper bound in microseconds of the bucket containing the given quantile
+fn quantile(counts: &[u64; BUCKETS], total: u64, quantile: f64) -> u64 {
+    let rank = (quantile * total as f64).ceil().max(1.0) as u64;
+    let mut seen = 0;
+    for (i, count) in counts.iter().enumerate() {
+        seen += count;
+        if seen >= rank {
+            return 1 << i;
+        }
+    }
+    1 << (BUCKETS - 1)
+}
+
+/// Latency histograms of each request kind, updated without locking
+pub struct Metrics {
+    /// Histogram of each request kind, indexed by its discriminant
+    histograms: Vec<Histogram>,
+}
+
+impl Metrics {
+    /// Create new [`Metrics`] without any recorded requests
+    pub fn new() -> Self {
+        Self {
+            histograms: RequestKind::ALL.iter().map(|_| Histogram::new()).collect(),
+        }
+    }
+
+    /// Record the time a request of the given kind took until its response
+    pub fn record(&self, kind: RequestKind, latency: Duration) {
+        self.histograms[kind as usize].record(latency);
+    }
+
+    /// Get the number of requests and the p50 and p99 latencies of each request kind
+    /// as plain text, one kind per line
+    ///
+    /// Kinds without requests are left out. The latencies are the upper bounds of
+    /// the buckets containing them. If `reset`, the histograms are cleared.
+    pub fn report(&self, reset: bool) -> String {
+        let mut report = String::new();
+        for kind in RequestKind::ALL {
+            let counts = self.histograms[kind as usize].counts(reset);
+            let total = counts.iter().sum();
+            if total == 0 {
+                continue;
+            }
+            let _ = writeln!(
+                report,
+                "{kind:?} count={total} p50<={}us p99<={}us",
+                quantile(&counts, total, 0.5),
+                quantile(&counts, total, 0.99),
+            );
+        }
+        report
+    }
+}
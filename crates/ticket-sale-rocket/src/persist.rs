@@ -0,0 +1,113 @@
+//! Persisting the state of the system to disk and restoring it
+//!
+//! The state is stored as text, one item per line:
+//!
+//! ```text
+//! tickets <total number of tickets>
+//! sold <number of sold tickets>
+//! available <ids of the tickets in the database>
+//! server <id> <ids of the non-reserved tickets>
+//! reservation <customer id> <ids of the reserved tickets>
+//! ```
+//!
+//! Reservations belong to the server preceding them.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use uuid::Uuid;
+
+use super::enums::ServerState;
+
+/// State of the whole system
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SystemState {
+    /// Number of initially available tickets
+    pub tickets: u32,
+    /// Number of sold tickets
+    pub sold: u32,
+    /// Tickets in the database
+    pub available: Vec<u32>,
+    /// State of each server
+    pub servers: Vec<ServerState>,
+}
+
+impl SystemState {
+    /// Write the state to the file at `path`
+    pub fn write_to(&self, path: &Path) -> io::Result<()> {
+        let mut contents = format!("tickets {}\nsold {}\n", self.tickets, self.sold);
+        contents.push_str(&line("available", &self.available));
+        for server in &self.servers {
+            contents.push_str(&line(&format!("server {}", server.id), &server.tickets));
+            for (customer, tickets) in &server.reservations {
+                contents.push_str(&line(&format!("reservation {customer}"), tickets));
+            }
+        }
+        fs::write(path, contents)
+    }
+
+    /// Read the state from the file at `path`
+    pub fn read_from(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut state = SystemState::default();
+        for line in contents.lines() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("tickets") => state.tickets = parse(words.next())?,
+                Some("sold") => state.sold = parse(words.next())?,
+                Some("available") => state.available = parse_tickets(words)?,
+                Some("server") => {
+                    let id = parse_uuid(words.next())?;
+                    state.servers.push(ServerState {
+                        id,
+                        tickets: parse_tickets(words)?,
+                        reservations: Vec::new(),
+                    });
+                }
+                Some("reservation") => {
+                    let customer = parse_uuid(words.next())?;
+                    let tickets = parse_tickets(words)?;
+                    match state.servers.last_mut() {
+                        Some(server) => server.reservations.push((customer, tickets)),
+                        None => return Err(invalid("reservation without a server")),
+                    }
+                }
+                Some(word) => return Err(invalid(&format!("unknown entry {word}"))),
+                None => {}
+            }
+        }
+        Ok(state)
+    }
+}
+
+/// Format a line with the given prefix followed by the tickets
+fn line(prefix: &str, tickets: &[u32]) -> String {
+    let mut line = String::from(prefix);
+    for ticket in tickets {
+        line.push_str(&format!(" {ticket}"));
+    }
+    line.push('\n');
+    line
+}
+
+fn parse(word: Option<&str>) -> io::Result<u32> {
+    word.and_then(|word| word.parse().ok())
+        .ok_or_else(|| invalid("expected a number"))
+}
+
+fn parse_uuid(word: Option<&str>) -> io::Result<Uuid> {
+    word.and_then(|word| word.parse().ok())
+        .ok_or_else(|| invalid("expected an id"))
+}
+
+fn parse_tickets<'a>(words: impl Iterator<Item = &'a str>) -> io::Result<Vec<u32>> {
+    words.map(|word| parse(Some(word))).collect()
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("malformed snapshot: {msg}"),
+    )
+}
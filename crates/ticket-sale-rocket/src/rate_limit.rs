@@ -0,0 +1,68 @@
+//! Per-customer rate limiting for the bonus implementation
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use uuid::Uuid;
+
+/// Counter of the requests of each customer
+///
+/// The balancer counts the requests of each customer without an active session and
+/// rejects them once there are too many. Servers report when a customer's session
+/// starts or ends, the count is reset once the customer has no session left, so
+/// customers actually buying tickets are not blocked permanently.
+pub struct RateLimiter {
+    /// Maximal number of requests per customer, 0 means unlimited
+    max_requests: u32,
+
+    /// Number of requests of each customer since their last session ended
+    requests: DashMap<Uuid, u32>,
+
+    /// Number of servers each customer has an active session on
+    sessions: DashMap<Uuid, u32>,
+}
+
+impl RateLimiter {
+    /// Create a new [`RateLimiter`]
+    pub fn new(max_requests: u32) -> Self {
+        Self {
+            max_requests,
+            requests: DashMap::new(),
+            sessions: DashMap::new(),
+        }
+    }
+
+    /// Count a request of the customer, returns whether it is allowed
+    ///
+    /// Requests of customers with an active session are always allowed and not
+    /// counted.
+    pub fn allow(&self, customer: Uuid) -> bool {
+        if self.max_requests == 0 || self.has_session(customer) {
+            return true;
+        }
+        let mut requests = self.requests.entry(customer).or_insert(0);
+        *requests += 1;
+        *requests <= self.max_requests
+    }
+
+    /// Whether the customer has an active session on any server
+    pub fn has_session(&self, customer: Uuid) -> bool {
+        self.sessions.contains_key(&customer)
+    }
+
+    /// Record that a session of the customer started on a server
+    pub fn session_started(&self, customer: Uuid) {
+        *self.sessions.entry(customer).or_insert(0) += 1;
+    }
+
+    /// Record that a session of the customer ended on a server, resetting the count
+    /// once the customer has no session left
+    pub fn session_ended(&self, customer: Uuid) {
+        if let Entry::Occupied(mut sessions) = self.sessions.entry(customer) {
+            *sessions.get_mut() -= 1;
+            if *sessions.get() == 0 {
+                sessions.remove();
+                self.requests.remove(&customer);
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! Sliding-window counter of the requests of each customer
+
+use std::time::Duration;
+
+use uuid::Uuid;
+
+use super::window::WindowCounter;
+
+/// Maximal number of requests a server counts, older ones are forgotten early
+const MAX_RECENT_REQUESTS: usize = 100_000;
+
+/// Requests of each customer a server processed within the last `window`
+///
+/// A bonus server (and the bonus balancer) keeps customers that sent many requests
+/// recently instead of handing them to a random server, so busy customers stay
+/// where their requests are cheap. Customers without a request within the window are
+/// forgotten, so the counter only grows with the customers that are currently active.
+pub struct RecentRequests(WindowCounter<Uuid>);
+
+impl RecentRequests {
+    /// Create a new [`RecentRequests`] counting requests for `window_secs` seconds
+    pub fn new(window_secs: u32) -> Self {
+        let window = Duration::from_secs(window_secs as u64);
+        Self(WindowCounter::new(window, MAX_RECENT_REQUESTS))
+    }
+
+    /// Count a request of the customer, returns its number of requests within the
+    /// window including this one
+    pub fn record(&mut self, customer: Uuid) -> u32 {
+        self.0.record(customer)
+    }
+
+    /// Get the number of customers with a request within the window
+    pub fn num_customers(&mut self) -> u32 {
+        self.0.num_keys() as u32
+    }
+}
@@ -0,0 +1,20 @@
+//! Sticky routing of customers to servers via rendezvous hashing
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use uuid::Uuid;
+
+/// Get the index of the server the given customer is routed to
+///
+/// Every customer prefers the server with the highest hash of the customer and server
+/// ids. Adding or removing a server thus only moves the customers preferring that
+/// server, all others stay with their server. Returns `None` if there are no servers.
+pub fn hashed_server_index(customer: Uuid, servers: &[Uuid]) -> Option<usize> {
+    (0..servers.len()).max_by_key(|&i| {
+        let mut hasher = DefaultHasher::new();
+        customer.hash(&mut hasher);
+        servers[i].hash(&mut hasher);
+        hasher.finish()
+    })
+}
@@ -3,21 +3,29 @@
 #![allow(clippy::too_many_arguments)]
 use std::collections::HashMap;
 use std::collections::VecDeque;
-use std::sync::Arc;
-use std::time::Instant;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crossbeam::channel::Receiver;
 use crossbeam::channel::Sender;
 use crossbeam::select;
 use parking_lot::Mutex;
-use ticket_sale_core::{Request, RequestKind};
+use rand::Rng;
+use ticket_sale_core::{AllocationStrategy, Request, RequestError, RequestKind};
 use uuid::Uuid;
 
 use super::coordinator_standard::CoordinatorStandard;
 use super::database::Database;
+use super::debug;
 use super::enums::EstimatorServerStatus;
 use super::enums::HighPriorityServerRequest;
+use super::enums::ServerState;
+use super::enums::ServerStats;
 use super::enums::ServerStatus;
+use super::enums::TicketSelection;
+use super::idempotency::RecentBuys;
 
 pub struct ServerStandard {
     /// The server's ID
@@ -26,6 +34,9 @@ pub struct ServerStandard {
     database: Arc<Mutex<Database>>,
     coordinator: Arc<Mutex<CoordinatorStandard>>,
 
+    /// Upper bound for the tickets that can still be sold, shared with the database
+    max_available: Arc<AtomicU32>,
+
     /// Current server status
     status: ServerStatus,
 
@@ -35,15 +46,32 @@ pub struct ServerStandard {
     /// Estimate of tickets in other servers
     estimate: u32,
 
-    /// Map from customer id to ticket id and time it was reserved
-    reserved: HashMap<Uuid, (u32, Instant)>,
+    /// Map from customer id to reserved ticket ids and time they expire
+    reserved: HashMap<Uuid, (Vec<u32>, Instant)>,
 
-    /// Queue of reservations as (customer id, time of reservation)
+    /// Queue of reservations as (customer id, time of expiry), ordered by the time of
+    /// expiry
     timeout_queue: VecDeque<(Uuid, Instant)>,
 
     /// The reservation timeout
     reservation_timeout: u32,
 
+    /// Maximal number of seconds randomly added to the timeout of each reservation
+    timeout_jitter: u32,
+
+    /// Recently completed buys, so retried buy requests succeed again
+    recent_buys: RecentBuys,
+
+    /// Strategy for deciding how many tickets to allocate at once
+    allocation_strategy: AllocationStrategy,
+
+    /// Maximal number of tickets to allocate at once
+    max_batch: u32,
+
+    /// Whether the estimator runs, otherwise the database is counted instead of the
+    /// estimate
+    estimator_enabled: bool,
+
     /// Receivers for receiving requests
     low_priority: Option<Receiver<Request>>,
     high_priority: Option<Receiver<HighPriorityServerRequest>>,
@@ -52,40 +80,62 @@ pub struct ServerStandard {
     coordinator_terminated_sender: Sender<Uuid>,
 
     /// Sender for sending the server's number of tickets to the estimator
-    estimator_tickets_sender: Sender<u32>,
+    estimator_tickets_sender: Sender<(Uuid, u32)>,
 
     /// Sender for notifying the estimator of the server's termination
     estimator_scaling_sender: Sender<EstimatorServerStatus>,
+
+    /// Number of reservations, shared with the coordinator
+    reservations: Arc<AtomicU32>,
+
+    /// Sender for notifying of the customers whose reservations expired, if any
+    expiry_sender: Option<mpsc::Sender<Uuid>>,
 }
 
 impl ServerStandard {
     /// Create a new [`ServerStandard`]
     pub fn new(
         database: Arc<Mutex<Database>>,
+        max_available: Arc<AtomicU32>,
         coordinator: Arc<Mutex<CoordinatorStandard>>,
         reservation_timeout: u32,
+        timeout_jitter: u32,
+        idempotency_window: u32,
+        allocation_strategy: AllocationStrategy,
+        max_batch: u32,
+        estimator_enabled: bool,
         low_priority: Receiver<Request>,
         high_priority: Receiver<HighPriorityServerRequest>,
         coordinator_terminated_sender: Sender<Uuid>,
-        estimator_tickets_sender: Sender<u32>,
+        estimator_tickets_sender: Sender<(Uuid, u32)>,
         estimator_scaling_sender: Sender<EstimatorServerStatus>,
+        reservations: Arc<AtomicU32>,
+        expiry_sender: Option<mpsc::Sender<Uuid>>,
     ) -> Self {
         let id = Uuid::new_v4();
         Self {
             id,
             database,
             coordinator,
+            max_available,
             status: ServerStatus::Active,
             tickets: Vec::new(),
             estimate: 0,
             reserved: HashMap::new(),
             timeout_queue: VecDeque::new(),
             reservation_timeout,
+            timeout_jitter,
+            recent_buys: RecentBuys::new(idempotency_window),
+            allocation_strategy,
+            max_batch,
+            estimator_enabled,
             low_priority: Some(low_priority),
             high_priority: Some(high_priority),
             coordinator_terminated_sender,
             estimator_tickets_sender,
             estimator_scaling_sender,
+            reservations,
+            expiry_sender,
         }
     }
 
@@ -137,13 +187,21 @@ impl ServerStandard {
                     let high_priority_receiver = self.high_priority.take().unwrap();
                     drop(high_priority_receiver);
 
-                    // Assign a new server to all low priority requests
+                    // Redirect all low priority requests to a new server
                     let low_priority_receiver = self.low_priority.take().unwrap();
-                    while let Ok(mut rq) = low_priority_receiver.try_recv() {
+                    while let Ok(rq) = low_priority_receiver.try_recv() {
                         let coordinator_guard = self.coordinator.lock();
-                        let x = coordinator_guard.get_random_server();
-                        rq.set_server_id(x);
-                        rq.respond_with_err("Our error: Server no longer exists.");
+                        match coordinator_guard.get_random_server() {
+                            Some(x) => {
+                                rq.respond_with_redirect(x, "Our error: Server no longer exists.")
+                            }
+                            None => {
+                                rq.respond_with_err_kind(
+                                    RequestError::ServerGone,
+                                    "Our error: Server no longer exists.",
+                                )
+                            }
+                        }
                     }
 
                     // Drop the low priority receiver to prevent
@@ -157,6 +215,16 @@ impl ServerStandard {
 
             // If the server needs to shut down after that request
             if self.status == ServerStatus::Shutdown {
+                // Answer the requests still queued, nobody processes them anymore
+                if let Some(low_priority_receiver) = self.low_priority.take() {
+                    for rq in low_priority_receiver.try_iter() {
+                        rq.respond_with_err_kind(
+                            RequestError::Unavailable,
+                            "Our error: Shutting down.",
+                        );
+                    }
+                }
+
                 // Terminate the server
                 break;
             }
@@ -232,13 +300,123 @@ impl ServerStandard {
         match rq {
             HighPriorityServerRequest::Activate => self.activate(),
             HighPriorityServerRequest::Deactivate => self.deactivate(),
-            HighPriorityServerRequest::Shutdown => self.status = ServerStatus::Shutdown,
+            HighPriorityServerRequest::Drain => self.drain(),
+            HighPriorityServerRequest::Shutdown => self.shutdown(),
+            HighPriorityServerRequest::Prefill { count } => self.prefill(count),
             HighPriorityServerRequest::Estimate { tickets } => {
                 self.send_tickets(tickets);
             }
+            HighPriorityServerRequest::ReportStats { sender } => {
+                let _ = sender.send(self.get_stats());
+            }
+            HighPriorityServerRequest::ReportReserved { sender } => {
+                let _ = sender.send(self.get_num_reserved());
+            }
+            HighPriorityServerRequest::ReportAvailable { sender } => {
+                let _ = sender.send(self.get_num_available());
+            }
+            HighPriorityServerRequest::ClearReservations { sender } => {
+                let _ = sender.send(self.clear_reservations());
+            }
+            HighPriorityServerRequest::ReportLoad { sender } => {
+                let _ = sender.send((self.id, self.get_num_reserved()));
+            }
+            #[cfg(feature = "persist")]
+            HighPriorityServerRequest::Persist { sender } => {
+                let _ = sender.send(self.get_state());
+            }
+        }
+    }
+
+    /// Get the server's ticket counts for the debugging statistics
+    pub fn get_stats(&mut self) -> ServerStats {
+        // Remove reservations that have timed out
+        self.remove_timeouted_reservations();
+
+        ServerStats {
+            id: self.id,
+            available: self.tickets.len() as u32,
+            reserved: self
+                .reserved
+                .values()
+                .map(|(tickets, _)| tickets.len() as u32)
+                .sum(),
+            estimate: self.estimate,
+            customers: 0,
+        }
+    }
+
+    /// Get the number of tickets reserved on this server
+    pub fn get_num_reserved(&mut self) -> u32 {
+        // Remove reservations that have timed out
+        self.remove_timeouted_reservations();
+
+        self.reserved
+            .values()
+            .map(|(tickets, _)| tickets.len() as u32)
+            .sum()
+    }
+
+    /// Get the number of non-reserved tickets held by this server
+    pub fn get_num_available(&mut self) -> u32 {
+        // Tickets of timed out reservations are available again
+        self.remove_timeouted_reservations();
+
+        self.tickets.len() as u32
+    }
+
+    /// Abort all reservations, returning their tickets to the database
+    ///
+    /// Returns the number of released tickets.
+    pub fn clear_reservations(&mut self) -> u32 {
+        let mut database_guard = self.database.lock();
+        let mut released = 0;
+        for (tickets, _) in self.reserved.values() {
+            database_guard.deallocate(tickets);
+            released += tickets.len() as u32;
+        }
+        drop(database_guard);
+
+        // Without reservations, the timeout queue has nothing left to expire
+        self.reserved.clear();
+        self.timeout_queue.clear();
+        self.update_reservations();
+
+        // A terminating server has no reason left to stay
+        self.terminate_if_done();
+        released
+    }
+
+    /// Get the server's state for persisting it
+    #[cfg(feature = "persist")]
+    pub fn get_state(&mut self) -> ServerState {
+        // Remove reservations that have timed out
+        self.remove_timeouted_reservations();
+
+        ServerState {
+            id: self.id,
+            tickets: self.tickets.clone(),
+            reservations: self
+                .reserved
+                .iter()
+                .map(|(customer, (tickets, _))| (*customer, tickets.clone()))
+                .collect(),
         }
     }
 
+    /// Restore the given state before the server is started
+    ///
+    /// The timeouts of the restored reservations start anew.
+    pub fn restore(&mut self, state: ServerState) {
+        self.id = state.id;
+        self.tickets = state.tickets;
+        for (customer, tickets) in state.reservations {
+            let deadline = self.draw_deadline().0;
+            self.insert_reservation(customer, tickets, deadline);
+        }
+        self.update_reservations();
+    }
+
     /// Activate the server
     pub fn activate(&mut self) {
         // If the server is supposed to shut down, do not interfere
@@ -248,6 +426,15 @@ impl ServerStandard {
         self.status = ServerStatus::Active;
     }
 
+    /// Allocate up to `count` tickets from the database if the server is active
+    pub fn prefill(&mut self, count: u32) {
+        if self.status != ServerStatus::Active {
+            return;
+        }
+        let tickets = self.database.lock().allocate(count);
+        self.tickets.extend(tickets);
+    }
+
     /// Deactivate the server
     pub fn deactivate(&mut self) {
         // If the server is supposed to shut down, do not interfere
@@ -268,45 +455,111 @@ impl ServerStandard {
         }
     }
 
+    /// Drain the server
+    ///
+    /// Unlike [`Self::deactivate`], the non-reserved tickets are kept until the
+    /// reservations are cleared, new reservations are refused in the meantime.
+    pub fn drain(&mut self) {
+        // If the server is supposed to shut down, do not interfere
+        if self.status == ServerStatus::Shutdown {
+            return;
+        }
+        self.status = ServerStatus::Draining;
+        self.finish_draining();
+    }
+
+    /// Terminate the server if it is terminating and no reservations are left, or
+    /// deactivate it if it is draining and no reservations are left
+    fn terminate_if_done(&mut self) {
+        if self.reserved.is_empty() && self.status == ServerStatus::Terminating {
+            self.status = ServerStatus::Terminated;
+        }
+        self.finish_draining();
+    }
+
+    /// Deactivate the server if it is draining and no reservations are left
+    fn finish_draining(&mut self) {
+        if self.reserved.is_empty() && self.status == ServerStatus::Draining {
+            self.deactivate();
+        }
+    }
+
+    /// Shut the server down
+    pub fn shutdown(&mut self) {
+        self.status = ServerStatus::Shutdown;
+
+        // Return all tickets, including the reserved ones, to the database
+        let mut database_guard = self.database.lock();
+        database_guard.deallocate(self.tickets.as_slice());
+        self.tickets.clear();
+        for (_, (tickets, _)) in self.reserved.drain() {
+            database_guard.deallocate(&tickets);
+        }
+    }
+
+    /// Draw the time of expiry of a new reservation and its timeout in seconds
+    ///
+    /// A random jitter of up to `timeout_jitter` seconds is added to the timeout, so
+    /// reservations made together do not all expire at once.
+    fn draw_deadline(&self) -> (Instant, u32) {
+        let jitter = rand::thread_rng().gen_range(0..=self.timeout_jitter);
+        let ttl = self.reservation_timeout.saturating_add(jitter);
+        (Instant::now() + Duration::from_secs(ttl as u64), ttl)
+    }
+
+    /// Record a reservation of the customer expiring at `deadline`, keeping the
+    /// timeout queue ordered by the time of expiry
+    fn insert_reservation(&mut self, customer: Uuid, tickets: Vec<u32>, deadline: Instant) {
+        self.reserved.insert(customer, (tickets, deadline));
+        let position = self.timeout_queue.partition_point(|&(_, t)| t <= deadline);
+        self.timeout_queue.insert(position, (customer, deadline));
+    }
+
     /// Removes reservations that have timed out
     pub fn remove_timeouted_reservations(&mut self) {
         let mut database_guard = self.database.lock();
 
         // While we have reservations
         while !self.timeout_queue.is_empty() {
-            if self.timeout_queue.front().unwrap().1.elapsed().as_secs()
-                <= self.reservation_timeout as u64
-            {
+            if self.timeout_queue.front().unwrap().1 > Instant::now() {
                 // No more timeouted reservations
                 break;
             }
-            // Get customer and time of reservation
+            // Get customer and time of expiry
             let customer = self.timeout_queue.front().unwrap().0;
             let time = self.timeout_queue.front().unwrap().1;
             self.timeout_queue.pop_front();
 
             // If reservation still exists
             if self.reserved.contains_key(&customer) && self.reserved[&customer].1 == time {
-                let ticket = self.reserved[&customer].0;
+                // Remove reservation
+                let (tickets, _) = self.reserved.remove(&customer).unwrap();
                 // If the server is active
                 if self.status == ServerStatus::Active {
-                    // Return the ticket to the list
-                    self.tickets.push(ticket);
+                    // Return the tickets to the list
+                    self.tickets.extend(tickets);
                 } else {
-                    // Otherwise, return it to the database
-                    database_guard.deallocate(&[ticket]);
+                    // Otherwise, return them to the database
+                    database_guard.deallocate(&tickets);
+                }
+
+                // Notify of the expiry
+                if let Some(sender) = &self.expiry_sender {
+                    let _ = sender.send(customer);
                 }
-                // Remove reservation
-                self.reserved.remove(&customer);
             }
         }
         drop(database_guard);
+        self.update_reservations();
 
         // If no reservations are left and the server is terminating
-        if self.reserved.is_empty() && self.status == ServerStatus::Terminating {
-            // Mark server as terminated
-            self.status = ServerStatus::Terminated;
-        }
+        self.terminate_if_done();
+    }
+
+    /// Publishes the number of reservations to the coordinator
+    fn update_reservations(&self) {
+        self.reservations
+            .store(self.reserved.len() as u32, Ordering::Relaxed);
     }
 
     /// Stores estimate and sends its number of tickets to the estimator
@@ -317,27 +570,62 @@ impl ServerStandard {
         self.estimate = tickets;
         let _ = self
             .estimator_tickets_sender
-            .send(self.tickets.len() as u32);
+            .send((self.id, self.tickets.len() as u32));
     }
 
     /// Processes a given low priority request
-    pub fn process_low_priority(&mut self, rq: Request) {
+    pub fn process_low_priority(&mut self, mut rq: Request) {
         // Remove reservations that have timed out
         self.remove_timeouted_reservations();
 
+        // Responses carry the id of the server that processed the request, error
+        // responses may replace it with a newly assigned server
+        rq.set_server_id(self.id);
+
         match rq.kind() {
             RequestKind::NumAvailableTickets => {
                 rq.respond_with_int(self.get_available_tickets());
             }
+            RequestKind::LocalAvailableTickets => {
+                rq.respond_with_int(self.tickets.len() as u32);
+            }
             RequestKind::ReserveTicket => {
-                self.process_reservation(rq);
+                // A specific ticket id may be requested
+                let selection = match rq.read_u32() {
+                    Some(id) => TicketSelection::Id(id),
+                    None => TicketSelection::Last,
+                };
+                self.process_reservation(rq, selection);
+            }
+            RequestKind::ReserveMultiple => {
+                self.process_reservation_multiple(rq);
+            }
+            RequestKind::CanReserve => {
+                let possible = self.can_reserve(rq.customer_id());
+                rq.respond_with_int(possible as u32);
             }
             RequestKind::BuyTicket => {
                 self.process_buy(rq);
             }
+            RequestKind::BuyAll => {
+                self.process_buy_all(rq);
+            }
+            RequestKind::AbortAll => {
+                self.process_abort_all(rq);
+            }
+            RequestKind::TransferReservation => {
+                self.process_transfer(rq);
+            }
+            RequestKind::GetMyReservations => {
+                let tickets = self.get_reservations(rq.customer_id());
+                rq.respond_with_int_list(&tickets);
+            }
             RequestKind::AbortPurchase => {
                 self.process_cancel(rq);
             }
+            RequestKind::Debug => {
+                self.process_debug(rq);
+            }
             _ => {
                 rq.respond_with_err("Our error: RequestKind not found.");
             }
@@ -346,27 +634,90 @@ impl ServerStandard {
 
     /// Get number of available tickets
     pub fn get_available_tickets(&self) -> u32 {
-        self.tickets.len() as u32 + self.estimate
+        // Without the estimator, there is no estimate of the other servers, so count
+        // the tickets of the database instead (exact with a single server)
+        if !self.estimator_enabled {
+            return self.tickets.len() as u32 + self.database.lock().get_num_available();
+        }
+
+        // The estimate may double count tickets while scaling, so never report more
+        // than can still be sold
+        let max_available = self.max_available.load(Ordering::Relaxed);
+        (self.tickets.len() as u32 + self.estimate).min(max_available)
+    }
+
+    /// Get the ids of the tickets reserved for the customer on this server
+    pub fn get_reservations(&self, customer: Uuid) -> Vec<u32> {
+        self.reserved
+            .get(&customer)
+            .map(|(tickets, _)| tickets.clone())
+            .unwrap_or_default()
+    }
+
+    /// Check whether the customer could currently reserve a ticket
+    ///
+    /// This does not change any state. A customer with a reservation cannot reserve
+    /// another ticket, otherwise a reservation succeeds if this server or the database
+    /// still has a ticket.
+    pub fn can_reserve(&self, customer: Uuid) -> bool {
+        !self.reserved.contains_key(&customer)
+            && (!self.tickets.is_empty() || self.database.lock().get_num_available() > 0)
+    }
+
+    /// Process a debug request
+    pub fn process_debug(&mut self, mut rq: Request) {
+        let path = debug::path(rq.url());
+        if path == debug::RESERVE_POSITION {
+            // Make sure the request has a position
+            match rq.read_u32() {
+                Some(position) => {
+                    self.process_reservation(rq, TicketSelection::Position(position as usize))
+                }
+                None => rq.respond_with_err("Our error: No position given."),
+            }
+        } else if path == debug::ORPHAN_TICKET {
+            // Deliberately lose a ticket, allocating one if necessary
+            if self.tickets.is_empty() {
+                self.tickets.extend(self.database.lock().allocate(1));
+            }
+            match self.tickets.pop() {
+                Some(ticket) => rq.respond_with_int(ticket),
+                None => rq.respond_with_sold_out(),
+            }
+        } else if path == debug::SLEEP {
+            // Block the server for the given time
+            match debug::query_param(rq.url(), "ms").and_then(|ms| ms.parse().ok()) {
+                Some(ms) => {
+                    rq.respond_with_int(ms);
+                    thread::sleep(Duration::from_millis(ms as u64));
+                }
+                None => rq.respond_with_err("Our error: No sleep time given."),
+            }
+        } else {
+            rq.respond_with_err("Our error: Debug command not found.");
+        }
     }
 
     /// Process a reservation request
-    pub fn process_reservation(&mut self, mut rq: Request) {
+    ///
+    /// The `selection` decides which ticket is reserved. A ticket requested by id is
+    /// taken from the server's non-reserved tickets or the database. If it is neither,
+    /// i.e., it is reserved, sold, or held by another server, the request fails.
+    pub fn process_reservation(&mut self, rq: Request, selection: TicketSelection) {
         // Get the customer id and check if he already has a reservation
         let customer = rq.customer_id();
         if self.reserved.contains_key(&customer) {
-            rq.respond_with_err("Our error: One reservation already present.");
+            rq.respond_with_err_kind(
+                RequestError::Conflict,
+                "Our error: One reservation already present.",
+            );
             return;
         }
 
-        // If the server is terminating
-        if self.status == ServerStatus::Terminating {
-            // Assign a new server and respond with error
-            let coordinator_guard = self.coordinator.lock();
-            let x = coordinator_guard.get_random_server();
-            rq.set_server_id(x);
-            rq.respond_with_err("Our error: Ticket reservations no longer allowed on this server");
+        // If the server is terminating or draining, the client must go elsewhere
+        let Some(rq) = self.redirect_if_closed(rq) else {
             return;
-        }
+        };
 
         // If server doesn't have any tickets
         if self.tickets.is_empty() {
@@ -382,18 +733,135 @@ impl ServerStandard {
             let database_tickets = database_guard.get_num_available();
 
             // Determine number of tickets to allocate
-            let num_tickets = (database_tickets as f64).sqrt() as u32;
+            let num_tickets = self
+                .allocation_strategy
+                .batch_size(database_tickets)
+                .min(self.max_batch);
 
             // Allocate the tickets
             self.tickets.extend(database_guard.allocate(num_tickets));
         }
 
-        // Reserve the last ticket
-        let ticket = self.tickets.pop().unwrap();
-        let time = Instant::now();
-        self.reserved.insert(customer, (ticket, time));
-        self.timeout_queue.push_back((customer, time));
-        rq.respond_with_int(ticket);
+        // Reserve the selected ticket
+        let ticket = match selection {
+            TicketSelection::Last => self.tickets.pop().unwrap(),
+            TicketSelection::Position(position) if position < self.tickets.len() => {
+                self.tickets.remove(position)
+            }
+            TicketSelection::Position(_) => {
+                rq.respond_with_err_kind(
+                    RequestError::Conflict,
+                    "Our error: No ticket at that position.",
+                );
+                return;
+            }
+            TicketSelection::Id(id) => {
+                if let Some(index) = self.tickets.iter().position(|&ticket| ticket == id) {
+                    self.tickets.remove(index)
+                } else if self.database.lock().allocate_specific(id) {
+                    id
+                } else {
+                    rq.respond_with_err_kind(
+                        RequestError::Conflict,
+                        "Our error: Ticket not available.",
+                    );
+                    return;
+                }
+            }
+        };
+        let (deadline, ttl) = self.draw_deadline();
+        self.insert_reservation(customer, vec![ticket], deadline);
+        self.update_reservations();
+        // The reservation was made just now, so it expires after its full timeout
+        rq.respond_with_reservation(ticket, ttl);
+    }
+
+    /// Redirect a reservation to a new server if this one is terminating or draining
+    ///
+    /// Returns the request if this server still takes reservations.
+    fn redirect_if_closed(&self, rq: Request) -> Option<Request> {
+        if !matches!(
+            self.status,
+            ServerStatus::Terminating | ServerStatus::Draining
+        ) {
+            return Some(rq);
+        }
+
+        // Redirect the client to a new server
+        let coordinator_guard = self.coordinator.lock();
+        match coordinator_guard.get_random_server() {
+            Some(x) => {
+                rq.respond_with_redirect(
+                    x,
+                    "Our error: Ticket reservations no longer allowed on this server",
+                )
+            }
+            None => {
+                rq.respond_with_err_kind(
+                    RequestError::ServerGone,
+                    "Our error: Ticket reservations no longer allowed on this server",
+                )
+            }
+        }
+        None
+    }
+
+    /// Process a request reserving multiple tickets at once
+    ///
+    /// Up to the requested number of tickets are reserved for the customer. If fewer
+    /// tickets are left, all of them are reserved. The customer may not hold any other
+    /// reservation.
+    pub fn process_reservation_multiple(&mut self, mut rq: Request) {
+        // Make sure the request has a positive number of tickets
+        let count = match rq.read_u32() {
+            Some(count) if count > 0 => count as usize,
+            _ => {
+                rq.respond_with_err("Our error: No number of tickets given.");
+                return;
+            }
+        };
+
+        // Get the customer id and check if he already has a reservation
+        let customer = rq.customer_id();
+        if self.reserved.contains_key(&customer) {
+            rq.respond_with_err_kind(
+                RequestError::Conflict,
+                "Our error: One reservation already present.",
+            );
+            return;
+        }
+
+        // If the server is terminating or draining, the client must go elsewhere
+        let Some(rq) = self.redirect_if_closed(rq) else {
+            return;
+        };
+
+        // If the server doesn't have enough tickets, allocate at least the missing ones
+        if self.tickets.len() < count {
+            let mut database_guard = self.database.lock();
+            let database_tickets = database_guard.get_num_available();
+            let num_tickets = self
+                .allocation_strategy
+                .batch_size(database_tickets)
+                .min(self.max_batch)
+                .max((count - self.tickets.len()) as u32);
+            self.tickets.extend(database_guard.allocate(num_tickets));
+        }
+
+        // If there are no tickets at all => sold out
+        if self.tickets.is_empty() {
+            rq.respond_with_sold_out();
+            return;
+        }
+
+        // Reserve the last (up to) count tickets
+        let tickets = self
+            .tickets
+            .split_off(self.tickets.len().saturating_sub(count));
+        let deadline = self.draw_deadline().0;
+        self.insert_reservation(customer, tickets, deadline);
+        self.update_reservations();
+        rq.respond_with_int_list(&self.reserved[&customer].0);
     }
 
     /// Process a buy request
@@ -412,28 +880,168 @@ impl ServerStandard {
 
             match reservation {
                 // Make sure the customer has a reservation
-                Some((reservation_ticket, time)) => {
+                Some((mut reservation_tickets, time)) => {
                     // And that it reserved that specific ticket
-                    if reservation_ticket == ticket {
+                    if let Some(index) = reservation_tickets.iter().position(|&t| t == ticket) {
+                        // Keep the remaining tickets of a multiple reservation
+                        reservation_tickets.swap_remove(index);
+                        if !reservation_tickets.is_empty() {
+                            self.reserved.insert(customer, (reservation_tickets, time));
+                        }
+
+                        // Record the sale
+                        self.database.lock().mark_sold(ticket);
+
+                        self.update_reservations();
+
                         // Terminate server if this was the last reservation and server was
                         // terminating
-                        if self.reserved.is_empty() && self.status == ServerStatus::Terminating {
-                            self.status = ServerStatus::Terminated;
-                        }
+                        self.terminate_if_done();
+                        self.recent_buys.record(customer, ticket);
                         rq.respond_with_int(ticket);
                     } else {
                         // Insert the reservation back so it can still be bought later
-                        self.reserved.insert(customer, (reservation_ticket, time));
-                        rq.respond_with_err(
+                        self.reserved.insert(customer, (reservation_tickets, time));
+                        self.reject_buy(
+                            rq,
+                            ticket,
                             "Our error: Reservation not made for that ticket for buy request.",
                         )
                     }
                 }
-                None => rq.respond_with_err("Our error: No reservation for buy request."),
+                None => self.reject_buy(rq, ticket, "Our error: No reservation for buy request."),
             }
         }
     }
 
+    /// Reject a buy request without a matching reservation, unless it repeats a recent
+    /// buy of the customer, which succeeds again
+    fn reject_buy(&mut self, rq: Request, ticket: u32, err: &str) {
+        if self.recent_buys.contains(rq.customer_id(), ticket) {
+            rq.respond_with_int(ticket);
+        } else {
+            rq.respond_with_err_kind(RequestError::Conflict, err);
+        }
+    }
+
+    /// Process a request buying all tickets reserved by the customer
+    pub fn process_buy_all(&mut self, rq: Request) {
+        // Remove all reservations of the customer
+        let customer = rq.customer_id();
+        let Some((tickets, _)) = self.reserved.remove(&customer) else {
+            rq.respond_with_err_kind(
+                RequestError::Conflict,
+                "Our error: No reservation for buy all request.",
+            );
+            return;
+        };
+
+        // Record the sales
+        let mut database_guard = self.database.lock();
+        for &ticket in &tickets {
+            database_guard.mark_sold(ticket);
+        }
+        drop(database_guard);
+
+        self.update_reservations();
+
+        // Terminate server if this was the last reservation and server was terminating
+        self.terminate_if_done();
+
+        rq.respond_with_int_list(&tickets);
+    }
+
+    /// Process a request aborting all reservations of the customer
+    pub fn process_abort_all(&mut self, rq: Request) {
+        // Remove all reservations of the customer, the stale entries in the timeout
+        // queue no longer match any reservation
+        let customer = rq.customer_id();
+        let Some((tickets, _)) = self.reserved.remove(&customer) else {
+            rq.respond_with_err_kind(
+                RequestError::Conflict,
+                "Our error: No reservation for abort all request.",
+            );
+            return;
+        };
+
+        // Return the tickets to the non-reserved list or database
+        self.release_tickets(&tickets);
+
+        self.update_reservations();
+
+        // Terminate server if this was the last reservation and server was terminating
+        self.terminate_if_done();
+
+        rq.respond_with_int_list(&tickets);
+    }
+
+    /// Process a request transferring a reserved ticket of the customer to another
+    /// customer
+    ///
+    /// The reservation of the target keeps the original reservation time, so the
+    /// transfer does not extend it.
+    pub fn process_transfer(&mut self, mut rq: Request) {
+        // Make sure the request has a ticket id and a target customer
+        let Some((ticket, target)) = rq.read_transfer() else {
+            rq.respond_with_err("Our error: No ticket id and target customer given.");
+            return;
+        };
+        let customer = rq.customer_id();
+        if target == customer {
+            rq.respond_with_err("Our error: Cannot transfer a reservation to oneself.");
+            return;
+        }
+
+        // A customer holds at most one reservation
+        if self.reserved.contains_key(&target) {
+            rq.respond_with_err_kind(
+                RequestError::Conflict,
+                "Our error: Target customer already has a reservation.",
+            );
+            return;
+        }
+
+        // Make sure the customer reserved that specific ticket
+        let Some((reservation_tickets, time)) = self.reserved.get_mut(&customer) else {
+            rq.respond_with_err_kind(
+                RequestError::Conflict,
+                "Our error: No reservation for transfer request.",
+            );
+            return;
+        };
+        let Some(index) = reservation_tickets.iter().position(|&t| t == ticket) else {
+            rq.respond_with_err_kind(
+                RequestError::Conflict,
+                "Our error: Reservation not made for that ticket for transfer request.",
+            );
+            return;
+        };
+
+        // Keep the remaining tickets of a multiple reservation
+        let time = *time;
+        reservation_tickets.swap_remove(index);
+        if reservation_tickets.is_empty() {
+            self.reserved.remove(&customer);
+        }
+
+        // Reserve the ticket for the target, keeping the time of expiry
+        self.insert_reservation(target, vec![ticket], time);
+
+        self.update_reservations();
+
+        rq.respond_with_int(ticket);
+    }
+
+    /// Return released tickets to the non-reserved list or, if the server is not
+    /// active, to the database
+    fn release_tickets(&mut self, tickets: &[u32]) {
+        if self.status == ServerStatus::Active {
+            self.tickets.extend(tickets);
+        } else {
+            self.database.lock().deallocate(tickets);
+        }
+    }
+
     /// Process a cancel request
     pub fn process_cancel(&mut self, mut rq: Request) {
         // Make sure the request has a ticket id
@@ -450,31 +1058,39 @@ impl ServerStandard {
 
             match reservation {
                 // Make sure the customer has a reservation
-                Some((reservation_ticket, time)) => {
+                Some((mut reservation_tickets, time)) => {
                     // And that it reserved that specific ticket
-                    if reservation_ticket == ticket {
-                        // Return ticket to non-reserved list or database
-                        if self.status == ServerStatus::Active {
-                            self.tickets.push(ticket);
-                        } else {
-                            self.database.lock().deallocate(&[ticket]);
+                    if let Some(index) = reservation_tickets.iter().position(|&t| t == ticket) {
+                        // Keep the remaining tickets of a multiple reservation
+                        reservation_tickets.swap_remove(index);
+                        if !reservation_tickets.is_empty() {
+                            self.reserved.insert(customer, (reservation_tickets, time));
                         }
 
+                        // Return ticket to non-reserved list or database
+                        self.release_tickets(&[ticket]);
+
+                        self.update_reservations();
+
                         // Terminate server if this was the last reservation and server was
                         // terminating
-                        if self.reserved.is_empty() && self.status == ServerStatus::Terminating {
-                            self.status = ServerStatus::Terminated;
-                        }
+                        self.terminate_if_done();
                         rq.respond_with_int(ticket);
                     } else {
                         // Insert the reservation back so it can still be cancelled later
-                        self.reserved.insert(customer, (reservation_ticket, time));
-                        rq.respond_with_err(
+                        self.reserved.insert(customer, (reservation_tickets, time));
+                        rq.respond_with_err_kind(
+                            RequestError::Conflict,
                             "Our error: Reservation not made for that ticket for buy request.",
                         )
                     }
                 }
-                None => rq.respond_with_err("Our error: No reservation for cancel request."),
+                None => {
+                    rq.respond_with_err_kind(
+                        RequestError::Conflict,
+                        "Our error: No reservation for cancel request.",
+                    )
+                }
             }
         }
     }
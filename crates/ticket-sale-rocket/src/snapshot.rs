@@ -0,0 +1,27 @@
+//! Copy-on-write snapshot of the non-terminating servers
+
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use uuid::Uuid;
+
+/// Snapshot of the ids of the non-terminating servers
+///
+/// The coordinator replaces the snapshot whenever the non-terminating servers change.
+/// Readers only hold the read lock to clone the current [`Arc`], so they never wait
+/// for the coordinator, only for the short swap of the pointer.
+#[derive(Default)]
+pub struct ServerSnapshot(RwLock<Arc<Vec<Uuid>>>);
+
+impl ServerSnapshot {
+    /// Get the current snapshot
+    pub fn get(&self) -> Arc<Vec<Uuid>> {
+        self.0.read().clone()
+    }
+
+    /// Replace the snapshot by the given servers
+    pub fn set(&self, servers: &[Uuid]) {
+        let servers = Arc::new(servers.to_vec());
+        *self.0.write() = servers;
+    }
+}
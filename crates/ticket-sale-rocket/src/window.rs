@@ -0,0 +1,74 @@
+//! Counter of the keys seen within a sliding time window
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+/// Number of times each key was recorded within the last `window`
+///
+/// Keys without a record within the window are forgotten, so the counter only grows
+/// with the keys recorded recently. At most `max_records` records are kept, older
+/// ones are forgotten early.
+pub struct WindowCounter<K> {
+    /// Time a record is counted
+    window: Duration,
+
+    /// Maximal number of records kept
+    max_records: usize,
+
+    /// Number of records of each key within the window
+    counts: HashMap<K, u32>,
+
+    /// Records in the order they were made, with their time
+    queue: VecDeque<(K, Instant)>,
+}
+
+impl<K: Copy + Eq + Hash> WindowCounter<K> {
+    /// Create a new [`WindowCounter`] keeping at most `max_records` records
+    pub fn new(window: Duration, max_records: usize) -> Self {
+        Self {
+            window,
+            max_records,
+            counts: HashMap::new(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Record the key, returns its number of records within the window including
+    /// this one
+    pub fn record(&mut self, key: K) -> u32 {
+        self.prune();
+        self.queue.push_back((key, Instant::now()));
+        let count = self.counts.entry(key).or_insert(0);
+        *count += 1;
+        *count
+    }
+
+    /// Get the number of records of the key within the window
+    pub fn count(&mut self, key: &K) -> u32 {
+        self.prune();
+        self.counts.get(key).copied().unwrap_or(0)
+    }
+
+    /// Get the number of keys with a record within the window
+    pub fn num_keys(&mut self) -> usize {
+        self.prune();
+        self.counts.len()
+    }
+
+    /// Forget the records older than the window or exceeding the maximal number
+    fn prune(&mut self) {
+        while let Some(&(key, time)) = self.queue.front() {
+            if time.elapsed() < self.window && self.queue.len() < self.max_records {
+                break;
+            }
+            self.queue.pop_front();
+            if let Some(count) = self.counts.get_mut(&key) {
+                *count -= 1;
+                if *count == 0 {
+                    self.counts.remove(&key);
+                }
+            }
+        }
+    }
+}
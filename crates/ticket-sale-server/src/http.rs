@@ -2,115 +2,286 @@
 
 use std::io;
 use std::io::{Read, Write};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use ticket_sale_core::RequestKind;
+use ticket_sale_core::{RequestError, RequestKind};
 use tiny_http::{Header, Response};
 use uuid::Uuid;
 
 /// Length of any hyphenated UUID
 const UUID_LEN: usize = b"a1a2a3a4-b1b2-c1c2-d1d2-d3d4d5d6d7d8".len();
 
-struct HTTPRequest(tiny_http::Request);
+/// Format of the response bodies, negotiated by the `Accept` header
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Bare integers and newline separated lists (the default)
+    PlainText,
+    /// JSON objects such as `{"value":42}`
+    Json,
+}
+
+/// Names of the headers carrying the customer and server ids
+pub struct HeaderNames {
+    /// Header carrying the customer id, `X-Customer-Id` by default
+    pub customer: String,
+    /// Header carrying the server id, `X-Server-Id` by default
+    pub server: String,
+}
+
+impl Default for HeaderNames {
+    fn default() -> Self {
+        Self {
+            customer: String::from("X-Customer-Id"),
+            server: String::from("X-Server-Id"),
+        }
+    }
+}
+
+/// Whether `name` can be used as header name, i.e., is non-empty ASCII without
+/// whitespace or colons
+pub fn is_valid_header_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_graphic() && b != b':')
+}
+
+struct HTTPRequest {
+    inner: tiny_http::Request,
+    format: Format,
+    /// Id for tracing the request, echoed in the `X-Request-Id` header
+    request_id: String,
+    headers: Arc<HeaderNames>,
+}
 
 impl ticket_sale_core::RawRequest for HTTPRequest {
     fn url(&self) -> &str {
-        self.0.url()
+        self.inner.url()
     }
 
     fn method(&self) -> ticket_sale_core::RequestMethod {
-        match self.0.method() {
+        match self.inner.method() {
             tiny_http::Method::Get => ticket_sale_core::RequestMethod::Get,
             tiny_http::Method::Post => ticket_sale_core::RequestMethod::Post,
+            tiny_http::Method::Put => ticket_sale_core::RequestMethod::Put,
+            tiny_http::Method::Delete => ticket_sale_core::RequestMethod::Delete,
             _ => unreachable!(),
         }
     }
 
+    fn authorization(&self) -> Option<&str> {
+        self.inner
+            .headers()
+            .iter()
+            .find(|hdr| hdr.field.equiv("authorization"))
+            .map(|hdr| hdr.value.as_str())
+    }
+
+    fn headers(&self) -> Vec<(String, String)> {
+        self.inner
+            .headers()
+            .iter()
+            .map(|hdr| (hdr.field.to_string(), hdr.value.to_string()))
+            .collect()
+    }
+
     fn read_bytes(&mut self) -> io::Result<Vec<u8>> {
-        let mut buf = Vec::with_capacity(self.0.body_length().unwrap_or(0));
-        self.0.as_reader().read_to_end(&mut buf)?;
+        let mut buf = Vec::with_capacity(self.inner.body_length().unwrap_or(0));
+        self.inner.as_reader().read_to_end(&mut buf)?;
         Ok(buf)
     }
 
     fn read_string(&mut self) -> io::Result<String> {
-        let mut s = String::with_capacity(self.0.body_length().unwrap_or(0));
-        self.0.as_reader().read_to_string(&mut s)?;
+        let mut s = String::with_capacity(self.inner.body_length().unwrap_or(0));
+        self.inner.as_reader().read_to_string(&mut s)?;
         Ok(s)
     }
 
     fn read_u32(&mut self) -> Option<u32> {
-        let mut s = String::with_capacity(self.0.body_length().unwrap_or(16));
-        self.0.as_reader().read_to_string(&mut s).ok()?;
+        let mut s = String::with_capacity(self.inner.body_length().unwrap_or(16));
+        self.inner.as_reader().read_to_string(&mut s).ok()?;
         s.parse().ok()
     }
 
-    fn respond_with_err(self: Box<Self>, err: String, customer: Uuid, server: Option<Uuid>) {
+    fn respond_with_err(
+        self: Box<Self>,
+        kind: RequestError,
+        err: String,
+        customer: Uuid,
+        server: Option<Uuid>,
+    ) {
+        let body = match self.format {
+            Format::PlainText => err,
+            Format::Json => format!("{{\"error\":{}}}", json_string(&err)),
+        };
         self.respond(
-            Response::from_string(err).with_status_code(400),
+            Response::from_string(body).with_status_code(kind.status_code()),
             customer,
             server,
         )
     }
 
+    fn respond_with_redirect(self: Box<Self>, err: String, customer: Uuid, server: Uuid) {
+        let body = match self.format {
+            Format::PlainText => err,
+            Format::Json => {
+                format!(
+                    "{{\"error\":{},\"redirect\":\"{server}\"}}",
+                    json_string(&err)
+                )
+            }
+        };
+        let status = RequestError::ServerGone.status_code();
+        self.respond(
+            Response::from_string(body).with_status_code(status),
+            customer,
+            Some(server),
+        )
+    }
+
+    fn respond_with_unauthorized(self: Box<Self>, customer: Uuid, server: Option<Uuid>) {
+        let body = match self.format {
+            Format::PlainText => "Unauthorized".to_owned(),
+            Format::Json => "{\"error\":\"Unauthorized\"}".to_owned(),
+        };
+        let mut res = Response::from_string(body).with_status_code(401);
+        res.add_header(Header::from_bytes(b"WWW-Authenticate", b"Bearer").unwrap());
+        self.respond(res, customer, server)
+    }
+
     fn respond_with_int(self: Box<Self>, int: u32, customer: Uuid, server: Option<Uuid>) {
+        let body = match self.format {
+            Format::PlainText => int.to_string(),
+            Format::Json => format!("{{\"value\":{int}}}"),
+        };
         self.respond(
-            Response::from_string(int.to_string()).with_status_code(200),
+            Response::from_string(body).with_status_code(200),
             customer,
             server,
         )
     }
 
+    fn respond_with_reservation(
+        self: Box<Self>,
+        ticket: u32,
+        ttl_secs: u32,
+        customer: Uuid,
+        server: Option<Uuid>,
+    ) {
+        let body = match self.format {
+            Format::PlainText => ticket.to_string(),
+            Format::Json => format!("{{\"value\":{ticket}}}"),
+        };
+        let expires = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+            + ttl_secs as u64;
+        let mut res = Response::from_string(body).with_status_code(200);
+        res.add_header(Header::from_bytes(b"X-Reservation-Expires", expires.to_string()).unwrap());
+        self.respond(res, customer, server)
+    }
+
     fn respond_with_string(self: Box<Self>, s: String, customer: Uuid, server: Option<Uuid>) {
+        let body = match self.format {
+            Format::PlainText => s,
+            Format::Json => format!("{{\"value\":{}}}", json_string(&s)),
+        };
         self.respond(
-            Response::from_string(s).with_status_code(200),
+            Response::from_string(body).with_status_code(200),
             customer,
             server,
         )
     }
 
     fn respond_with_sold_out(self: Box<Self>, customer: Uuid, server: Option<Uuid>) {
+        let body = match self.format {
+            Format::PlainText => "SOLD OUT",
+            Format::Json => "{\"sold_out\":true}",
+        };
         self.respond(
-            Response::from_string("SOLD OUT").with_status_code(200),
+            Response::from_string(body).with_status_code(200),
             customer,
             server,
         )
     }
 
     fn respond_with_server_list(self: Box<Self>, servers: &[Uuid]) {
-        let mut s = Vec::<u8>::with_capacity((UUID_LEN + 1) * servers.len());
-        for id in servers {
-            writeln!(&mut s, "{}", id.hyphenated()).unwrap();
+        let mut s = Vec::<u8>::with_capacity((UUID_LEN + 3) * servers.len() + 14);
+        match self.format {
+            Format::PlainText => {
+                for id in servers {
+                    writeln!(&mut s, "{}", id.hyphenated()).unwrap();
+                }
+            }
+            Format::Json => {
+                write!(&mut s, "{{\"servers\":[").unwrap();
+                for (i, id) in servers.iter().enumerate() {
+                    let sep = if i == 0 { "" } else { "," };
+                    write!(&mut s, "{sep}\"{}\"", id.hyphenated()).unwrap();
+                }
+                write!(&mut s, "]}}").unwrap();
+            }
         }
 
         let mut res = Response::from_data(s);
         add_response_cors_headers(&mut res);
-        self.0.respond(res).expect("HTTP response failed");
+        add_content_type_header(&mut res, self.format);
+        res.add_header(Header::from_bytes(b"X-Request-Id", self.request_id).unwrap());
+        self.inner.respond(res).expect("HTTP response failed");
+    }
+
+    fn respond_with_int_list(self: Box<Self>, ints: &[u32], customer: Uuid, server: Option<Uuid>) {
+        let mut s = Vec::<u8>::new();
+        match self.format {
+            Format::PlainText => {
+                for i in ints {
+                    writeln!(&mut s, "{i}").unwrap();
+                }
+            }
+            Format::Json => {
+                let values: Vec<_> = ints.iter().map(u32::to_string).collect();
+                write!(&mut s, "{{\"values\":[{}]}}", values.join(",")).unwrap();
+            }
+        }
+
+        self.respond(
+            Response::from_data(s).with_status_code(200),
+            customer,
+            server,
+        )
     }
 }
 
 impl HTTPRequest {
-    /// Add HTTP headers (CORS, X-Customer-Id, X-Server-Id) to `res` and send it
+    /// Add HTTP headers (CORS, Content-Type, X-Request-Id, and the customer and server
+    /// id headers) to `res` and send it
     fn respond<R: Read>(self, mut res: Response<R>, customer: Uuid, server: Option<Uuid>) {
         add_response_cors_headers(&mut res);
+        add_content_type_header(&mut res, self.format);
+        res.add_header(Header::from_bytes(b"X-Request-Id", self.request_id).unwrap());
 
         let mut cid = Vec::<u8>::with_capacity(UUID_LEN);
         write!(&mut cid, "{}", customer.hyphenated()).unwrap();
-        res.add_header(tiny_http::Header::from_bytes(b"X-Customer-Id", cid).unwrap());
+        res.add_header(Header::from_bytes(self.headers.customer.as_bytes(), cid).unwrap());
 
         if let Some(server) = server {
             let mut sid = Vec::<u8>::with_capacity(UUID_LEN);
             write!(&mut sid, "{}", server.hyphenated()).unwrap();
-            res.add_header(tiny_http::Header::from_bytes(b"X-Server-Id", sid).unwrap());
+            res.add_header(Header::from_bytes(self.headers.server.as_bytes(), sid).unwrap());
         }
 
-        self.0.respond(res).expect("HTTP response failed");
+        self.inner.respond(res).expect("HTTP response failed");
     }
 }
 
-/// Parse the given HTTP request
+/// Parse the given HTTP request, reading the customer and server ids from the given
+/// headers
 ///
 /// If [`None`] is returned, the request was already answered with a
 /// corresponding error message.
-pub fn parse(rq: tiny_http::Request) -> Option<ticket_sale_core::Request> {
+pub fn parse(
+    rq: tiny_http::Request,
+    headers: &Arc<HeaderNames>,
+) -> Option<ticket_sale_core::Request> {
     use tiny_http::Method::*;
 
     let kind = match (rq.method(), rq.url()) {
@@ -123,33 +294,75 @@ pub fn parse(rq: tiny_http::Request) -> Option<ticket_sale_core::Request> {
         (Get, "/api/admin/num_servers") => RequestKind::GetNumServers,
         (Post, "/api/admin/num_servers") => RequestKind::SetNumServers,
         (Get, "/api/admin/get_servers") => RequestKind::GetServers,
+        (Post, "/api/admin/activate_server") => RequestKind::ActivateServer,
+        (Post, "/api/admin/drain") => RequestKind::DrainServer,
+        (Post, "/api/admin/warmup") => RequestKind::Warmup,
+        (Get, "/api/admin/scaling_state") => RequestKind::GetScalingState,
+        (Get, "/api/admin/server_load") => RequestKind::GetServerLoad,
+        (Post, "/api/admin/reset_reservations") => RequestKind::ResetReservations,
+        (Get, "/api/admin/terminating") => RequestKind::GetTerminatingServers,
         (Get, "/api/num_available_tickets") => RequestKind::NumAvailableTickets,
+        (Get, "/api/local_available_tickets") => RequestKind::LocalAvailableTickets,
+        (Get, "/api/total_available") => RequestKind::GetTotalAvailable,
+        (Get, "/api/capacity") => RequestKind::GetCapacity,
+        (Get, "/api/timeout") => RequestKind::GetTimeout,
+        (Get, "/api/sold_tickets") => RequestKind::GetSoldTickets,
+        (Get, "/api/reserved_count") => RequestKind::GetReservedCount,
+        (Get, "/api/ping") => RequestKind::Ping,
+        (Get, "/api/can_reserve") => RequestKind::CanReserve,
+        (Get, "/api/my_reservations") => RequestKind::GetMyReservations,
         (Post, "/api/reserve_ticket") => RequestKind::ReserveTicket,
+        (Post, "/api/reserve_multiple") => RequestKind::ReserveMultiple,
         (Post, "/api/buy_ticket") => RequestKind::BuyTicket,
+        (Post, "/api/buy_all") => RequestKind::BuyAll,
+        (Post, "/api/abort_all") => RequestKind::AbortAll,
+        (Post, "/api/transfer") => RequestKind::TransferReservation,
         (Post, "/api/abort_purchase") => RequestKind::AbortPurchase,
-        (Get, url) | (Post, url) => {
-            if url.starts_with("/api/debug") {
-                RequestKind::Debug
-            } else {
-                let mut res = Response::from_string(
-                    "🦀 could not find the service you are looking for!
+        (Get, "/api/debug/variant") => RequestKind::GetVariant,
+        // Debugging commands may use any of the supported methods
+        (Get | Post | Put | Delete, url) if url.starts_with("/api/debug") => RequestKind::Debug,
+        (Get, _) | (Post, _) => {
+            let mut res = Response::from_string(
+                "🦀 could not find the service you are looking for!
 
 Valid requests are:
   GET  /api/admin/num_servers
   POST /api/admin/num_servers
   GET  /api/admin/get_servers
+  POST /api/admin/activate_server
+  POST /api/admin/drain
+  POST /api/admin/warmup
+  GET  /api/admin/scaling_state
+  GET  /api/admin/server_load
+  POST /api/admin/reset_reservations
+  GET  /api/admin/terminating
   GET  /api/num_available_tickets
+  GET  /api/local_available_tickets
+  GET  /api/total_available
+  GET  /api/capacity
+  GET  /api/timeout
+  GET  /api/sold_tickets
+  GET  /api/reserved_count
+  GET  /api/ping
+  GET  /api/can_reserve
+  GET  /api/my_reservations
   POST /api/reserve_ticket
+  POST /api/reserve_multiple
   POST /api/buy_ticket
+  POST /api/buy_all
+  POST /api/abort_all
+  POST /api/transfer
   POST /api/abort_purchase
+  GET  /api/debug/variant
   GET  /api/debug(.*)
-  POST /api/debug(.*)",
-                )
-                .with_status_code(404);
-                add_response_cors_headers(&mut res);
-                rq.respond(res).expect("HTTP response failed");
-                return None;
-            }
+  POST /api/debug(.*)
+  PUT  /api/debug(.*)
+  DELETE /api/debug(.*)",
+            )
+            .with_status_code(404);
+            add_response_cors_headers(&mut res);
+            rq.respond(res).expect("HTTP response failed");
+            return None;
         }
         _ => {
             let mut res = Response::empty(405);
@@ -161,23 +374,40 @@ Valid requests are:
 
     let mut cid = None;
     let mut sid = None;
+    let mut format = Format::PlainText;
+    let mut request_id = None;
     for hdr in rq.headers() {
-        if hdr.field.equiv("x-server-id") {
+        let field = hdr.field.as_str().as_str();
+        if field.eq_ignore_ascii_case(&headers.server) {
             if let Ok(id) = Uuid::parse_str(hdr.value.as_str()) {
                 sid = Some(id);
             }
-        } else if hdr.field.equiv("x-customer-id") {
+        } else if field.eq_ignore_ascii_case(&headers.customer) {
             if let Ok(id) = Uuid::parse_str(hdr.value.as_str()) {
                 cid = Some(id);
             }
+        } else if hdr.field.equiv("accept") && hdr.value.as_str().contains("application/json") {
+            format = Format::Json;
+        } else if hdr.field.equiv("x-request-id") {
+            request_id = Some(hdr.value.to_string());
         }
     }
 
+    // Without an id from the client, generate a short random one
+    let request_id =
+        request_id.unwrap_or_else(|| Uuid::new_v4().simple().to_string()[..8].to_owned());
+
     Some(ticket_sale_core::Request::from_raw(
         kind,
         cid.unwrap_or_else(Uuid::new_v4),
         sid,
-        Box::new(HTTPRequest(rq)),
+        Some(request_id.clone()),
+        Box::new(HTTPRequest {
+            inner: rq,
+            format,
+            request_id,
+            headers: headers.clone(),
+        }),
     ))
 }
 
@@ -188,3 +418,27 @@ fn add_response_cors_headers<R: Read>(res: &mut Response<R>) {
     res.add_header(Header::from_bytes(b"Access-Control-Allow-Headers", b"*").unwrap());
     res.add_header(Header::from_bytes(b"Access-Control-Expose-Headers", b"*").unwrap());
 }
+
+/// Add the Content-Type header for JSON responses to `res`
+fn add_content_type_header<R: Read>(res: &mut Response<R>, format: Format) {
+    if format == Format::Json {
+        res.add_header(Header::from_bytes(b"Content-Type", b"application/json").unwrap());
+    }
+}
+
+/// Format `s` as JSON string literal
+fn json_string(s: &str) -> String {
+    let mut json = String::with_capacity(s.len() + 2);
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
@@ -3,11 +3,164 @@
 #![warn(missing_docs)]
 
 mod http;
+mod signal;
 pub mod slug;
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::thread;
 
-use ticket_sale_core::{Config, RequestHandler};
+use http::HeaderNames;
+use serde::Deserialize;
+use signal::ShutdownSignal;
+use ticket_sale_core::{
+    AllocationStrategy, Config, LaunchInfo, RequestHandler, ScaleDownPolicy, ServerSelection,
+};
+use ticket_sale_rocket::Database;
+
+/// Default number of HTTP worker threads, each of which hands its requests to the
+/// shared load balancer
+const DEFAULT_THREADS: u32 = 64;
+
+/// Explanation of how configuration values are combined
+const CONFIG_PRECEDENCE: &str = "Options are applied from left to right: flags given \
+after `-config` override the values from the file, which in turn override the defaults \
+and flags given before `-config`. Keys missing in the file keep their previous value.";
+
+/// Configuration file (TOML) for the ticket sales system
+///
+/// All keys are optional. See [`CONFIG_PRECEDENCE`] for how the values interact with
+/// the command line flags.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    tickets: Option<u32>,
+    timeout: Option<u32>,
+    timeout_jitter: Option<u32>,
+    idempotency_window: Option<u32>,
+    initial_servers: Option<u32>,
+    min_servers: Option<u32>,
+    max_servers: Option<u32>,
+    estimator_roundtrip_time: Option<u32>,
+    estimator_smoothing: Option<f64>,
+    estimator_parallel: Option<bool>,
+    estimator_enabled: Option<bool>,
+    exact_below_servers: Option<u32>,
+    scale_down_policy: Option<String>,
+    server_selection: Option<String>,
+    allocation_strategy: Option<String>,
+    max_batch: Option<u32>,
+    server_queue_capacity: Option<u32>,
+    allow_debug_sleep: Option<bool>,
+    allow_debug_orphan: Option<bool>,
+    ordered_allocation: Option<bool>,
+    reassign_rate: Option<u32>,
+    keep_server_after: Option<u32>,
+    request_window: Option<u32>,
+    session_affinity: Option<f64>,
+    max_requests_per_customer: Option<u32>,
+    admin_token: Option<String>,
+    state: Option<String>,
+    bonus: Option<bool>,
+}
+
+impl ConfigFile {
+    /// Read and parse the configuration file at `path`
+    fn load(path: &str) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| format!("could not read config file {path}: {err}"))?;
+        toml::from_str(&contents).map_err(|err| format!("malformed config file {path}: {err}"))
+    }
+
+    /// Override the values of `config` with the ones given in the file
+    fn apply(self, config: &mut Config) -> Result<(), String> {
+        if let Some(tickets) = self.tickets {
+            config.tickets = tickets;
+        }
+        if let Some(timeout) = self.timeout {
+            config.timeout = timeout;
+        }
+        if let Some(jitter) = self.timeout_jitter {
+            config.timeout_jitter = jitter;
+        }
+        if let Some(window) = self.idempotency_window {
+            config.idempotency_window = window;
+        }
+        if let Some(initial_servers) = self.initial_servers {
+            config.initial_servers = initial_servers;
+        }
+        if let Some(min_servers) = self.min_servers {
+            config.min_servers = min_servers;
+        }
+        if let Some(max_servers) = self.max_servers {
+            config.max_servers = max_servers;
+        }
+        if let Some(time) = self.estimator_roundtrip_time {
+            config.estimator_roundtrip_time = time;
+        }
+        if let Some(smoothing) = self.estimator_smoothing {
+            config.estimator_smoothing = smoothing;
+        }
+        if let Some(parallel) = self.estimator_parallel {
+            config.estimator_parallel = parallel;
+        }
+        if let Some(enabled) = self.estimator_enabled {
+            config.estimator_enabled = enabled;
+        }
+        if let Some(servers) = self.exact_below_servers {
+            config.exact_below_servers = servers;
+        }
+        if let Some(policy) = self.scale_down_policy {
+            config.scale_down_policy = policy.parse()?;
+        }
+        if let Some(selection) = self.server_selection {
+            config.server_selection = selection.parse()?;
+        }
+        if let Some(strategy) = self.allocation_strategy {
+            config.allocation_strategy = strategy.parse()?;
+        }
+        if let Some(max_batch) = self.max_batch {
+            config.max_batch = max_batch;
+        }
+        if let Some(capacity) = self.server_queue_capacity {
+            config.server_queue_capacity = capacity;
+        }
+        if let Some(allow_debug_sleep) = self.allow_debug_sleep {
+            config.allow_debug_sleep = allow_debug_sleep;
+        }
+        if let Some(allow_debug_orphan) = self.allow_debug_orphan {
+            config.allow_debug_orphan = allow_debug_orphan;
+        }
+        if let Some(ordered_allocation) = self.ordered_allocation {
+            config.ordered_allocation = ordered_allocation;
+        }
+        if let Some(reassign_rate) = self.reassign_rate {
+            config.reassign_rate = reassign_rate;
+        }
+        if let Some(keep_server_after) = self.keep_server_after {
+            config.keep_server_after = keep_server_after;
+        }
+        if let Some(request_window) = self.request_window {
+            config.request_window = request_window;
+        }
+        if let Some(affinity) = self.session_affinity {
+            config.session_affinity = affinity;
+        }
+        if let Some(max_requests) = self.max_requests_per_customer {
+            config.max_requests_per_customer = max_requests;
+        }
+        if let Some(token) = self.admin_token {
+            config.admin_token = Some(token);
+        }
+        if let Some(state) = self.state {
+            config.state_path = Some(state.into());
+        }
+        if let Some(bonus) = self.bonus {
+            config.bonus = bonus;
+        }
+        Ok(())
+    }
+}
 
 /// Command line options
 #[derive(Debug)]
@@ -19,8 +172,12 @@ struct Opts {
     port: u16,
     /// Port for the HTTP server to listen on
     host: String,
-    /// Number of load balancer threads
+    /// Number of HTTP worker threads, all sharing one load balancer
     balancer_threads: u32,
+    /// Name of the header carrying the customer id in requests and responses
+    customer_header: String,
+    /// Name of the header carrying the server id in requests and responses
+    server_header: String,
 
     /// Run the sequential “slug” implementation 🐌
     slug: bool,
@@ -34,11 +191,40 @@ impl Opts {
             config: Config {
                 tickets: 1000,
                 timeout: 10,
+                timeout_jitter: 0,
+                idempotency_window: 0,
                 initial_servers: 2,
+                min_servers: 1,
+                max_servers: 1000,
                 estimator_roundtrip_time: 10,
+                estimator_smoothing: 1.0,
+                estimator_parallel: false,
+                estimator_enabled: true,
+                exact_below_servers: 0,
+                scale_down_policy: ScaleDownPolicy::HighestIndex,
+                server_selection: ServerSelection::Random,
+                allocation_strategy: AllocationStrategy::Sqrt,
+                max_batch: u32::MAX,
+                server_queue_capacity: 10_000,
+                allow_debug_sleep: false,
+                allow_debug_orphan: false,
+                ordered_allocation: false,
+                reassign_rate: 150,
+                keep_server_after: 100,
+                request_window: 10,
+                session_affinity: 1.0,
+                max_requests_per_customer: 0,
+                admin_token: None,
+                on_launch: Some(print_launch_info),
+                server_id_seed: None,
+                expiry_sender: None,
+                routing_logger: None,
+                state_path: None,
                 bonus: false,
             },
-            balancer_threads: 64,
+            balancer_threads: DEFAULT_THREADS,
+            customer_header: HeaderNames::default().customer,
+            server_header: HeaderNames::default().server,
             slug: false,
         };
 
@@ -49,20 +235,112 @@ impl Opts {
                     "-port" => opts.port = arg.parse().expect("-port takes a decimal u16"),
                     "-host" => opts.host = arg,
                     "-tickets" => {
-                        opts.config.tickets = arg.parse().expect("-tickets takes a decimal u32")
+                        let tickets: u64 = arg.parse().expect("-tickets takes a decimal u64");
+                        // Ticket counts are `u32` throughout the system, reject instead of
+                        // truncating
+                        opts.config.tickets = u32::try_from(tickets).unwrap_or_else(|_| {
+                            eprintln!("Error: -tickets must be at most {}", u32::MAX);
+                            std::process::exit(1);
+                        })
+                    }
+                    "-balancer-threads" | "-threads" => {
+                        opts.balancer_threads = arg.parse().expect("-threads takes a decimal u32")
                     }
-                    "-balancer-threads" => {
-                        opts.balancer_threads =
-                            arg.parse().expect("-balancer-threads takes a decimal u32")
+                    "-min-servers" => {
+                        opts.config.min_servers =
+                            arg.parse().expect("-min-servers takes a decimal u32")
+                    }
+                    "-max-servers" => {
+                        opts.config.max_servers =
+                            arg.parse().expect("-max-servers takes a decimal u32")
                     }
                     "-timeout" => {
                         opts.config.timeout = arg.parse().expect("-timeout takes a decimal u32")
                     }
+                    "-timeout-jitter" => {
+                        opts.config.timeout_jitter =
+                            arg.parse().expect("-timeout-jitter takes a decimal u32")
+                    }
+                    "-idempotency-window" => {
+                        opts.config.idempotency_window = arg
+                            .parse()
+                            .expect("-idempotency-window takes a decimal u32")
+                    }
                     "-estimator-roundtrip-time" => {
                         opts.config.estimator_roundtrip_time = arg
                             .parse()
                             .expect("-estimator-roundtrip-time takes a decimal u32")
                     }
+                    "-estimator-smoothing" => {
+                        opts.config.estimator_smoothing = arg
+                            .parse()
+                            .expect("-estimator-smoothing takes a decimal f64")
+                    }
+                    "-exact-below-servers" => {
+                        opts.config.exact_below_servers = arg
+                            .parse()
+                            .expect("-exact-below-servers takes a decimal u32")
+                    }
+                    "-max-batch" => {
+                        opts.config.max_batch = arg.parse().expect("-max-batch takes a decimal u32")
+                    }
+                    "-server-queue-capacity" => {
+                        opts.config.server_queue_capacity = arg
+                            .parse()
+                            .expect("-server-queue-capacity takes a decimal u32")
+                    }
+                    "-reassign-rate" => {
+                        opts.config.reassign_rate =
+                            arg.parse().expect("-reassign-rate takes a decimal u32")
+                    }
+                    "-keep-server-after" => {
+                        opts.config.keep_server_after =
+                            arg.parse().expect("-keep-server-after takes a decimal u32")
+                    }
+                    "-request-window" => {
+                        opts.config.request_window =
+                            arg.parse().expect("-request-window takes a decimal u32")
+                    }
+                    "-session-affinity" => {
+                        opts.config.session_affinity =
+                            arg.parse().expect("-session-affinity takes a decimal f64")
+                    }
+                    "-max-requests-per-customer" => {
+                        opts.config.max_requests_per_customer = arg
+                            .parse()
+                            .expect("-max-requests-per-customer takes a decimal u32")
+                    }
+                    "-admin-token" => opts.config.admin_token = Some(arg),
+                    "-state" => opts.config.state_path = Some(arg.into()),
+                    "-customer-header" => opts.customer_header = arg,
+                    "-server-header" => opts.server_header = arg,
+                    "-scale-down-policy" => {
+                        opts.config.scale_down_policy = arg.parse().unwrap_or_else(|err| {
+                            eprintln!("Error: {err}");
+                            std::process::exit(1);
+                        })
+                    }
+                    "-server-selection" => {
+                        opts.config.server_selection = arg.parse().unwrap_or_else(|err| {
+                            eprintln!("Error: {err}");
+                            std::process::exit(1);
+                        })
+                    }
+                    "-allocation-strategy" => {
+                        opts.config.allocation_strategy = arg.parse().unwrap_or_else(|err| {
+                            eprintln!("Error: {err}");
+                            std::process::exit(1);
+                        })
+                    }
+                    "-config" => {
+                        match ConfigFile::load(&arg).and_then(|file| file.apply(&mut opts.config)) {
+                            Ok(()) => {}
+                            Err(err) => {
+                                eprintln!("Error: {err}\n{CONFIG_PRECEDENCE}");
+                                std::process::exit(1);
+                            }
+                        }
+                    }
                     _ => {
                         eprintln!("Error: ignoring unknown option {opt}");
                         std::process::exit(1);
@@ -72,6 +350,11 @@ impl Opts {
             } else {
                 match arg.as_str() {
                     "-bonus" => opts.config.bonus = true,
+                    "-allow-debug-sleep" => opts.config.allow_debug_sleep = true,
+                    "-allow-debug-orphan" => opts.config.allow_debug_orphan = true,
+                    "-ordered-allocation" => opts.config.ordered_allocation = true,
+                    "-estimator-parallel" => opts.config.estimator_parallel = true,
+                    "-no-estimator" => opts.config.estimator_enabled = false,
                     "-slug" => opts.slug = true,
                     _ => option = Some(arg),
                 }
@@ -81,37 +364,146 @@ impl Opts {
             eprintln!("Error: ignoring leftover option {opt}");
             std::process::exit(1);
         }
+        if opts.config.min_servers > opts.config.max_servers {
+            eprintln!("Error: -min-servers must not be greater than -max-servers");
+            std::process::exit(1);
+        }
+        if let Some(path) = &opts.config.state_path {
+            // Starting without a broken state file could sell the sold tickets again
+            if let Err(err) = Database::restore_from(path, opts.config.ordered_allocation) {
+                eprintln!("Error: -state {} cannot be restored: {err}", path.display());
+                std::process::exit(1);
+            }
+        }
+        if opts.config.reassign_rate >= 10000 {
+            eprintln!("Error: -reassign-rate must be below 10000");
+            std::process::exit(1);
+        }
+        if !(0.0..=1.0).contains(&opts.config.session_affinity) {
+            eprintln!("Error: -session-affinity must be in [0, 1]");
+            std::process::exit(1);
+        }
+        if opts.balancer_threads == 0 {
+            eprintln!("Error: -threads must be positive");
+            std::process::exit(1);
+        }
+        if opts.config.max_batch == 0 {
+            eprintln!("Error: -max-batch must be positive");
+            std::process::exit(1);
+        }
+        if opts.config.server_queue_capacity == 0 {
+            eprintln!("Error: -server-queue-capacity must be positive");
+            std::process::exit(1);
+        }
+        if !(opts.config.estimator_smoothing > 0.0 && opts.config.estimator_smoothing <= 1.0) {
+            eprintln!("Error: -estimator-smoothing must be in (0, 1]");
+            std::process::exit(1);
+        }
+        for (flag, name) in [
+            ("-customer-header", &opts.customer_header),
+            ("-server-header", &opts.server_header),
+        ] {
+            if !http::is_valid_header_name(name) {
+                eprintln!("Error: {flag} must be non-empty ASCII without whitespace or colons");
+                std::process::exit(1);
+            }
+        }
+        if opts
+            .customer_header
+            .eq_ignore_ascii_case(&opts.server_header)
+        {
+            eprintln!("Error: -customer-header and -server-header must differ");
+            std::process::exit(1);
+        }
 
         opts
     }
 }
 
-fn http_loop<H: RequestHandler>(server: &tiny_http::Server, handler: &H) {
+/// Print a summary of the launched ticket sales system
+fn print_launch_info(info: &LaunchInfo) {
+    println!(
+        "Launched {} implementation with {} tickets, timeout {}s, estimator roundtrip \
+         time {}s, and {} servers:",
+        if info.bonus { "bonus" } else { "standard" },
+        info.tickets,
+        info.timeout,
+        info.estimator_roundtrip_time,
+        info.servers.len(),
+    );
+    for server in &info.servers {
+        println!("  {server}");
+    }
+}
+
+fn http_loop<H: RequestHandler>(
+    server: &tiny_http::Server,
+    handler: &H,
+    headers: &Arc<HeaderNames>,
+    shutting_down: &AtomicBool,
+) {
     loop {
-        let rq = server.recv().expect("HTTP receive failed");
-        if let Some(rq) = http::parse(rq) {
-            handler.handle(rq);
+        match server.recv() {
+            Ok(rq) => {
+                if let Some(rq) = http::parse(rq, headers) {
+                    handler.handle(rq);
+                }
+            }
+            // We were unblocked for shutting down
+            Err(_) if shutting_down.load(Ordering::Relaxed) => return,
+            Err(err) => panic!("HTTP receive failed: {err}"),
         }
     }
 }
 
+/// Serve requests with the given number of threads until SIGINT or SIGTERM arrives
+///
+/// All threads call [`RequestHandler::handle`] on the same `handler` concurrently,
+/// which the `Sync` bound makes safe.
+fn serve<H: RequestHandler + Sync>(
+    server: &tiny_http::Server,
+    handler: &H,
+    headers: &Arc<HeaderNames>,
+    threads: u32,
+    signal: &ShutdownSignal,
+) {
+    let shutting_down = AtomicBool::new(false);
+    thread::scope(|s| {
+        for i in 0..threads {
+            thread::Builder::new()
+                .name(format!("balancer_{i}"))
+                .spawn_scoped(s, || http_loop(server, handler, headers, &shutting_down))
+                .unwrap();
+        }
+
+        // Unblock each thread once, so that it leaves its loop after answering the
+        // requests received so far
+        signal.wait();
+        shutting_down.store(true, Ordering::Relaxed);
+        for _ in 0..threads {
+            server.unblock();
+        }
+    });
+}
+
 fn main() {
     let opts = Opts::from_args();
 
     let server = tiny_http::Server::http((opts.host.as_str(), opts.port)).unwrap();
+    let signal = ShutdownSignal::install().expect("Installing the signal handlers failed");
+    let headers = Arc::new(HeaderNames {
+        customer: opts.customer_header,
+        server: opts.server_header,
+    });
 
     if opts.slug {
-        http_loop(&server, &slug::Server::new(&opts.config));
+        let slug = slug::Server::new(&opts.config);
+        serve(&server, &slug, &headers, 1, &signal);
+        slug.shutdown();
     } else {
         let balancer = ticket_sale_rocket::launch(&opts.config);
-
-        thread::scope(|s| {
-            for i in 0..opts.balancer_threads {
-                thread::Builder::new()
-                    .name(format!("balancer_{i}"))
-                    .spawn_scoped(s, || http_loop(&server, &balancer))
-                    .unwrap();
-            }
-        });
+        serve(&server, &balancer, &headers, opts.balancer_threads, &signal);
+        // Wait for the estimator and the servers to terminate
+        balancer.shutdown();
     }
 }
@@ -0,0 +1,56 @@
+//! Waiting for SIGINT and SIGTERM via the self-pipe trick
+
+use std::io;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// Write end of the pipe, written to by the signal handler
+static PIPE_WRITE_FD: AtomicI32 = AtomicI32::new(-1);
+
+/// Signal handler waking up the thread waiting for the signal
+extern "C" fn notify(_signal: libc::c_int) {
+    let fd = PIPE_WRITE_FD.load(Ordering::Relaxed);
+    // Unlike most functions, `write` may be called from a signal handler
+    unsafe { libc::write(fd, [0u8].as_ptr().cast(), 1) };
+}
+
+/// Receiver for SIGINT and SIGTERM
+pub struct ShutdownSignal {
+    /// Read end of the pipe
+    read_fd: libc::c_int,
+}
+
+impl ShutdownSignal {
+    /// Install the handlers for SIGINT and SIGTERM
+    ///
+    /// Must be called at most once per process.
+    pub fn install() -> io::Result<Self> {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        PIPE_WRITE_FD.store(fds[1], Ordering::Relaxed);
+
+        let handler = notify as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        for signal in [libc::SIGINT, libc::SIGTERM] {
+            if unsafe { libc::signal(signal, handler) } == libc::SIG_ERR {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        Ok(Self { read_fd: fds[0] })
+    }
+
+    /// Block until SIGINT or SIGTERM arrives
+    pub fn wait(&self) {
+        let mut buf = [0u8; 1];
+        loop {
+            if unsafe { libc::read(self.read_fd, buf.as_mut_ptr().cast(), 1) } == 1 {
+                return;
+            }
+            // The read may be interrupted by another signal, try again then
+            let err = io::Error::last_os_error();
+            if err.kind() != io::ErrorKind::Interrupted {
+                panic!("Reading the signal pipe failed: {err}");
+            }
+        }
+    }
+}
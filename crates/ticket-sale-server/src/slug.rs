@@ -6,7 +6,7 @@ use std::time::Instant;
 
 use parking_lot::Mutex;
 use ticket_sale_core::Config;
-use ticket_sale_core::{Request, RequestHandler, RequestKind};
+use ticket_sale_core::{Request, RequestError, RequestHandler, RequestKind};
 use uuid::Uuid;
 
 #[derive(Debug)]
@@ -42,6 +42,12 @@ struct ServerInner {
 
     /// Reservation timeout in seconds
     reservation_timeout: u32,
+
+    /// Number of sold tickets
+    sold: u32,
+
+    /// Number of initially available tickets
+    tickets: u32,
 }
 
 impl ServerInner {
@@ -70,21 +76,66 @@ impl ServerInner {
                 rq.respond_with_int(1);
             }
             RequestKind::SetNumServers => {
-                if rq.read_u32().is_some() {
+                if let Some(num_servers) = rq.read_u32() {
                     // In your implementation, you need to support this request
                     // for on-demand scaling. After scaling, you should respond
-                    // with the number of servers.
-                    rq.respond_with_err("Slug does not support on-demand scaling!");
+                    // with the number of servers. The slug only supports its
+                    // single server.
+                    if num_servers == 1 {
+                        rq.respond_with_int(1);
+                    } else {
+                        rq.respond_with_err("Slug does not support on-demand scaling!");
+                    }
                 } else {
                     rq.respond_with_err("No number of servers provided!");
                 }
             }
             RequestKind::GetServers => rq.respond_with_server_list(&[self.id]),
+            RequestKind::GetTerminatingServers => rq.respond_with_server_list(&[]),
+            RequestKind::ActivateServer | RequestKind::DrainServer => {
+                rq.respond_with_err("Slug does not support on-demand scaling!");
+            }
+            // The single server already holds all tickets, there is nothing to hand out
+            RequestKind::Warmup => rq.respond_with_int(0),
+            // The slug never scales, so scaling is always settled
+            RequestKind::GetScalingState => rq.respond_with_int(0),
+            RequestKind::GetTimeout => rq.respond_with_int(self.reservation_timeout),
+            RequestKind::GetSoldTickets => rq.respond_with_int(self.sold),
+            RequestKind::GetCapacity => rq.respond_with_int(self.tickets),
+            RequestKind::GetReservedCount => {
+                self.clear_reservations();
+                rq.respond_with_int(self.reservations.len() as u32)
+            }
+            RequestKind::GetServerLoad => {
+                self.clear_reservations();
+                rq.respond_with_string(format!("{} {}", self.id, self.reservations.len()))
+            }
+            RequestKind::ResetReservations => {
+                let released = self.reservations.len() as u32;
+                let tickets = self.reservations.drain().map(|(_, res)| res.ticket);
+                self.available_tickets.extend(tickets);
+                rq.respond_with_int(released)
+            }
+            RequestKind::Ping => rq.respond_with_int(0),
+            RequestKind::GetVariant => rq.respond_with_string("slug"),
 
             // Handling the following requests will remain the Server's responsibility.
-            RequestKind::NumAvailableTickets => {
+            RequestKind::NumAvailableTickets
+            | RequestKind::LocalAvailableTickets
+            | RequestKind::GetTotalAvailable => {
                 rq.respond_with_int(self.available_tickets.len() as u32)
             }
+            RequestKind::CanReserve => {
+                // This request requires us to respond with a server id
+                rq.set_server_id(self.id);
+
+                // A reservation succeeds if the customer has none yet and there
+                // is a ticket left
+                let customer = rq.customer_id();
+                let possible = !self.reservations.contains_key(&customer)
+                    && !self.available_tickets.is_empty();
+                rq.respond_with_int(possible as u32);
+            }
             RequestKind::ReserveTicket => {
                 // This request requires us to respond with a server id
                 rq.set_server_id(self.id);
@@ -94,7 +145,10 @@ impl ServerInner {
                     Entry::Occupied(_) => {
                         // We do not allow a customer to reserve more than a
                         // ticket at a time.
-                        rq.respond_with_err("A ticket has already been reserved!");
+                        rq.respond_with_err_kind(
+                            RequestError::Conflict,
+                            "A ticket has already been reserved!",
+                        );
                     }
                     Entry::Vacant(entry) => {
                         // Try to take a ticket from the stack of available
@@ -119,21 +173,39 @@ impl ServerInner {
                     if let Some(res) = self.reservations.get(&cid) {
                         if ticket != res.ticket {
                             // The id does not match the id of the reservation
-                            rq.respond_with_err("Invalid ticket id provided!");
+                            rq.respond_with_err_kind(
+                                RequestError::Conflict,
+                                "Invalid ticket id provided!",
+                            );
                         } else {
                             // Sell the ticket to the customer
                             self.reservations.remove(&cid);
+                            self.sold += 1;
                             rq.respond_with_int(ticket);
                         }
                     } else {
                         // Without a reservation there is nothing to buy.
-                        rq.respond_with_err("No ticket has been reserved!")
+                        rq.respond_with_err_kind(
+                            RequestError::Conflict,
+                            "No ticket has been reserved!",
+                        )
                     }
                 } else {
                     // The client is supposed to provide a ticket id.
                     rq.respond_with_err("No ticket id provided!");
                 }
             }
+            RequestKind::GetMyReservations => {
+                // This request requires us to respond with a server id
+                rq.set_server_id(self.id);
+
+                // Expired reservations are no longer held by the customer
+                self.clear_reservations();
+                match self.reservations.get(&rq.customer_id()) {
+                    Some(res) => rq.respond_with_int_list(&[res.ticket]),
+                    None => rq.respond_with_int_list(&[]),
+                }
+            }
             RequestKind::AbortPurchase => {
                 // This request requires us to respond with a server id
                 rq.set_server_id(self.id);
@@ -143,7 +215,10 @@ impl ServerInner {
                     if let Some(res) = self.reservations.get(&cid) {
                         if ticket != res.ticket {
                             // The id does not match the id of the reservation.
-                            rq.respond_with_err("Invalid ticket id provided!");
+                            rq.respond_with_err_kind(
+                                RequestError::Conflict,
+                                "Invalid ticket id provided!",
+                            );
                         } else {
                             // Abort the reservation and put the ticket back on the stack.
                             self.reservations.remove(&cid);
@@ -154,7 +229,10 @@ impl ServerInner {
                         }
                     } else {
                         // Without a reservation there is nothing to abort.
-                        rq.respond_with_err("No ticket has been reserved!")
+                        rq.respond_with_err_kind(
+                            RequestError::Conflict,
+                            "No ticket has been reserved!",
+                        )
                     }
                 } else {
                     // The client is supposed to provide a ticket id.
@@ -162,6 +240,13 @@ impl ServerInner {
                 }
             }
 
+            RequestKind::ReserveMultiple | RequestKind::BuyAll | RequestKind::AbortAll => {
+                rq.respond_with_err("Slug does not support reserving multiple tickets!");
+            }
+            RequestKind::TransferReservation => {
+                rq.respond_with_err("Slug does not support transferring reservations!");
+            }
+
             // Debug requests you may freely use to debug your implementation.
             RequestKind::Debug => {
                 // 📌 Hint: You can use `rq.url()` and `rq.method()` to
@@ -193,6 +278,8 @@ impl Server {
             available_tickets: (0..config.tickets).collect(),
             reservations: HashMap::new(),
             reservation_timeout: config.timeout,
+            sold: 0,
+            tickets: config.tickets,
         };
         Self(Mutex::new(inner))
     }
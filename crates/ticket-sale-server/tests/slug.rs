@@ -0,0 +1,160 @@
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Slug server process, killed when dropped
+struct Slug {
+    child: Child,
+    port: u16,
+    /// Name of the header carrying the customer id
+    customer_header: String,
+}
+
+impl Slug {
+    /// Start the slug on a free port and wait until it accepts connections
+    fn start() -> Slug {
+        Self::start_with_customer_header("X-Customer-Id")
+    }
+
+    /// Start the slug reading and sending customer ids in the given header
+    fn start_with_customer_header(customer_header: &str) -> Slug {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let child = Command::new(env!("CARGO_BIN_EXE_ticket-sale-server"))
+            .args(["-slug", "-host", "127.0.0.1", "-port", &port.to_string()])
+            .args(["-customer-header", customer_header])
+            .spawn()
+            .expect("Starting the server failed");
+        let slug = Slug {
+            child,
+            port,
+            customer_header: customer_header.to_owned(),
+        };
+
+        let start = Instant::now();
+        while TcpStream::connect(("127.0.0.1", port)).is_err() {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "The slug did not start."
+            );
+            thread::sleep(Duration::from_millis(10));
+        }
+        slug
+    }
+
+    /// Send a POST request, optionally as the given customer
+    fn post(&self, url: &str, customer: Option<&str>, body: &str) -> Response {
+        self.request("POST", url, customer, body)
+    }
+
+    /// Send a request with the given method, optionally as the given customer
+    fn request(&self, method: &str, url: &str, customer: Option<&str>, body: &str) -> Response {
+        let mut stream = TcpStream::connect(("127.0.0.1", self.port)).unwrap();
+        let customer_header = customer
+            .map(|customer| format!("{}: {customer}\r\n", self.customer_header))
+            .unwrap_or_default();
+        write!(
+            stream,
+            "{method} {url} HTTP/1.1\r\nHost: localhost\r\n{customer_header}Content-Length: {}\r\n\
+             Connection: close\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+        let customer = head
+            .lines()
+            .filter_map(|line| line.split_once(": "))
+            .find(|(name, _)| name.eq_ignore_ascii_case(&self.customer_header))
+            .map(|(_, value)| value.to_owned());
+        Response {
+            status,
+            customer,
+            body: body.to_owned(),
+        }
+    }
+}
+
+/// Status code, customer id header, and body of a response
+struct Response {
+    status: u16,
+    customer: Option<String>,
+    body: String,
+}
+
+impl Drop for Slug {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn test_slug_scales_to_one_server() {
+    let slug = Slug::start();
+
+    let response = slug.post("/api/admin/num_servers", None, "1");
+    assert_eq!(
+        (response.status, response.body.as_str()),
+        (200, "1"),
+        "Scaling the slug to its single server must succeed."
+    );
+    let response = slug.post("/api/admin/num_servers", None, "2");
+    assert_eq!(
+        response.status, 400,
+        "The slug cannot scale to more than one server."
+    );
+
+    // After scaling, customers can reserve and buy tickets as usual
+    let reservation = slug.post("/api/reserve_ticket", None, "");
+    assert_eq!(reservation.status, 200);
+    let customer = reservation
+        .customer
+        .expect("Responses carry the customer id.");
+    let purchase = slug.post("/api/buy_ticket", Some(&customer), &reservation.body);
+    assert_eq!((purchase.status, purchase.body), (200, reservation.body));
+}
+
+#[test]
+fn test_slug_custom_customer_header() {
+    let slug = Slug::start_with_customer_header("X-Session");
+
+    let reservation = slug.post("/api/reserve_ticket", None, "");
+    assert_eq!(reservation.status, 200);
+    let customer = reservation
+        .customer
+        .expect("Responses carry the customer id in the custom header.");
+
+    // The customer is recognized by the id given in the custom header
+    let purchase = slug.post("/api/buy_ticket", Some(&customer), &reservation.body);
+    assert_eq!((purchase.status, purchase.body), (200, reservation.body));
+    assert_eq!(purchase.customer.as_deref(), Some(customer.as_str()));
+}
+
+#[test]
+fn test_slug_debug_methods() {
+    let slug = Slug::start();
+
+    // Debugging commands accept all supported methods
+    for method in ["GET", "POST", "PUT", "DELETE"] {
+        let response = slug.request(method, "/api/debug/reservations", None, "");
+        assert_eq!(
+            response.status, 200,
+            "{method} must be accepted for debugging commands."
+        );
+    }
+
+    // The other routes still reject unexpected methods
+    let response = slug.request("PUT", "/api/reserve_ticket", None, "");
+    assert_eq!(response.status, 405);
+    let response = slug.request("DELETE", "/api/admin/num_servers", None, "");
+    assert_eq!(response.status, 405);
+}
@@ -3,7 +3,7 @@
 use std::ffi::c_void;
 use std::sync::OnceLock;
 
-use eyre::Result;
+use eyre::{ensure, Result};
 use jni::{
     objects::{GlobalRef, JClass, JMethodID, JPrimitiveArray, JString, JValue, ReleaseMode},
     signature::{Primitive, ReturnType},
@@ -11,7 +11,7 @@ use jni::{
     InitArgsBuilder, JNIEnv, JavaVM, NativeMethod,
 };
 use parking_lot::Mutex;
-use ticket_sale_core::RequestKind;
+use ticket_sale_core::{RequestError, RequestKind};
 use tokio::sync::oneshot;
 use tokio::task::{self, JoinHandle};
 use uuid::Uuid;
@@ -94,6 +94,11 @@ pub async fn start(
                 fn_ptr: Java_com_pseuco_cp24_request_MockRequest_respondWithServerIds
                     as *mut c_void,
             },
+            NativeMethod {
+                name: "respondWithIntList".into(),
+                sig: "(J[IZJJJJ)V".into(), // spell-checker:disable-line
+                fn_ptr: Java_com_pseuco_cp24_request_MockRequest_respondWithIntList as *mut c_void,
+            },
             NativeMethod {
                 name: "writeOutByte".into(),
                 sig: "(I)V".into(),
@@ -122,9 +127,17 @@ pub async fn start(
     // spell-checker:disable-next-line
     let mock_request_init = env.get_method_id(&mock_request_cls, "<init>", "(JIJJZJJI)V")?;
 
-    assert!(config.tickets <= i32::MAX as u32);
-    assert!(config.timeout <= i32::MAX as u32);
-    assert!(config.estimator_roundtrip_time <= i32::MAX as u32);
+    // Java only has signed integers, so ticket ids must fit into an `int`
+    ensure!(
+        config.tickets <= i32::MAX as u32,
+        "The Java implementation supports at most {} tickets",
+        i32::MAX
+    );
+    ensure!(config.timeout <= i32::MAX as u32, "Timeout out of range");
+    ensure!(
+        config.estimator_roundtrip_time <= i32::MAX as u32,
+        "Estimator roundtrip time out of range"
+    );
     let j_config = env.new_object(
         "com/pseuco/cp24/Config",
         "(IIIZ)V", // spell-checker:disable-line
@@ -203,11 +216,20 @@ impl JniContext {
     ) -> Result<()> {
         let (cid_m, cid_l) = customer_id.as_u64_pair();
         let (sid_m, sid_l) = server_id.unwrap_or_default().as_u64_pair();
+        // Java represents a missing payload by -1, so only payloads fitting into an
+        // `int` can be passed
         let payload = match payload {
-            Some(i) => {
-                debug_assert!(i <= i32::MAX as u32);
-                i as i32
+            Some(i) if i > i32::MAX as u32 => {
+                let response = Response::Error {
+                    kind: RequestError::BadRequest,
+                    msg: format!("Payload {i} exceeds the range of a Java int"),
+                    server_id,
+                    customer_id,
+                };
+                let _ = response_channel.send(response);
+                return Ok(());
             }
+            Some(i) => i as i32,
             None => -1,
         };
 
@@ -307,6 +329,7 @@ extern "system" fn Java_com_pseuco_cp24_request_MockRequest_respondWithError<'lo
         Err(_) => String::new(),
     };
     let response = Response::Error {
+        kind: RequestError::BadRequest,
         msg,
         server_id,
         customer_id,
@@ -334,11 +357,16 @@ extern "system" fn Java_com_pseuco_cp24_request_MockRequest_respondWithInt<'loca
         Some(Uuid::from_u64_pair(server_msb as u64, server_lsb as u64))
     };
     let customer_id = Uuid::from_u64_pair(customer_msb as u64, customer_lsb as u64);
-    debug_assert!(int >= 0);
-    let response = Response::Int {
-        i: int as u32,
-        server_id,
-        customer_id,
+    // A negative int would wrap around to a large ticket id
+    let response = match u32::try_from(int) {
+        Ok(i) => {
+            Response::Int {
+                i,
+                server_id,
+                customer_id,
+            }
+        }
+        Err(_) => negative_int_error(int, server_id, customer_id),
     };
     response_channel.send(response).unwrap();
 }
@@ -392,6 +420,63 @@ extern "system" fn Java_com_pseuco_cp24_request_MockRequest_respondWithServerIds
     response_channel.send(Response::ServerList(ids)).unwrap();
 }
 
+#[no_mangle]
+extern "system" fn Java_com_pseuco_cp24_request_MockRequest_respondWithIntList<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    response_ptr: jlong,
+    ints: JPrimitiveArray<'local, jint>,
+    has_server_id: jboolean,
+    server_lsb: jlong,
+    server_msb: jlong,
+    customer_lsb: jlong,
+    customer_msb: jlong,
+) {
+    // SAFETY: We recreate the channel passed to `JniContext::make_request()`
+    let response_channel = unsafe { channel_from_jni(response_ptr) };
+    let server_id = if has_server_id == 0 {
+        None
+    } else {
+        Some(Uuid::from_u64_pair(server_msb as u64, server_lsb as u64))
+    };
+    let customer_id = Uuid::from_u64_pair(customer_msb as u64, customer_lsb as u64);
+
+    // SAFETY: `ints` is freshly created on the Java side and “moved” here.
+    // Moreover, there are no concurrent JNI calls in this thread.
+    let ints = match unsafe { env.get_array_elements_critical(&ints, ReleaseMode::NoCopyBack) } {
+        Ok(elements) => {
+            elements
+                .iter()
+                .map(|&i| u32::try_from(i).map_err(|_| i))
+                .collect()
+        }
+        Err(_) => Ok(Vec::new()),
+    };
+
+    let response = match ints {
+        Ok(ints) => {
+            Response::IntList {
+                ints,
+                server_id,
+                customer_id,
+            }
+        }
+        Err(int) => negative_int_error(int, server_id, customer_id),
+    };
+    response_channel.send(response).unwrap();
+}
+
+/// Error response for a negative int sent by the Java side, which would otherwise
+/// be misinterpreted as a large `u32`
+fn negative_int_error(int: jint, server_id: Option<Uuid>, customer_id: Uuid) -> Response {
+    Response::Error {
+        kind: RequestError::BadRequest,
+        msg: format!("Invalid negative int {int} in response"),
+        server_id,
+        customer_id,
+    }
+}
+
 #[no_mangle]
 extern "system" fn Java_com_pseuco_cp24_request_MockRequest_writeOutByte<'local>(
     _env: JNIEnv<'local>,
@@ -1,8 +1,16 @@
 //! Mock API implementation directly using the `ticket-sale-rocket` crate
 
+use std::io;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
-use ticket_sale_core::{RawRequest, Request, RequestHandler, RequestKind};
+use eyre::{eyre, Result};
+use nanorand::{Rng, WyRand};
+use ticket_sale_core::{
+    RawRequest, Request, RequestError, RequestHandler, RequestKind, RequestMethod,
+};
+use ticket_sale_rocket::{Database, ShutdownReport};
 use tokio::sync::oneshot;
 use tokio::task::{self, JoinHandle};
 use uuid::Uuid;
@@ -15,35 +23,97 @@ pub struct MockBalancer {
 }
 
 struct MockRawRequest {
+    url: Option<String>,
+    method: Option<RequestMethod>,
+    body: Option<Vec<u8>>,
     payload: Option<u32>,
     kind: RequestKind,
+    authorization: Option<String>,
+    headers: Vec<(String, String)>,
     response_channel: oneshot::Sender<Response>,
 }
 
-pub async fn start(threads: u16, config: ticket_sale_core::Config) -> (MockBalancer, Api) {
-    let balancer = Arc::new(
-        tokio::task::spawn_blocking(move || ticket_sale_rocket::launch(&config))
-            .await
-            .unwrap(),
-    );
+/// How the mock dispatches requests to the balancer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MockOptions {
+    /// Number of worker threads handing requests to the balancer concurrently
+    pub workers: usize,
+    /// Seed for shuffling the requests queued at a worker before handing them to
+    /// the balancer, or `None` to hand them over in FIFO order
+    pub shuffle_seed: Option<u64>,
+}
+
+impl MockOptions {
+    /// A single worker handing the requests over one at a time in FIFO order
+    ///
+    /// The balancer then processes the requests in the order in which they were
+    /// sent, so a test can replay a specific interleaving.
+    pub fn deterministic() -> Self {
+        Self {
+            workers: 1,
+            shuffle_seed: None,
+        }
+    }
+}
+
+pub async fn start(options: MockOptions, config: ticket_sale_core::Config) -> (MockBalancer, Api) {
+    let balancer = tokio::task::spawn_blocking(move || ticket_sale_rocket::launch(&config))
+        .await
+        .unwrap();
+    serve(options, balancer)
+}
 
-    let it = (0..threads).map(|_| {
+/// Start the ticket sales system with the given database instead of a new one
+pub async fn start_with_database(
+    options: MockOptions,
+    config: ticket_sale_core::Config,
+    database: Arc<parking_lot::Mutex<Database>>,
+) -> (MockBalancer, Api) {
+    let balancer = tokio::task::spawn_blocking(move || {
+        ticket_sale_rocket::launch_with_database(&config, database)
+    })
+    .await
+    .unwrap();
+    serve(options, balancer)
+}
+
+/// Start the ticket sales system from a snapshot written by [`MockBalancer::persist`]
+pub async fn start_from_snapshot(
+    options: MockOptions,
+    config: ticket_sale_core::Config,
+    path: PathBuf,
+) -> io::Result<(MockBalancer, Api)> {
+    let balancer = tokio::task::spawn_blocking(move || {
+        ticket_sale_rocket::launch_from_snapshot(&config, &path)
+    })
+    .await
+    .unwrap()?;
+    Ok(serve(options, balancer))
+}
+
+fn serve(options: MockOptions, balancer: ticket_sale_rocket::Balancer) -> (MockBalancer, Api) {
+    assert_ne!(options.workers, 0);
+    let balancer = Arc::new(balancer);
+
+    let it = (0..options.workers).map(|worker| {
         let (sender, receiver) = flume::bounded::<RequestMsg>(65536);
         let balancer = balancer.clone();
+        // Give each worker its own sequence of shuffles
+        let mut rng = options
+            .shuffle_seed
+            .map(|seed| WyRand::new_seed(seed.wrapping_add(worker as u64)));
         let handle = task::spawn_blocking(move || {
             let balancer = &*balancer;
-            for msg in receiver.into_iter() {
-                let raw = Box::new(MockRawRequest {
-                    payload: msg.payload,
-                    kind: msg.kind,
-                    response_channel: msg.response_channel,
-                });
-                balancer.handle(Request::from_raw(
-                    msg.kind,
-                    msg.customer_id,
-                    msg.server_id,
-                    raw,
-                ))
+            while let Ok(msg) = receiver.recv() {
+                let mut batch = vec![msg];
+                // Shuffle the requests that queued up while the last batch was handled
+                if let Some(rng) = &mut rng {
+                    batch.extend(receiver.try_iter());
+                    rng.shuffle(&mut batch);
+                }
+                for msg in batch {
+                    hand_over(balancer, msg);
+                }
             }
         });
         (sender, handle)
@@ -57,48 +127,167 @@ pub async fn start(threads: u16, config: ticket_sale_core::Config) -> (MockBalan
     (mock_balancer, Api::new(senders))
 }
 
+/// Hand a request over to the balancer
+fn hand_over(balancer: &ticket_sale_rocket::Balancer, msg: RequestMsg) {
+    let raw = Box::new(MockRawRequest {
+        url: msg.url,
+        method: msg.method,
+        body: msg.body,
+        payload: msg.payload,
+        kind: msg.kind,
+        authorization: msg.authorization,
+        headers: msg.headers,
+        response_channel: msg.response_channel,
+    });
+    balancer.handle(Request::from_raw(
+        msg.kind,
+        msg.customer_id,
+        msg.server_id,
+        None,
+        raw,
+    ))
+}
+
 impl MockBalancer {
-    pub async fn shutdown(self) {
-        for handle in self.join_handles {
-            handle.await.unwrap()
-        }
-        task::spawn_blocking(move || Arc::into_inner(self.balancer).unwrap().shutdown())
+    pub async fn persist(&self, path: PathBuf) -> io::Result<()> {
+        let balancer = self.balancer.clone();
+        task::spawn_blocking(move || balancer.persist(&path))
+            .await
+            .unwrap()
+    }
+
+    /// Drain all servers for up to `drain_secs` seconds and shut them down, see
+    /// [`ticket_sale_rocket::Balancer::graceful_shutdown`]
+    pub async fn graceful_shutdown(&self, drain_secs: u32) {
+        let balancer = self.balancer.clone();
+        task::spawn_blocking(move || balancer.graceful_shutdown(drain_secs))
             .await
-            .unwrap();
+            .unwrap()
+    }
+
+    /// Shut the ticket sales system down, failing if this takes longer than the
+    /// given deadline
+    pub async fn shutdown(self, deadline: Option<Duration>) -> Result<ShutdownReport> {
+        let shutdown = async move {
+            // Turn the requests still arriving away, so the workers finish
+            let balancer = self.balancer.clone();
+            task::spawn_blocking(move || balancer.begin_shutdown())
+                .await
+                .unwrap();
+            for handle in self.join_handles {
+                handle.await.unwrap()
+            }
+            task::spawn_blocking(move || Arc::into_inner(self.balancer).unwrap().shutdown_report())
+                .await
+                .unwrap()
+        };
+        match deadline {
+            Some(deadline) => {
+                tokio::time::timeout(deadline, shutdown)
+                    .await
+                    .map_err(|_| eyre!("Shutdown did not complete within {deadline:?}"))
+            }
+            None => Ok(shutdown.await),
+        }
+    }
+}
+
+impl MockRawRequest {
+    /// Send the response, unless the customer stopped waiting for it, e.g., due to
+    /// its timeout
+    fn send(self, response: Response) {
+        let _ = self.response_channel.send(response);
     }
 }
 
 impl RawRequest for MockRawRequest {
     fn url(&self) -> &str {
         use RequestKind::*;
+        if let Some(url) = &self.url {
+            return url;
+        }
         match self.kind {
             GetNumServers => "/api/admin/num_servers",
             SetNumServers => "/api/admin/num_servers",
             GetServers => "/api/admin/get_servers",
+            ActivateServer => "/api/admin/activate_server",
+            DrainServer => "/api/admin/drain",
+            Warmup => "/api/admin/warmup",
+            GetScalingState => "/api/admin/scaling_state",
+            GetServerLoad => "/api/admin/server_load",
+            ResetReservations => "/api/admin/reset_reservations",
+            GetTerminatingServers => "/api/admin/terminating",
             NumAvailableTickets => "/api/num_available_tickets",
+            LocalAvailableTickets => "/api/local_available_tickets",
+            GetTotalAvailable => "/api/total_available",
+            GetCapacity => "/api/capacity",
+            GetReservedCount => "/api/reserved_count",
+            Ping => "/api/ping",
             ReserveTicket => "/api/reserve_ticket",
+            ReserveMultiple => "/api/reserve_multiple",
             BuyTicket => "/api/buy_ticket",
+            BuyAll => "/api/buy_all",
+            AbortAll => "/api/abort_all",
+            TransferReservation => "/api/transfer",
+            GetMyReservations => "/api/my_reservations",
             AbortPurchase => "/api/abort_purchase",
-            Debug => unreachable!(),
+            GetTimeout => "/api/timeout",
+            GetSoldTickets => "/api/sold_tickets",
+            CanReserve => "/api/can_reserve",
+            GetVariant => "/api/debug/variant",
+            Debug => "/api/debug",
         }
     }
 
-    fn method(&self) -> ticket_sale_core::RequestMethod {
-        use ticket_sale_core::RequestMethod::*;
+    fn method(&self) -> RequestMethod {
         use RequestKind::*;
+        use RequestMethod::*;
+        if let Some(method) = self.method {
+            return method;
+        }
         match self.kind {
-            GetNumServers | GetServers | NumAvailableTickets => Get,
+            GetNumServers
+            | GetServers
+            | NumAvailableTickets
+            | LocalAvailableTickets
+            | GetTimeout
+            | GetSoldTickets
+            | CanReserve
+            | GetReservedCount
+            | Ping
+            | GetMyReservations
+            | GetScalingState
+            | GetServerLoad
+            | GetVariant
+            | GetTotalAvailable
+            | GetTerminatingServers
+            | GetCapacity => Get,
             _ => Post,
         }
     }
 
+    fn authorization(&self) -> Option<&str> {
+        self.authorization.as_deref()
+    }
+
+    fn headers(&self) -> Vec<(String, String)> {
+        self.headers.clone()
+    }
+
     fn read_bytes(&mut self) -> std::io::Result<Vec<u8>> {
+        if let Some(body) = self.body.take() {
+            return Ok(body);
+        }
         Ok(match self.payload.take() {
             None => Vec::new(),
             Some(i) => i.to_string().into_bytes(),
         })
     }
     fn read_string(&mut self) -> std::io::Result<String> {
+        if let Some(body) = self.body.take() {
+            return String::from_utf8(body)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err));
+        }
         Ok(match self.payload.take() {
             None => String::new(),
             Some(i) => i.to_string(),
@@ -108,13 +297,39 @@ impl RawRequest for MockRawRequest {
         self.payload.take()
     }
 
-    fn respond_with_err(self: Box<Self>, msg: String, customer_id: Uuid, server_id: Option<Uuid>) {
+    fn respond_with_err(
+        self: Box<Self>,
+        kind: RequestError,
+        msg: String,
+        customer_id: Uuid,
+        server_id: Option<Uuid>,
+    ) {
         let response = Response::Error {
+            kind,
+            msg,
+            server_id,
+            customer_id,
+        };
+        self.send(response)
+    }
+
+    fn respond_with_redirect(self: Box<Self>, msg: String, customer_id: Uuid, server_id: Uuid) {
+        let response = Response::Redirect {
             msg,
             server_id,
             customer_id,
         };
-        self.response_channel.send(response).unwrap()
+        self.send(response)
+    }
+
+    fn respond_with_unauthorized(self: Box<Self>, customer_id: Uuid, server_id: Option<Uuid>) {
+        let response = Response::Error {
+            kind: RequestError::Unauthorized,
+            msg: "Unauthorized".to_owned(),
+            server_id,
+            customer_id,
+        };
+        self.send(response)
     }
 
     fn respond_with_int(self: Box<Self>, i: u32, customer_id: Uuid, server_id: Option<Uuid>) {
@@ -123,14 +338,32 @@ impl RawRequest for MockRawRequest {
             server_id,
             customer_id,
         };
-        self.response_channel.send(response).unwrap()
+        self.send(response)
+    }
+
+    fn respond_with_reservation(
+        self: Box<Self>,
+        ticket: u32,
+        ttl_secs: u32,
+        customer_id: Uuid,
+        server_id: Option<Uuid>,
+    ) {
+        let response = Response::Reservation {
+            ticket,
+            ttl_secs,
+            server_id,
+            customer_id,
+        };
+        self.send(response)
     }
 
     fn respond_with_string(self: Box<Self>, s: String, customer_id: Uuid, server_id: Option<Uuid>) {
-        panic!(
-            "{:?} must not be answered with a string.\ncustomer: {customer_id:?}\nserver: {server_id:?}\nmessage: {s}",
-            self.kind,
-        )
+        let response = Response::String {
+            s,
+            server_id,
+            customer_id,
+        };
+        self.send(response)
     }
 
     fn respond_with_sold_out(self: Box<Self>, customer_id: Uuid, server_id: Option<Uuid>) {
@@ -138,11 +371,25 @@ impl RawRequest for MockRawRequest {
             server_id,
             customer_id,
         };
-        self.response_channel.send(response).unwrap()
+        self.send(response)
     }
 
     fn respond_with_server_list(self: Box<Self>, servers: &[Uuid]) {
         let response = Response::ServerList(servers.to_vec());
-        self.response_channel.send(response).unwrap()
+        self.send(response)
+    }
+
+    fn respond_with_int_list(
+        self: Box<Self>,
+        ints: &[u32],
+        customer_id: Uuid,
+        server_id: Option<Uuid>,
+    ) {
+        let response = Response::IntList {
+            ints: ints.to_vec(),
+            server_id,
+            customer_id,
+        };
+        self.send(response)
     }
 }
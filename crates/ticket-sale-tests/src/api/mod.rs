@@ -1,10 +1,14 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 use std::{str::FromStr, sync::Arc};
 
-use eyre::Result;
+use eyre::{eyre, Result};
 use flume::Sender;
-use nanorand::Rng;
+use nanorand::{Rng, WyRand};
+use parking_lot::Mutex;
 use thiserror::Error;
-use ticket_sale_core::RequestKind;
+use ticket_sale_core::{RequestError, RequestKind, RequestMethod};
 use tokio::sync::oneshot;
 use uuid::Uuid;
 
@@ -12,14 +16,40 @@ pub mod jni;
 pub mod mock;
 
 #[derive(Debug, Error)]
-#[error("Error 400: {0}")]
-pub struct ApiError(String);
+#[error("Error {}: {msg}", self.kind.status_code())]
+pub struct ApiError {
+    kind: RequestError,
+    msg: String,
+    /// Server to send the request again to, if the old one is gone
+    redirect: Option<Uuid>,
+}
+
+impl ApiError {
+    /// Get the kind of the error
+    ///
+    /// A redirect is of kind [`RequestError::ServerGone`].
+    pub fn kind(&self) -> RequestError {
+        self.kind
+    }
+
+    /// Get the server to send the request again to, if the response was a redirect
+    /// instead of a generic error
+    pub fn redirect(&self) -> Option<Uuid> {
+        self.redirect
+    }
+}
 
 pub type ApiResult<T> = std::result::Result<T, ApiError>;
 
+/// Error returned if a request exceeds the timeout given in its [`RequestOptions`]
+#[derive(Debug, Error)]
+#[error("Request timed out after {0:?}")]
+pub struct TimeoutError(pub Duration);
+
 #[derive(Debug)]
 enum Response {
     Error {
+        kind: RequestError,
         msg: String,
         server_id: Option<Uuid>,
         customer_id: Uuid,
@@ -29,34 +59,83 @@ enum Response {
         server_id: Option<Uuid>,
         customer_id: Uuid,
     },
+    Reservation {
+        ticket: u32,
+        ttl_secs: u32,
+        server_id: Option<Uuid>,
+        customer_id: Uuid,
+    },
     SoldOut {
         server_id: Option<Uuid>,
         customer_id: Uuid,
     },
     ServerList(Vec<Uuid>),
+    IntList {
+        ints: Vec<u32>,
+        server_id: Option<Uuid>,
+        customer_id: Uuid,
+    },
+    String {
+        s: String,
+        server_id: Option<Uuid>,
+        customer_id: Uuid,
+    },
+    /// The customer's server is gone, retry with `server_id`
+    Redirect {
+        msg: String,
+        server_id: Uuid,
+        customer_id: Uuid,
+    },
 }
 
 impl Response {
+    /// Turn a redirect to `server_id` into an [`ApiResponse`] with a failed result
+    fn redirect_response<T>(msg: String, server_id: Uuid, customer_id: Uuid) -> ApiResponse<T> {
+        ApiResponse {
+            server_id: Some(server_id),
+            customer_id: Some(customer_id),
+            result: Err(ApiError {
+                kind: RequestError::ServerGone,
+                msg,
+                redirect: Some(server_id),
+            }),
+        }
+    }
+
     fn into_api_response_usize(self, rq_kind: RequestKind) -> ApiResponse<usize> {
         match self {
             Response::Error {
+                kind,
                 msg,
                 server_id,
                 customer_id,
-            } => ApiResponse {
-                server_id,
-                customer_id: Some(customer_id),
-                result: Err(ApiError(msg)),
-            },
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Err(ApiError {
+                        kind,
+                        msg,
+                        redirect: None,
+                    }),
+                }
+            }
             Response::Int {
                 i,
                 server_id,
                 customer_id,
-            } => ApiResponse {
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Ok(i as usize),
+                }
+            }
+            Response::Redirect {
+                msg,
                 server_id,
-                customer_id: Some(customer_id),
-                result: Ok(i as usize),
-            },
+                customer_id,
+            } => Self::redirect_response(msg, server_id, customer_id),
             resp => panic!("{rq_kind:?} must not be answered by {resp:?}"),
         }
     }
@@ -64,23 +143,238 @@ impl Response {
     fn into_api_response_u64(self, rq_kind: RequestKind) -> ApiResponse<u64> {
         match self {
             Response::Error {
+                kind,
+                msg,
+                server_id,
+                customer_id,
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Err(ApiError {
+                        kind,
+                        msg,
+                        redirect: None,
+                    }),
+                }
+            }
+            Response::Int {
+                i,
+                server_id,
+                customer_id,
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Ok(i as u64),
+                }
+            }
+            Response::Redirect {
                 msg,
                 server_id,
                 customer_id,
-            } => ApiResponse {
+            } => Self::redirect_response(msg, server_id, customer_id),
+            resp => panic!("{rq_kind:?} must not be answered by {resp:?}"),
+        }
+    }
+
+    fn into_api_response_u64_list(self, rq_kind: RequestKind) -> ApiResponse<Vec<u64>> {
+        match self {
+            Response::Error {
+                kind,
+                msg,
                 server_id,
-                customer_id: Some(customer_id),
-                result: Err(ApiError(msg)),
-            },
+                customer_id,
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Err(ApiError {
+                        kind,
+                        msg,
+                        redirect: None,
+                    }),
+                }
+            }
+            Response::IntList {
+                ints,
+                server_id,
+                customer_id,
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Ok(ints.into_iter().map(u64::from).collect()),
+                }
+            }
+            Response::Redirect {
+                msg,
+                server_id,
+                customer_id,
+            } => Self::redirect_response(msg, server_id, customer_id),
+            resp => panic!("{rq_kind:?} must not be answered by {resp:?}"),
+        }
+    }
+
+    fn into_api_response_string(self, rq_kind: RequestKind) -> ApiResponse<String> {
+        match self {
+            Response::Error {
+                kind,
+                msg,
+                server_id,
+                customer_id,
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Err(ApiError {
+                        kind,
+                        msg,
+                        redirect: None,
+                    }),
+                }
+            }
+            Response::String {
+                s,
+                server_id,
+                customer_id,
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Ok(s),
+                }
+            }
+            Response::Redirect {
+                msg,
+                server_id,
+                customer_id,
+            } => Self::redirect_response(msg, server_id, customer_id),
+            resp => panic!("{rq_kind:?} must not be answered by {resp:?}"),
+        }
+    }
+
+    /// Render any response as text, one item per line for lists
+    fn into_api_response_text(self) -> ApiResponse<String> {
+        let join = |items: Vec<String>| items.join("\n");
+        let (result, server_id, customer_id) = match self {
+            Response::Error {
+                kind,
+                msg,
+                server_id,
+                customer_id,
+            } => {
+                (
+                    Err(ApiError {
+                        kind,
+                        msg,
+                        redirect: None,
+                    }),
+                    server_id,
+                    Some(customer_id),
+                )
+            }
             Response::Int {
                 i,
                 server_id,
                 customer_id,
-            } => ApiResponse {
+            } => (Ok(i.to_string()), server_id, Some(customer_id)),
+            Response::Reservation {
+                ticket,
+                server_id,
+                customer_id,
+                ..
+            } => (Ok(ticket.to_string()), server_id, Some(customer_id)),
+            Response::SoldOut {
+                server_id,
+                customer_id,
+            } => (Ok(String::from("SOLD OUT")), server_id, Some(customer_id)),
+            Response::ServerList(list) => {
+                let list = list.iter().map(Uuid::to_string).collect();
+                (Ok(join(list)), None, None)
+            }
+            Response::IntList {
+                ints,
+                server_id,
+                customer_id,
+            } => {
+                let ints = ints.iter().map(u32::to_string).collect();
+                (Ok(join(ints)), server_id, Some(customer_id))
+            }
+            Response::String {
+                s,
                 server_id,
-                customer_id: Some(customer_id),
-                result: Ok(i as u64),
-            },
+                customer_id,
+            } => (Ok(s), server_id, Some(customer_id)),
+            Response::Redirect {
+                msg,
+                server_id,
+                customer_id,
+            } => return Self::redirect_response(msg, server_id, customer_id),
+        };
+        ApiResponse {
+            server_id,
+            customer_id,
+            result,
+        }
+    }
+
+    fn into_api_response_reservation(self, rq_kind: RequestKind) -> ApiResponse<Reservation> {
+        match self {
+            Response::Error {
+                kind,
+                msg,
+                server_id,
+                customer_id,
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Err(ApiError {
+                        kind,
+                        msg,
+                        redirect: None,
+                    }),
+                }
+            }
+            Response::Int {
+                i,
+                server_id,
+                customer_id,
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Ok(Reservation::Reserved(i as u64, None)),
+                }
+            }
+            Response::Reservation {
+                ticket,
+                ttl_secs,
+                server_id,
+                customer_id,
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Ok(Reservation::Reserved(ticket as u64, Some(ttl_secs as u64))),
+                }
+            }
+            Response::SoldOut {
+                server_id,
+                customer_id,
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Ok(Reservation::SoldOut),
+                }
+            }
+            Response::Redirect {
+                msg,
+                server_id,
+                customer_id,
+            } => Self::redirect_response(msg, server_id, customer_id),
             resp => panic!("{rq_kind:?} must not be answered by {resp:?}"),
         }
     }
@@ -88,27 +382,122 @@ impl Response {
 
 struct RequestMsg {
     kind: RequestKind,
+    /// URL of the request, by default derived from the kind
+    url: Option<String>,
+    /// Method of a [`RequestKind::Debug`] request, by default derived from the kind
+    method: Option<RequestMethod>,
+    /// Body of a [`RequestKind::Debug`] request, instead of the payload
+    body: Option<Vec<u8>>,
     payload: Option<u32>,
     customer_id: Uuid,
     server_id: Option<Uuid>,
+    /// Value of the `Authorization` header
+    authorization: Option<String>,
+    /// Further headers of a [`RequestKind::Debug`] request
+    headers: Vec<(String, String)>,
     response_channel: oneshot::Sender<Response>,
 }
 
+/// Source of the customer ids for new user sessions
+#[derive(Clone, Debug, Default)]
+pub enum CustomerIdSource {
+    /// Fresh random ids
+    #[default]
+    Random,
+    /// The ids `1`, `2`, `3`, … (as 128 bit integers)
+    Sequential,
+    /// Reproducible pseudo-random ids generated from the given seed
+    Seeded(u64),
+    /// Cycle through the given ids, e.g., to deliberately create collisions
+    Fixed(Vec<Uuid>),
+}
+
+/// State of a [`CustomerIdSource`]
+enum CustomerIdGenerator {
+    Random,
+    Sequential(u128),
+    Seeded(WyRand),
+    Fixed { ids: Vec<Uuid>, next: usize },
+}
+
+impl CustomerIdGenerator {
+    fn new(source: CustomerIdSource) -> Self {
+        match source {
+            CustomerIdSource::Random => Self::Random,
+            CustomerIdSource::Sequential => Self::Sequential(0),
+            CustomerIdSource::Seeded(seed) => Self::Seeded(WyRand::new_seed(seed)),
+            CustomerIdSource::Fixed(ids) => {
+                assert!(
+                    !ids.is_empty(),
+                    "A fixed set of customer ids must not be empty"
+                );
+                Self::Fixed { ids, next: 0 }
+            }
+        }
+    }
+
+    fn next_id(&mut self) -> Uuid {
+        let mut bytes = [0u8; 16];
+        match self {
+            Self::Random => nanorand::tls_rng().fill(&mut bytes),
+            Self::Sequential(counter) => {
+                *counter += 1;
+                return Uuid::from_u128(*counter);
+            }
+            Self::Seeded(rng) => rng.fill(&mut bytes),
+            Self::Fixed { ids, next } => {
+                let id = ids[*next];
+                *next = (*next + 1) % ids.len();
+                return id;
+            }
+        }
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    }
+}
+
+/// How an [`Api`] picks the balancer channel of a request
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Dispatch {
+    /// Always the channel of this clone
+    #[default]
+    Pinned,
+    /// The channel with the fewest queued requests, taking turns among equally
+    /// full ones
+    LeastFull,
+}
+
 pub struct Api {
     /// One channel per balancer thread
     channels: Arc<Vec<Sender<RequestMsg>>>,
 
-    my_channel: Sender<RequestMsg>,
     my_index: usize,
+    dispatch: Dispatch,
+
+    /// Channel to start the search for the least full one from, shared among all
+    /// clones
+    next_index: Arc<AtomicUsize>,
+
+    /// Number of requests sent over each channel, shared among all clones
+    sent: Arc<Vec<AtomicU64>>,
+
+    /// Generator for customer ids, shared among all clones
+    customer_ids: Arc<Mutex<CustomerIdGenerator>>,
+
+    /// Token sent along with every request
+    admin_token: Option<String>,
 }
 
 impl Api {
     fn new(channels: Vec<Sender<RequestMsg>>) -> Self {
-        let my_channel = channels[0].clone();
+        let sent = channels.iter().map(|_| AtomicU64::new(0)).collect();
         Self {
             channels: Arc::new(channels),
-            my_channel,
             my_index: 0,
+            dispatch: Dispatch::Pinned,
+            next_index: Arc::new(AtomicUsize::new(0)),
+            sent: Arc::new(sent),
+            customer_ids: Arc::new(Mutex::new(CustomerIdGenerator::Random)),
+            admin_token: None,
         }
     }
 }
@@ -118,34 +507,164 @@ impl Clone for Api {
         let my_index = (self.my_index + 1) % self.channels.len();
         Self {
             channels: self.channels.clone(),
-            my_channel: self.channels[my_index].clone(),
             my_index,
+            dispatch: self.dispatch,
+            next_index: self.next_index.clone(),
+            sent: self.sent.clone(),
+            customer_ids: self.customer_ids.clone(),
+            admin_token: self.admin_token.clone(),
         }
     }
 }
 
+/// Time waited beyond the reservation timeout for a reservation to expire
+const EXPIRY_MARGIN: Duration = Duration::from_millis(100);
+
+/// Interval for polling the number of sold tickets
+const SOLD_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Time [`Api::scale`] waits at most for the number of servers to settle
+const SCALE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Interval for polling the number of servers
+const SCALE_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Time waited before the first retry of a request, doubled for every further one
+const RETRY_BACKOFF: Duration = Duration::from_millis(10);
+
+/// Number of redirects a [`UserSession`] follows per request
+const MAX_REDIRECTS: usize = 3;
+
 const NO_REQUEST_OPTIONS: RequestOptions = RequestOptions {
     server_id: None,
     customer_id: None,
+    timeout: None,
+    max_retries: 0,
 };
 
 impl Api {
+    /// Get a clone sending each request over the balancer channel with the fewest
+    /// queued requests
+    ///
+    /// Unlike the clones created by [`Clone::clone`], which each stick to one
+    /// channel, it spreads the requests of concurrent tasks sharing it over all
+    /// balancer threads.
+    pub fn any(&self) -> Api {
+        Api {
+            dispatch: Dispatch::LeastFull,
+            ..self.clone()
+        }
+    }
+
+    /// Get the number of requests sent over each balancer channel so far, by all
+    /// clones
+    ///
+    /// Each balancer thread serves one channel, so this is the number of requests
+    /// handed to each thread.
+    pub fn requests_per_channel(&self) -> Vec<u64> {
+        self.sent
+            .iter()
+            .map(|sent| sent.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    /// Get the index of the channel to send the next request over
+    fn pick_channel(&self) -> usize {
+        match self.dispatch {
+            Dispatch::Pinned => self.my_index,
+            Dispatch::LeastFull => {
+                let n = self.channels.len();
+                let start = self.next_index.fetch_add(1, Ordering::Relaxed);
+                (start..start + n)
+                    .map(|i| i % n)
+                    .min_by_key(|&i| self.channels[i].len())
+                    .unwrap()
+            }
+        }
+    }
+
     async fn make_request(
         &self,
         kind: RequestKind,
         payload: Option<u32>,
         options: &RequestOptions,
     ) -> Result<Response> {
-        let (sender, receiver) = oneshot::channel();
-        let msg = RequestMsg {
-            kind,
-            payload,
-            customer_id: options.customer_id.unwrap_or_default(),
-            server_id: options.server_id,
-            response_channel: sender,
-        };
-        self.my_channel.send_async(msg).await?;
-        Ok(receiver.await?)
+        self.make_request_with_url(kind, None, payload, options)
+            .await
+    }
+
+    async fn make_request_with_url(
+        &self,
+        kind: RequestKind,
+        url: Option<String>,
+        payload: Option<u32>,
+        options: &RequestOptions,
+    ) -> Result<Response> {
+        self.make_raw_request(kind, url, None, None, &[], payload, options)
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn make_raw_request(
+        &self,
+        kind: RequestKind,
+        url: Option<String>,
+        method: Option<RequestMethod>,
+        body: Option<Vec<u8>>,
+        headers: &[(String, String)],
+        payload: Option<u32>,
+        options: &RequestOptions,
+    ) -> Result<Response> {
+        let mut backoff = RETRY_BACKOFF;
+        let mut retries = 0;
+        loop {
+            let (sender, receiver) = oneshot::channel();
+            let msg = RequestMsg {
+                kind,
+                url: url.clone(),
+                method,
+                body: body.clone(),
+                payload,
+                customer_id: options.customer_id.unwrap_or_default(),
+                server_id: options.server_id,
+                authorization: self
+                    .admin_token
+                    .as_ref()
+                    .map(|token| format!("Bearer {token}")),
+                headers: headers.to_vec(),
+                response_channel: sender,
+            };
+
+            let index = self.pick_channel();
+            let channel = &self.channels[index];
+            let Some(timeout) = options.timeout else {
+                channel.send_async(msg).await?;
+                self.sent[index].fetch_add(1, Ordering::Relaxed);
+                return Ok(receiver.await?);
+            };
+
+            // A request that could not be sent in time is dropped from the channel,
+            // so sending it again does not duplicate it
+            match tokio::time::timeout(timeout, channel.send_async(msg)).await {
+                Ok(sent) => {
+                    sent?;
+                    self.sent[index].fetch_add(1, Ordering::Relaxed);
+                }
+                Err(_) if retries < options.max_retries => {
+                    retries += 1;
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    continue;
+                }
+                Err(_) => return Err(TimeoutError(timeout).into()),
+            }
+
+            // The request was sent, so only wait for its response
+            return match tokio::time::timeout(timeout, receiver).await {
+                Ok(response) => Ok(response?),
+                Err(_) => Err(TimeoutError(timeout).into()),
+            };
+        }
     }
 
     pub async fn get_num_servers(&self) -> Result<ApiResponse<usize>> {
@@ -160,28 +679,187 @@ impl Api {
         Ok(response.await?.into_api_response_usize(kind))
     }
 
+    /// Scale to `n` servers and wait until the number of servers settles
+    ///
+    /// Fails if the balancer rejects the request. Otherwise, polls the number of
+    /// servers until it equals the one the balancer scaled to (`n` clamped to the
+    /// configured bounds) or a timeout elapses, and returns the final number.
+    pub async fn scale(&self, n: usize) -> Result<usize> {
+        let target = self.post_num_servers(n).await?.result?;
+        let start = Instant::now();
+        loop {
+            let current = self.get_num_servers().await?.result?;
+            if current == target || start.elapsed() > SCALE_TIMEOUT {
+                return Ok(current);
+            }
+            tokio::time::sleep(SCALE_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Get the number of servers deactivated by a previous scale request that did not
+    /// terminate yet, `0` once scaling settled
+    pub async fn get_scaling_state(&self) -> Result<ApiResponse<usize>> {
+        let kind = RequestKind::GetScalingState;
+        let response = self.make_request(kind, None, &NO_REQUEST_OPTIONS);
+        Ok(response.await?.into_api_response_usize(kind))
+    }
+
+    /// Get the number of reserved tickets of each non-terminating server
+    pub async fn get_server_load(&self) -> Result<ApiResponse<Vec<(Uuid, u64)>>> {
+        let kind = RequestKind::GetServerLoad;
+        let response = self.make_request(kind, None, &NO_REQUEST_OPTIONS);
+        response
+            .await?
+            .into_api_response_string(kind)
+            .map_response(|load| {
+                // One `<server id> <reserved tickets>` line per server
+                load.lines()
+                    .map(|line| {
+                        let (server, reserved) = line
+                            .split_once(' ')
+                            .ok_or_else(|| eyre!("Malformed server load: {line}"))?;
+                        Ok((server.parse()?, reserved.parse()?))
+                    })
+                    .collect()
+            })
+    }
+
+    /// Activate the terminating server with the given id again
+    pub async fn activate_server(&self, server_id: Uuid) -> Result<ApiResponse<usize>> {
+        let kind = RequestKind::ActivateServer;
+        let options = RequestOptions {
+            server_id: Some(server_id),
+            customer_id: None,
+            ..Default::default()
+        };
+        let response = self.make_request(kind, None, &options);
+        Ok(response.await?.into_api_response_usize(kind))
+    }
+
+    /// Drain the non-terminating server with the given id
+    pub async fn drain_server(&self, server_id: Uuid) -> Result<ApiResponse<usize>> {
+        let kind = RequestKind::DrainServer;
+        let options = RequestOptions {
+            server_id: Some(server_id),
+            customer_id: None,
+            ..Default::default()
+        };
+        let response = self.make_request(kind, None, &options);
+        Ok(response.await?.into_api_response_usize(kind))
+    }
+
+    /// Distribute the tickets evenly to the non-terminating servers, returning the
+    /// number of tickets handed out
+    pub async fn warmup(&self) -> Result<ApiResponse<u64>> {
+        let kind = RequestKind::Warmup;
+        let response = self.make_request(kind, None, &NO_REQUEST_OPTIONS);
+        Ok(response.await?.into_api_response_u64(kind))
+    }
+
     pub async fn get_servers(&self) -> Result<ApiResponse<Vec<Uuid>>> {
-        let kind = RequestKind::GetServers;
+        self.get_server_list(RequestKind::GetServers).await
+    }
+
+    /// Get the servers that are terminating but did not terminate yet
+    pub async fn get_terminating_servers(&self) -> Result<ApiResponse<Vec<Uuid>>> {
+        self.get_server_list(RequestKind::GetTerminatingServers)
+            .await
+    }
+
+    async fn get_server_list(&self, kind: RequestKind) -> Result<ApiResponse<Vec<Uuid>>> {
         let response = self.make_request(kind, None, &NO_REQUEST_OPTIONS);
         Ok(match response.await? {
             Response::Error {
+                kind,
                 msg,
                 server_id,
                 customer_id,
-            } => ApiResponse {
-                server_id,
-                customer_id: Some(customer_id),
-                result: Err(ApiError(msg)),
-            },
-            Response::ServerList(list) => ApiResponse {
-                server_id: None,
-                customer_id: None,
-                result: Ok(list),
-            },
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Err(ApiError {
+                        kind,
+                        msg,
+                        redirect: None,
+                    }),
+                }
+            }
+            Response::ServerList(list) => {
+                ApiResponse {
+                    server_id: None,
+                    customer_id: None,
+                    result: Ok(list),
+                }
+            }
             resp => panic!("{kind:?} must not be answered by {resp:?}"),
         })
     }
 
+    pub async fn get_timeout(&self) -> Result<ApiResponse<u64>> {
+        let kind = RequestKind::GetTimeout;
+        let response = self.make_request(kind, None, &NO_REQUEST_OPTIONS);
+        Ok(response.await?.into_api_response_u64(kind))
+    }
+
+    /// Get the number of initially available tickets
+    pub async fn get_capacity(&self) -> Result<ApiResponse<u64>> {
+        let kind = RequestKind::GetCapacity;
+        let response = self.make_request(kind, None, &NO_REQUEST_OPTIONS);
+        Ok(response.await?.into_api_response_u64(kind))
+    }
+
+    /// Get the number of sold tickets
+    pub async fn get_sold_tickets(&self) -> Result<ApiResponse<u64>> {
+        let kind = RequestKind::GetSoldTickets;
+        let response = self.make_request(kind, None, &NO_REQUEST_OPTIONS);
+        Ok(response.await?.into_api_response_u64(kind))
+    }
+
+    /// Get the number of tickets currently reserved across all servers
+    pub async fn get_reserved_count(&self) -> Result<ApiResponse<u64>> {
+        let kind = RequestKind::GetReservedCount;
+        let response = self.make_request(kind, None, &NO_REQUEST_OPTIONS);
+        Ok(response.await?.into_api_response_u64(kind))
+    }
+
+    /// Get the exact number of non-reserved tickets in the database and all servers
+    pub async fn get_total_available(&self) -> Result<ApiResponse<u64>> {
+        let kind = RequestKind::GetTotalAvailable;
+        let response = self.make_request(kind, None, &NO_REQUEST_OPTIONS);
+        Ok(response.await?.into_api_response_u64(kind))
+    }
+
+    /// Ping the load balancer, returning the round-trip latency
+    ///
+    /// The balancer answers without involving any server, so this measures the
+    /// overhead of the balancer and the API alone.
+    pub async fn ping(&self) -> Result<Duration> {
+        let kind = RequestKind::Ping;
+        let start = Instant::now();
+        let response = self.make_request(kind, None, &NO_REQUEST_OPTIONS).await?;
+        let latency = start.elapsed();
+        response.into_api_response_u64(kind).result?;
+        Ok(latency)
+    }
+
+    /// Wait until at least `count` tickets are sold
+    ///
+    /// Fails if this takes longer than `timeout`.
+    pub async fn wait_for_sold(&self, count: u64, timeout: Duration) -> Result<()> {
+        let start = Instant::now();
+        loop {
+            let sold = self.get_sold_tickets().await?.result?;
+            if sold >= count {
+                return Ok(());
+            }
+            if start.elapsed() > timeout {
+                eyre::bail!("Only {sold} of {count} tickets were sold within {timeout:?}.");
+            }
+            tokio::time::sleep(SOLD_POLL_INTERVAL).await;
+        }
+    }
+
     pub async fn get_available_tickets(
         &self,
         options: &RequestOptions,
@@ -191,43 +869,267 @@ impl Api {
         Ok(response.await?.into_api_response_u64(kind))
     }
 
+    /// Abort all reservations on all servers, returning the number of released
+    /// tickets
+    pub async fn reset_reservations(&self) -> Result<ApiResponse<u64>> {
+        let kind = RequestKind::ResetReservations;
+        let response = self.make_request(kind, None, &NO_REQUEST_OPTIONS);
+        Ok(response.await?.into_api_response_u64(kind))
+    }
+
+    /// Get the number of available tickets as reported by the given server
+    pub async fn get_available_tickets_for(&self, server: Uuid) -> Result<ApiResponse<u64>> {
+        let options = RequestOptions {
+            server_id: Some(server),
+            ..NO_REQUEST_OPTIONS
+        };
+        self.get_available_tickets(&options).await
+    }
+
+    /// Get the exact number of non-reserved tickets held by the server given in
+    /// `options`
+    pub async fn get_local_available_tickets(
+        &self,
+        options: &RequestOptions,
+    ) -> Result<ApiResponse<u64>> {
+        let kind = RequestKind::LocalAvailableTickets;
+        let response = self.make_request(kind, None, options);
+        Ok(response.await?.into_api_response_u64(kind))
+    }
+
     pub async fn reserve_ticket(
         &self,
         options: &RequestOptions,
     ) -> Result<ApiResponse<Reservation>> {
         let kind = RequestKind::ReserveTicket;
         let response = self.make_request(kind, None, options);
+        Ok(response.await?.into_api_response_reservation(kind))
+    }
+
+    /// Check whether the customer could currently reserve a ticket, without
+    /// reserving one
+    pub async fn can_reserve(&self, options: &RequestOptions) -> Result<ApiResponse<bool>> {
+        let kind = RequestKind::CanReserve;
+        let response = self.make_request(kind, None, options);
+        response
+            .await?
+            .into_api_response_u64(kind)
+            .map_response(|i| Ok(i != 0))
+    }
+
+    /// Reserve up to `count` tickets at once
+    ///
+    /// If no tickets are left, the result is an empty list.
+    pub async fn reserve_multiple(
+        &self,
+        count: u32,
+        options: &RequestOptions,
+    ) -> Result<ApiResponse<Vec<u64>>> {
+        let kind = RequestKind::ReserveMultiple;
+        let response = self.make_request(kind, Some(count), options);
         Ok(match response.await? {
             Response::Error {
+                kind,
                 msg,
                 server_id,
                 customer_id,
-            } => ApiResponse {
-                server_id,
-                customer_id: Some(customer_id),
-                result: Err(ApiError(msg)),
-            },
-            Response::Int {
-                i,
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Err(ApiError {
+                        kind,
+                        msg,
+                        redirect: None,
+                    }),
+                }
+            }
+            Response::IntList {
+                ints,
                 server_id,
                 customer_id,
-            } => ApiResponse {
-                server_id,
-                customer_id: Some(customer_id),
-                result: Ok(Reservation::Reserved(i as u64)),
-            },
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Ok(ints.into_iter().map(u64::from).collect()),
+                }
+            }
             Response::SoldOut {
                 server_id,
                 customer_id,
-            } => ApiResponse {
+            } => {
+                ApiResponse {
+                    server_id,
+                    customer_id: Some(customer_id),
+                    result: Ok(Vec::new()),
+                }
+            }
+            Response::Redirect {
+                msg,
                 server_id,
-                customer_id: Some(customer_id),
-                result: Ok(Reservation::SoldOut),
-            },
+                customer_id,
+            } => Response::redirect_response(msg, server_id, customer_id),
             resp => panic!("{kind:?} must not be answered by {resp:?}"),
         })
     }
 
+    /// Reserve the ticket with the given id
+    ///
+    /// Fails if the ticket is reserved, sold, or held by another server.
+    pub async fn reserve_specific_ticket(
+        &self,
+        ticket_id: u64,
+        options: &RequestOptions,
+    ) -> Result<ApiResponse<Reservation>> {
+        let kind = RequestKind::ReserveTicket;
+        let response = self.make_request(kind, Some(ticket_id as u32), options);
+        Ok(response.await?.into_api_response_reservation(kind))
+    }
+
+    /// Reserve the ticket at `position` (counted from the front) of the server's
+    /// non-reserved tickets
+    ///
+    /// This is a debugging command allowing tests to control which ticket ids are
+    /// handed out.
+    pub async fn debug_reserve_position(
+        &self,
+        position: u64,
+        options: &RequestOptions,
+    ) -> Result<ApiResponse<Reservation>> {
+        let kind = RequestKind::Debug;
+        let url = String::from("/api/debug/reserve_position");
+        let response = self.make_request_with_url(kind, Some(url), Some(position as u32), options);
+        Ok(response.await?.into_api_response_reservation(kind))
+    }
+
+    /// Make a server deliberately lose one of its tickets
+    ///
+    /// This is a debugging command simulating a bug, e.g., to test the reconciliation
+    /// on shutdown.
+    pub async fn debug_orphan_ticket(
+        &self,
+        options: &RequestOptions,
+    ) -> Result<ApiResponse<Reservation>> {
+        let kind = RequestKind::Debug;
+        let url = String::from("/api/debug/orphan_ticket");
+        let response = self.make_request_with_url(kind, Some(url), None, options);
+        Ok(response.await?.into_api_response_reservation(kind))
+    }
+
+    /// Make a server sleep for `ms` milliseconds, blocking it
+    ///
+    /// This is a debugging command for simulating a slow server, it needs to be allowed
+    /// in the configuration.
+    pub async fn debug_sleep(&self, ms: u32, options: &RequestOptions) -> Result<ApiResponse<u64>> {
+        let kind = RequestKind::Debug;
+        let url = format!("/api/debug/sleep?ms={ms}");
+        let response = self.make_request_with_url(kind, Some(url), None, options);
+        Ok(response.await?.into_api_response_u64(kind))
+    }
+
+    /// Get the number of passes the estimator completed over all servers
+    pub async fn debug_estimator_passes(&self) -> Result<ApiResponse<u64>> {
+        let kind = RequestKind::Debug;
+        let url = String::from("/api/debug/estimator_passes");
+        let response = self.make_request_with_url(kind, Some(url), None, &NO_REQUEST_OPTIONS);
+        Ok(response.await?.into_api_response_u64(kind))
+    }
+
+    /// Get the implementation that is running, `standard` or `bonus` (`slug` for the
+    /// sequential reference implementation)
+    pub async fn variant(&self) -> Result<ApiResponse<String>> {
+        let kind = RequestKind::GetVariant;
+        let response = self.make_request(kind, None, &NO_REQUEST_OPTIONS);
+        Ok(response.await?.into_api_response_string(kind))
+    }
+
+    /// Send a debugging command with the given URL, method, and body
+    ///
+    /// Any response is rendered as text, one item per line for lists.
+    pub async fn debug(
+        &self,
+        url: &str,
+        method: RequestMethod,
+        body: Option<Vec<u8>>,
+    ) -> Result<ApiResponse<String>> {
+        let kind = RequestKind::Debug;
+        let url = Some(url.to_owned());
+        let response = self.make_raw_request(
+            kind,
+            url,
+            Some(method),
+            body,
+            &[],
+            None,
+            &NO_REQUEST_OPTIONS,
+        );
+        Ok(response.await?.into_api_response_text())
+    }
+
+    /// Send the given headers to the debugging command reflecting them and get the
+    /// headers it received as (name, value) pairs
+    pub async fn debug_echo(
+        &self,
+        headers: &[(String, String)],
+    ) -> Result<ApiResponse<Vec<(String, String)>>> {
+        let kind = RequestKind::Debug;
+        let url = Some(String::from("/api/debug/echo"));
+        let response =
+            self.make_raw_request(kind, url, None, None, headers, None, &NO_REQUEST_OPTIONS);
+        response
+            .await?
+            .into_api_response_string(kind)
+            .map_response(|echo| {
+                // One `<name>: <value>` line per header
+                echo.lines()
+                    .map(|line| {
+                        let (name, value) = line
+                            .split_once(": ")
+                            .ok_or_else(|| eyre!("Malformed header: {line}"))?;
+                        Ok((name.to_owned(), value.to_owned()))
+                    })
+                    .collect()
+            })
+    }
+
+    /// Get the ids of the tickets in the database, i.e., not allocated by any server
+    pub async fn debug_db_ids(&self) -> Result<ApiResponse<Vec<u64>>> {
+        let kind = RequestKind::Debug;
+        let url = String::from("/api/debug/db_ids");
+        let response = self.make_request_with_url(kind, Some(url), None, &NO_REQUEST_OPTIONS);
+        Ok(response.await?.into_api_response_u64_list(kind))
+    }
+
+    /// Get the latency histograms of the requests handled so far as text, one request
+    /// kind per line, clearing them afterwards if `reset`
+    pub async fn debug_metrics(&self, reset: bool) -> Result<ApiResponse<String>> {
+        let kind = RequestKind::Debug;
+        let url = match reset {
+            true => String::from("/api/debug/metrics?reset"),
+            false => String::from("/api/debug/metrics"),
+        };
+        let response = self.make_request_with_url(kind, Some(url), None, &NO_REQUEST_OPTIONS);
+        Ok(response.await?.into_api_response_string(kind))
+    }
+
+    /// Get the ticket counts of each non-terminating server
+    pub async fn debug_server_stats(&self) -> Result<ApiResponse<Vec<ServerStats>>> {
+        let kind = RequestKind::Debug;
+        let url = String::from("/api/debug/server_stats");
+        let response = self.make_request_with_url(kind, Some(url), None, &NO_REQUEST_OPTIONS);
+        response
+            .await?
+            .into_api_response_string(kind)
+            .map_response(|json| {
+                // The array contains one server per line
+                json.lines()
+                    .filter(|line| line.starts_with('{'))
+                    .map(str::parse)
+                    .collect()
+            })
+    }
+
     pub async fn abort_purchase(
         &self,
         ticket_id: u64,
@@ -248,12 +1150,58 @@ impl Api {
         Ok(response.await?.into_api_response_u64(kind))
     }
 
+    /// Buy all tickets reserved by the customer at once
+    pub async fn buy_all(&self, options: &RequestOptions) -> Result<ApiResponse<Vec<u64>>> {
+        let kind = RequestKind::BuyAll;
+        let response = self.make_request(kind, None, options);
+        Ok(response.await?.into_api_response_u64_list(kind))
+    }
+
+    /// Abort all reservations of the customer at once
+    pub async fn abort_all(&self, options: &RequestOptions) -> Result<ApiResponse<Vec<u64>>> {
+        let kind = RequestKind::AbortAll;
+        let response = self.make_request(kind, None, options);
+        Ok(response.await?.into_api_response_u64_list(kind))
+    }
+
+    /// Transfer the customer's reservation of the given ticket to the target customer
+    pub async fn transfer_reservation(
+        &self,
+        ticket_id: u64,
+        target: Uuid,
+        options: &RequestOptions,
+    ) -> Result<ApiResponse<u64>> {
+        let kind = RequestKind::TransferReservation;
+        let body = format!("{ticket_id} {}", target.hyphenated()).into_bytes();
+        let response = self.make_raw_request(kind, None, None, Some(body), &[], None, options);
+        Ok(response.await?.into_api_response_u64(kind))
+    }
+
+    /// Get the ids of the tickets currently reserved for the customer
+    pub async fn get_my_reservations(
+        &self,
+        options: &RequestOptions,
+    ) -> Result<ApiResponse<Vec<u64>>> {
+        let kind = RequestKind::GetMyReservations;
+        let response = self.make_request(kind, None, options);
+        Ok(response.await?.into_api_response_u64_list(kind))
+    }
+
+    /// Set the source of customer ids for new user sessions (shared by all clones)
+    pub fn set_customer_id_source(&self, source: CustomerIdSource) {
+        *self.customer_ids.lock() = CustomerIdGenerator::new(source);
+    }
+
+    /// Set the admin token sent along with the requests of this API (only
+    /// affects clones created afterwards)
+    pub fn set_admin_token(&mut self, token: Option<String>) {
+        self.admin_token = token;
+    }
+
     pub fn create_user_session(&self, server_id: Option<Uuid>) -> UserSession {
-        let mut bytes = [0u8; 16];
-        nanorand::tls_rng().fill(&mut bytes);
         UserSession {
             api: self,
-            customer_id: uuid::Builder::from_random_bytes(bytes).into_uuid(),
+            customer_id: self.customer_ids.lock().next_id(),
             server_id,
             state: SessionState::None,
         }
@@ -282,27 +1230,73 @@ impl<T> ApiResponse<T> {
 
 pub enum Reservation {
     SoldOut,
-    Reserved(u64),
+    /// A reserved ticket and, if reported, the seconds until the reservation expires
+    Reserved(u64, Option<u64>),
 }
 
 impl Reservation {
     pub fn reserved(&self) -> Result<u64> {
         match self {
-            Reservation::SoldOut => Err(eyre::eyre!(
-                "Reservation failed when it shall have succeeded."
-            )),
-            Reservation::Reserved(ticket_id) => Ok(*ticket_id),
+            Reservation::SoldOut => {
+                Err(eyre::eyre!(
+                    "Reservation failed when it shall have succeeded."
+                ))
+            }
+            Reservation::Reserved(ticket_id, _) => Ok(*ticket_id),
         }
     }
 }
 
+/// Ticket counts of a server, as reported by [`Api::debug_server_stats`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ServerStats {
+    pub server_id: Uuid,
+    /// Number of non-reserved tickets
+    pub available: u64,
+    /// Number of reserved tickets
+    pub reserved: u64,
+    /// Estimate of the tickets in the other servers
+    pub estimate: u64,
+    /// Number of customers whose recent requests the server counts
+    pub customers: u64,
+}
+
+impl FromStr for ServerStats {
+    type Err = eyre::Error;
+
+    /// Parse a JSON object like
+    /// `{"server_id":"…","available":1,"reserved":2,"estimate":3,"customers":4}`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let object = s.trim().trim_end_matches(',');
+        let fields = object
+            .strip_prefix('{')
+            .and_then(|object| object.strip_suffix('}'))
+            .ok_or_else(|| eyre!("Malformed server stats: {s}"))?;
+        let field = |name: &str| {
+            fields
+                .split(',')
+                .filter_map(|field| field.split_once(':'))
+                .find_map(|(key, value)| (key.trim_matches('"') == name).then_some(value))
+                .map(|value| value.trim_matches('"'))
+                .ok_or_else(|| eyre!("Server stats without {name}: {s}"))
+        };
+        Ok(Self {
+            server_id: field("server_id")?.parse()?,
+            available: field("available")?.parse()?,
+            reserved: field("reserved")?.parse()?,
+            estimate: field("estimate")?.parse()?,
+            customers: field("customers")?.parse()?,
+        })
+    }
+}
+
 impl FromStr for Reservation {
     type Err = eyre::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.trim() {
             "SOLD OUT" => Ok(Self::SoldOut),
-            s => Ok(Self::Reserved(s.parse()?)),
+            s => Ok(Self::Reserved(s.parse()?, None)),
         }
     }
 }
@@ -324,52 +1318,141 @@ impl<'a> UserSession<'a> {
         RequestOptions {
             server_id: self.server_id,
             customer_id: Some(self.customer_id),
+            ..Default::default()
         }
     }
 
+    /// Get the server the session is bound to, i.e., the one holding the
+    /// reservation after a successful reservation
+    pub fn current_server(&self) -> Option<Uuid> {
+        self.server_id
+    }
+
     fn process_response<T>(&mut self, response: ApiResponse<T>) -> ApiResponse<T> {
         self.server_id = response.server_id;
         response
     }
 
+    /// Whether the response redirects the session to a server other than its
+    /// current one, switching to it if so
+    fn redirected<T>(&mut self, response: &ApiResponse<T>) -> bool {
+        match response.result.as_ref().err().and_then(ApiError::redirect) {
+            Some(server) if Some(server) != self.server_id => {
+                self.server_id = Some(server);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Send a request, sending it again to the new server whenever the response
+    /// is a redirect, up to [`MAX_REDIRECTS`] times
+    ///
+    /// Returns the last response, which is still a redirect if the limit was hit.
+    async fn follow_redirects<T, F, Fut>(&mut self, mut request: F) -> Result<ApiResponse<T>>
+    where
+        F: FnMut(RequestOptions) -> Fut,
+        Fut: Future<Output = Result<ApiResponse<T>>>,
+    {
+        let mut response = request(self.request_options()).await?;
+        for _ in 0..MAX_REDIRECTS {
+            if !self.redirected(&response) {
+                break;
+            }
+            response = request(self.request_options()).await?;
+        }
+        Ok(response)
+    }
+
     pub async fn get_available_tickets(&mut self) -> Result<ApiResponse<u64>> {
-        Ok(self.process_response(
-            self.api
-                .get_available_tickets(&self.request_options())
-                .await?,
-        ))
+        let api = self.api;
+        let response = self
+            .follow_redirects(|options| async move { api.get_available_tickets(&options).await })
+            .await?;
+        Ok(self.process_response(response))
     }
 
     pub async fn reserve_ticket(&mut self) -> Result<ApiResponse<Reservation>> {
-        let response: ApiResponse<Reservation> =
-            self.process_response(self.api.reserve_ticket(&self.request_options()).await?);
+        let api = self.api;
+        let response = self
+            .follow_redirects(|options| async move { api.reserve_ticket(&options).await })
+            .await?;
+        Ok(self.process_reservation(response))
+    }
+
+    /// Reserve the ticket with the given id
+    pub async fn reserve_specific_ticket(
+        &mut self,
+        ticket_id: u64,
+    ) -> Result<ApiResponse<Reservation>> {
+        let api = self.api;
+        let response = self
+            .follow_redirects(|options| {
+                async move { api.reserve_specific_ticket(ticket_id, &options).await }
+            })
+            .await?;
+        Ok(self.process_reservation(response))
+    }
+
+    /// Update the session's state according to the response to a reservation
+    fn process_reservation(
+        &mut self,
+        response: ApiResponse<Reservation>,
+    ) -> ApiResponse<Reservation> {
+        let response = self.process_response(response);
         if let Ok(reservation) = &response.result {
             match reservation {
                 Reservation::SoldOut => {
                     self.state = SessionState::None;
                 }
-                Reservation::Reserved(ticket_id) => {
+                Reservation::Reserved(ticket_id, _) => {
                     self.state = SessionState::Reserved(*ticket_id);
                 }
             }
         }
-        Ok(response)
+        response
     }
 
     pub async fn abort_purchase(&mut self, ticket_id: u64) -> Result<ApiResponse<u64>> {
-        Ok(self.process_response(
-            self.api
-                .abort_purchase(ticket_id, &self.request_options())
-                .await?,
-        ))
+        let api = self.api;
+        let response = self
+            .follow_redirects(
+                |options| async move { api.abort_purchase(ticket_id, &options).await },
+            )
+            .await?;
+        Ok(self.process_response(response))
     }
 
     pub async fn buy_ticket(&mut self, ticket_id: u64) -> Result<ApiResponse<u64>> {
-        Ok(self.process_response(
-            self.api
-                .buy_ticket(ticket_id, &self.request_options())
-                .await?,
-        ))
+        let api = self.api;
+        let response = self
+            .follow_redirects(|options| async move { api.buy_ticket(ticket_id, &options).await })
+            .await?;
+        Ok(self.process_response(response))
+    }
+
+    /// Reserve a ticket and let the reservation expire
+    ///
+    /// Waits in real time until the reservation timeout has passed and makes the
+    /// server notice the expiry with another request. Returns the id of the ticket,
+    /// which is available again afterwards.
+    pub async fn reserve_then_expire(&mut self) -> Result<u64> {
+        let Reservation::Reserved(ticket_id, _) = self.reserve_ticket().await?.result? else {
+            return Err(eyre!("No ticket left to reserve and let expire"));
+        };
+
+        // Reservations expire once more than the timeout (in whole seconds) passed
+        let timeout = self.api.get_timeout().await?.result?;
+        tokio::time::sleep(Duration::from_secs(timeout + 1) + EXPIRY_MARGIN).await;
+
+        // The server removes expired reservations when processing the next request
+        self.get_available_tickets().await?.result?;
+        self.state = SessionState::None;
+        assert!(
+            self.buy_ticket(ticket_id).await?.result.is_err(),
+            "Buying a ticket after its reservation expired must fail."
+        );
+        Ok(ticket_id)
     }
 }
 
@@ -377,4 +1460,10 @@ impl<'a> UserSession<'a> {
 pub struct RequestOptions {
     pub server_id: Option<Uuid>,
     pub customer_id: Option<Uuid>,
+    /// Time to wait for sending the request and for its response, waiting
+    /// indefinitely if `None`
+    pub timeout: Option<Duration>,
+    /// How often to retry sending the request if the balancer's channel stays full
+    /// for longer than `timeout`
+    pub max_retries: u8,
 }
@@ -0,0 +1,183 @@
+//! Core correctness checks, runnable against any implementation
+//!
+//! Every check builds its own test context from the given builder, so the same
+//! checks run against the Rust and the Java implementation depending on the
+//! builder's [`RunCfg`].
+//!
+//! The Java implementation only understands the basic request kinds, so the
+//! checks count sold tickets on the client side instead of asking the system, and
+//! options only the Rust implementation supports are set for it alone.
+
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+use eyre::{ensure, eyre, Result};
+
+use crate::{Reservation, RunCfg, TestCtxBuilder};
+
+/// Run all checks
+pub async fn run_all(builder: &TestCtxBuilder) -> Result<()> {
+    conservation(builder.clone()).await?;
+    no_double_sell(builder.clone()).await?;
+    sold_out(builder.clone()).await?;
+    scaling_survival(builder.clone()).await?;
+    Ok(())
+}
+
+/// Whether the checks run against the Rust implementation
+fn is_rust(builder: &TestCtxBuilder) -> bool {
+    matches!(builder.run_cfg, RunCfg::RustNative)
+}
+
+/// Bought, aborted, and reserved tickets are neither lost nor created
+pub async fn conservation(builder: TestCtxBuilder) -> Result<()> {
+    let tickets = 100;
+    let ctx = builder
+        .with_tickets(tickets)
+        .with_reservation_timeout(30)
+        .build()
+        .await?;
+    // A single server gets all tickets, so it only reports them sold out once
+    // every ticket is sold or reserved
+    ctx.api.post_num_servers(1).await?.result?;
+
+    // Buy some tickets, abort some and leave some reserved
+    let mut bought = 0;
+    let mut reserved = Vec::new();
+    for i in 0..30 {
+        let mut session = ctx.api.create_user_session(None);
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        match i % 3 {
+            0 => {
+                session.buy_ticket(ticket_id).await?.result?;
+                bought += 1;
+            }
+            1 => {
+                session.abort_purchase(ticket_id).await?.result?;
+            }
+            _ => reserved.push((session, ticket_id)),
+        }
+    }
+
+    // Buy the remaining tickets, including the aborted ones
+    let mut session = ctx.api.create_user_session(None);
+    while let Reservation::Reserved(ticket_id, _) = session.reserve_ticket().await?.result? {
+        session.buy_ticket(ticket_id).await?.result?;
+        bought += 1;
+    }
+    let expected = tickets - reserved.len() as u64;
+    ensure!(
+        bought == expected,
+        "All {expected} tickets that are not reserved must be sold, got {bought}."
+    );
+
+    // The reserved tickets can still be bought
+    for (mut session, ticket_id) in reserved {
+        session.buy_ticket(ticket_id).await?.result?;
+    }
+
+    // For the Rust implementation, this also reconciles the tickets
+    ctx.finish().await;
+    Ok(())
+}
+
+/// Concurrent customers never buy the same ticket
+pub async fn no_double_sell(builder: TestCtxBuilder) -> Result<()> {
+    let tickets = 200;
+    let ctx = builder.with_tickets(tickets).build().await?;
+
+    let sold = Arc::new(Mutex::new(HashSet::new()));
+    let tasks: Vec<_> = (0..8)
+        .map(|_| {
+            let api = ctx.api.clone();
+            let sold = sold.clone();
+            tokio::spawn(async move {
+                let mut session = api.create_user_session(None);
+                for _ in 0..tickets {
+                    let ticket_id = match session.reserve_ticket().await?.result? {
+                        Reservation::Reserved(ticket_id, _) => ticket_id,
+                        Reservation::SoldOut => break,
+                    };
+                    session.buy_ticket(ticket_id).await?.result?;
+                    if !sold.lock().unwrap().insert(ticket_id) {
+                        return Err(eyre!("Ticket {ticket_id} was sold twice."));
+                    }
+                }
+                Ok(())
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await??;
+    }
+
+    let bought = sold.lock().unwrap().len() as u64;
+    ensure!(bought <= tickets, "More tickets were sold than available.");
+
+    ctx.finish().await;
+    Ok(())
+}
+
+/// A single server sells exactly the available tickets and then reports that they
+/// are sold out
+pub async fn sold_out(builder: TestCtxBuilder) -> Result<()> {
+    let tickets = 50;
+    let builder = builder.with_tickets(tickets);
+    // Only the Rust implementation supports server bounds, which keep it from
+    // scaling up again
+    let builder = if is_rust(&builder) {
+        builder.with_server_bounds(1, 1)
+    } else {
+        builder
+    };
+    let ctx = builder.build().await?;
+    ctx.api.post_num_servers(1).await?.result?;
+
+    let mut session = ctx.api.create_user_session(None);
+    for _ in 0..tickets {
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        session.buy_ticket(ticket_id).await?.result?;
+    }
+    ensure!(
+        matches!(
+            session.reserve_ticket().await?.result?,
+            Reservation::SoldOut
+        ),
+        "After buying all tickets, reserving must report that they are sold out."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
+
+/// Reservations on servers that get deactivated can still be bought
+pub async fn scaling_survival(builder: TestCtxBuilder) -> Result<()> {
+    let ctx = builder
+        .with_tickets(1_000)
+        .with_reservation_timeout(30)
+        .build()
+        .await?;
+
+    ctx.api.post_num_servers(4).await?.result?;
+    let servers = ctx.api.get_servers().await?.result?;
+    ensure!(servers.len() == 4, "Scaling up to 4 servers must succeed.");
+
+    // Reserve a ticket on every server
+    let mut reservations = Vec::new();
+    for server in servers {
+        let mut session = ctx.api.create_user_session(Some(server));
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        reservations.push((session, ticket_id));
+    }
+
+    ctx.api.post_num_servers(1).await?.result?;
+    for (mut session, ticket_id) in reservations {
+        ensure!(
+            session.buy_ticket(ticket_id).await?.result.is_ok(),
+            "A reservation must survive its server being deactivated."
+        );
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -1,9 +1,24 @@
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use eyre::{eyre, Result};
 use project_settings::ProjectSettings;
+use ticket_sale_rocket::{Database, ShutdownReport};
+use uuid::Uuid;
 
 mod api;
+pub mod conformance;
 mod project_settings;
-pub use api::{Api, ApiResponse, RequestOptions, Reservation, UserSession};
+pub use api::mock::MockOptions;
+pub use api::{
+    Api, ApiError, ApiResponse, CustomerIdSource, RequestOptions, Reservation, ServerStats,
+    SessionState, TimeoutError, UserSession,
+};
+pub use ticket_sale_core::{
+    AllocationStrategy, LaunchInfo, RequestError, RequestMethod, RoutingEvent, RoutingLogger,
+    ScaleDownPolicy, ServerSelection,
+};
 
 #[derive(Clone, Debug)]
 pub enum RunCfg {
@@ -12,17 +27,100 @@ pub enum RunCfg {
     JavaNative(String),
 }
 
+impl RunCfg {
+    /// Get the configurations of all implementations that can be run
+    ///
+    /// The Rust implementation is always available, the Java implementation only if
+    /// the jar from the project settings exists.
+    pub fn available() -> Result<Vec<RunCfg>> {
+        let settings = ProjectSettings::load()?;
+        let mut run_cfgs = vec![RunCfg::RustNative];
+        if settings.java.jar.is_file() {
+            let Some(path) = settings.java.jar.to_str() else {
+                return Err(eyre!("java.jar setting contains invalid UTF-8"));
+            };
+            run_cfgs.push(RunCfg::JavaNative(path.into()));
+        }
+        Ok(run_cfgs)
+    }
+}
+
+#[derive(Clone)]
 pub struct TestCtxBuilder {
     /// Whether to run the bonus implementation
     pub bonus: bool,
     /// Initial ticket number
+    ///
+    /// Must be at most [`u32::MAX`], as the ticket sales system counts tickets as
+    /// `u32` (widening the counts to `u64` is left for later).
     pub tickets: u64,
     /// Count of balancer threads
     pub balancer_threads: u16,
     /// Ticket reservation timeout in seconds
     pub reservation_timeout: u32,
+    /// Maximal number of seconds randomly added to each reservation timeout
+    pub timeout_jitter: u32,
+    /// Time in seconds a server remembers a completed buy (0, the default, disables
+    /// this)
+    pub idempotency_window: u32,
     /// Time in seconds the estimator takes to contact all servers
     pub estimator_roundtrip_time: u32,
+    /// Weight of the latest measurement in the estimator's smoothed ticket counts
+    pub estimator_smoothing: f64,
+    /// Whether the estimator polls all servers at once
+    pub estimator_parallel: bool,
+    /// Whether the estimator runs at all
+    pub estimator_enabled: bool,
+    /// Number of servers below which the available tickets are counted exactly
+    pub exact_below_servers: u32,
+    /// Minimal number of servers
+    pub min_servers: u32,
+    /// Maximal number of servers
+    pub max_servers: u32,
+    /// Strategy for picking the servers to deactivate when scaling down
+    pub scale_down_policy: ScaleDownPolicy,
+    /// Strategy for picking the server of a customer without one
+    pub server_selection: ServerSelection,
+    /// Strategy for deciding how many tickets a server allocates at once
+    pub allocation_strategy: AllocationStrategy,
+    /// Maximal number of tickets a server allocates at once
+    pub max_batch: u32,
+    /// Maximal number of requests waiting for a server
+    pub server_queue_capacity: u32,
+    /// Whether the debugging command making a server sleep is allowed
+    pub allow_debug_sleep: bool,
+    /// Whether the debugging command losing a ticket is allowed
+    pub allow_debug_orphan: bool,
+    /// Whether the database allocates the tickets with the lowest ids first
+    pub ordered_allocation: bool,
+    /// Chance in units of 1/10000 that a bonus server reassigns a request
+    pub reassign_rate: u32,
+    /// Number of recent requests after which a bonus server keeps a customer
+    pub keep_server_after: u32,
+    /// Number of seconds a bonus server counts the requests of a customer
+    pub request_window: u32,
+    /// Probability that the bonus balancer keeps a customer without an active
+    /// session on their previous server
+    pub session_affinity: f64,
+    /// Maximal number of requests of a customer in the bonus implementation
+    pub max_requests_per_customer: u32,
+    /// Token admin requests must carry, if any
+    pub admin_token: Option<String>,
+    /// Called with a summary of the system once it is launched (only for the Rust
+    /// implementation)
+    pub on_launch: Option<fn(&LaunchInfo)>,
+    /// Called with every routing decision of the load balancer (only for the Rust
+    /// implementation)
+    pub routing_logger: Option<RoutingLogger>,
+    /// File the database state is restored from and written to (only for the Rust
+    /// implementation)
+    pub state_path: Option<PathBuf>,
+    /// Seed for generating the customer ids and (only for the Rust implementation)
+    /// the server ids, printed if the test panics
+    pub seed: Option<u64>,
+    /// Seed for shuffling the requests queued at each balancer thread (only for the
+    /// Rust implementation)
+    pub shuffle_seed: Option<u64>,
 
     /// Whether to enable Java assertions (default: true)
     pub assertions: bool,
@@ -52,7 +150,34 @@ impl TestCtxBuilder {
             tickets: 1_000,
             balancer_threads: 2,
             reservation_timeout: 10,
+            timeout_jitter: 0,
+            idempotency_window: 0,
             estimator_roundtrip_time: 10,
+            estimator_smoothing: 1.0,
+            estimator_parallel: false,
+            estimator_enabled: true,
+            exact_below_servers: 0,
+            min_servers: 1,
+            max_servers: 1000,
+            scale_down_policy: ScaleDownPolicy::HighestIndex,
+            server_selection: ServerSelection::Random,
+            allocation_strategy: AllocationStrategy::Sqrt,
+            max_batch: u32::MAX,
+            server_queue_capacity: 10_000,
+            allow_debug_sleep: false,
+            allow_debug_orphan: false,
+            ordered_allocation: false,
+            reassign_rate: 150,
+            keep_server_after: 100,
+            request_window: 10,
+            session_affinity: 1.0,
+            max_requests_per_customer: 0,
+            admin_token: None,
+            on_launch: None,
+            routing_logger: None,
+            state_path: None,
+            seed: None,
+            shuffle_seed: None,
             assertions: true,
             run_cfg,
         })
@@ -77,56 +202,383 @@ impl TestCtxBuilder {
         self
     }
 
+    /// Add a random jitter of up to `jitter` seconds to the timeout of each
+    /// reservation
+    pub fn with_timeout_jitter(mut self, jitter: u32) -> Self {
+        self.timeout_jitter = jitter;
+        self
+    }
+
+    /// Set the time in seconds a server remembers a completed buy, so a retried buy
+    /// succeeds again (0 disables this)
+    pub fn with_idempotency_window(mut self, window: u32) -> Self {
+        self.idempotency_window = window;
+        self
+    }
+
     /// Set the time the estimator takes to contact all servers (in seconds)
     pub fn with_estimator_roundtrip_time(mut self, time: u32) -> Self {
         self.estimator_roundtrip_time = time;
         self
     }
 
+    /// Set the weight of the latest measurement in the estimator's exponentially
+    /// smoothed ticket count of each server (1 means no smoothing)
+    pub fn with_estimator_smoothing(mut self, smoothing: f64) -> Self {
+        assert!(smoothing > 0.0 && smoothing <= 1.0);
+        self.estimator_smoothing = smoothing;
+        self
+    }
+
+    /// Let the estimator poll all servers at once instead of one after another
+    pub fn with_parallel_estimator(mut self) -> Self {
+        self.estimator_parallel = true;
+        self
+    }
+
+    /// Do not start the estimator, e.g., for a single server
+    pub fn without_estimator(mut self) -> Self {
+        self.estimator_enabled = false;
+        self
+    }
+
+    /// Count the available tickets exactly while fewer than `servers` servers run
+    pub fn with_exact_below_servers(mut self, servers: u32) -> Self {
+        self.exact_below_servers = servers;
+        self
+    }
+
+    /// Set the bounds for the number of servers
+    pub fn with_server_bounds(mut self, min: u32, max: u32) -> Self {
+        assert!(min <= max);
+        self.min_servers = min;
+        self.max_servers = max;
+        self
+    }
+
+    /// Set the strategy for picking the servers to deactivate when scaling down
+    pub fn with_scale_down_policy(mut self, policy: ScaleDownPolicy) -> Self {
+        self.scale_down_policy = policy;
+        self
+    }
+
+    /// Set the strategy for picking the server of a customer without one
+    pub fn with_server_selection(mut self, selection: ServerSelection) -> Self {
+        self.server_selection = selection;
+        self
+    }
+
+    /// Set the strategy for deciding how many tickets a server allocates at once
+    pub fn with_allocation_strategy(mut self, strategy: AllocationStrategy) -> Self {
+        self.allocation_strategy = strategy;
+        self
+    }
+
+    /// Cap the number of tickets a server allocates at once
+    pub fn with_max_batch(mut self, max_batch: u32) -> Self {
+        assert_ne!(max_batch, 0);
+        self.max_batch = max_batch;
+        self
+    }
+
+    /// Limit the number of requests waiting for a server, further ones are rejected
+    /// as busy
+    pub fn with_server_queue_capacity(mut self, capacity: u32) -> Self {
+        assert_ne!(capacity, 0);
+        self.server_queue_capacity = capacity;
+        self
+    }
+
+    /// Allow the debugging command making a server sleep
+    pub fn with_debug_sleep(mut self) -> Self {
+        self.allow_debug_sleep = true;
+        self
+    }
+
+    /// Allow the debugging command losing one of a server's tickets
+    pub fn with_debug_orphan(mut self) -> Self {
+        self.allow_debug_orphan = true;
+        self
+    }
+
+    /// Allocate the tickets with the lowest ids first
+    pub fn with_ordered_allocation(mut self) -> Self {
+        self.ordered_allocation = true;
+        self
+    }
+
+    /// Set the chance in units of 1/10000 that a bonus server hands a request of a
+    /// customer without a reservation to a random server
+    pub fn with_reassign_rate(mut self, rate: u32) -> Self {
+        assert!(rate < 10000);
+        self.reassign_rate = rate;
+        self
+    }
+
+    /// Set the number of requests within the request window after which a bonus
+    /// server no longer hands requests of a customer to a random server
+    pub fn with_keep_server_after(mut self, requests: u32) -> Self {
+        self.keep_server_after = requests;
+        self
+    }
+
+    /// Set the number of seconds a bonus server counts the requests of a customer
+    pub fn with_request_window(mut self, window: u32) -> Self {
+        self.request_window = window;
+        self
+    }
+
+    /// Set the probability that the bonus balancer keeps a customer without an
+    /// active session on their previous server instead of handing them to a random
+    /// one
+    pub fn with_session_affinity(mut self, affinity: f64) -> Self {
+        assert!((0.0..=1.0).contains(&affinity));
+        self.session_affinity = affinity;
+        self
+    }
+
+    /// Limit the number of requests of a customer without an active session since
+    /// their last session ended in the bonus implementation
+    pub fn with_max_requests_per_customer(mut self, max_requests: u32) -> Self {
+        self.max_requests_per_customer = max_requests;
+        self
+    }
+
+    /// Require the given token for admin requests (see [`Api::set_admin_token`])
+    pub fn with_admin_token(mut self, token: impl Into<String>) -> Self {
+        self.admin_token = Some(token.into());
+        self
+    }
+
+    /// Call `callback` with a summary of the system once it is launched
+    pub fn with_on_launch(mut self, callback: fn(&LaunchInfo)) -> Self {
+        self.on_launch = Some(callback);
+        self
+    }
+
+    /// Set a callback for the routing decisions of the load balancer (only for the
+    /// Rust implementation)
+    pub fn with_routing_logger(mut self, logger: RoutingLogger) -> Self {
+        self.routing_logger = Some(logger);
+        self
+    }
+
+    /// Restore the database state from `path` if it exists and write it there on
+    /// shutdown (only for the Rust implementation)
+    pub fn with_state_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.state_path = Some(path.into());
+        self
+    }
+
+    /// Generate the customer ids and server ids reproducibly from `seed`, e.g., to
+    /// replay a failed run
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Set how requests are dispatched to the balancer (only for the Rust
+    /// implementation), e.g., [`MockOptions::deterministic`] to replay a specific
+    /// interleaving
+    pub fn with_mock_options(mut self, options: MockOptions) -> Self {
+        self.balancer_threads = options
+            .workers
+            .try_into()
+            .expect("too many balancer threads");
+        assert_ne!(self.balancer_threads, 0);
+        self.shuffle_seed = options.shuffle_seed;
+        self
+    }
+
     /// Try to disable assertions
     pub fn disable_assertions(mut self) -> Self {
         self.assertions = false;
         self
     }
 
+    /// Run the standard and the bonus implementation side by side with the same
+    /// configuration
+    pub fn with_both(self) -> DualTestCtxBuilder {
+        DualTestCtxBuilder(self)
+    }
+
+    /// Get the [`MockOptions`] for dispatching requests to the Rust implementation
+    fn mock_options(&self) -> MockOptions {
+        MockOptions {
+            workers: self.balancer_threads as usize,
+            shuffle_seed: self.shuffle_seed,
+        }
+    }
+
     /// Get the [`ticket_sale_core::Config`] for launching the ticket sales system
-    fn config(&self) -> ticket_sale_core::Config {
-        ticket_sale_core::Config {
-            tickets: self.tickets as u32,
+    ///
+    /// Fails if there are more tickets than the ticket sales system can count.
+    pub fn config(&self) -> Result<ticket_sale_core::Config> {
+        let Ok(tickets) = u32::try_from(self.tickets) else {
+            return Err(eyre!(
+                "{} tickets exceed the maximum of {}",
+                self.tickets,
+                u32::MAX
+            ));
+        };
+        Ok(ticket_sale_core::Config {
+            tickets,
             timeout: self.reservation_timeout,
+            timeout_jitter: self.timeout_jitter,
+            idempotency_window: self.idempotency_window,
             initial_servers: 2,
+            min_servers: self.min_servers,
+            max_servers: self.max_servers,
+            scale_down_policy: self.scale_down_policy,
+            server_selection: self.server_selection,
+            allocation_strategy: self.allocation_strategy,
+            max_batch: self.max_batch,
+            server_queue_capacity: self.server_queue_capacity,
+            allow_debug_sleep: self.allow_debug_sleep,
+            allow_debug_orphan: self.allow_debug_orphan,
+            ordered_allocation: self.ordered_allocation,
+            reassign_rate: self.reassign_rate,
+            keep_server_after: self.keep_server_after,
+            request_window: self.request_window,
+            session_affinity: self.session_affinity,
+            max_requests_per_customer: self.max_requests_per_customer,
+            admin_token: self.admin_token.clone(),
+            on_launch: self.on_launch,
+            server_id_seed: self.seed,
+            expiry_sender: None,
+            routing_logger: self.routing_logger.clone(),
+            state_path: self.state_path.clone(),
             estimator_roundtrip_time: self.estimator_roundtrip_time,
+            estimator_smoothing: self.estimator_smoothing,
+            estimator_parallel: self.estimator_parallel,
+            estimator_enabled: self.estimator_enabled,
+            exact_below_servers: self.exact_below_servers,
             bonus: self.bonus,
-        }
+        })
     }
 
     /// Build the test context
     pub async fn build(self) -> Result<TestCtx> {
-        let config = self.config();
-        let (balancer, api) = match self.run_cfg {
+        let mut config = self.config()?;
+        let (balancer, api, expiries) = match &self.run_cfg {
             RunCfg::RustNative => {
-                let (balancer, api) = api::mock::start(self.balancer_threads, config).await;
-                (Balancer::MockBalancer(balancer), api)
+                let (expiry_sender, expiries) = mpsc::channel();
+                config.expiry_sender = Some(expiry_sender);
+                let (balancer, api) = api::mock::start(self.mock_options(), config).await;
+                (Balancer::MockBalancer(balancer), api, Some(expiries))
             }
             RunCfg::JavaNative(exec) => {
                 let (balancer, api) =
-                    api::jni::start(self.balancer_threads, &config, &exec, self.assertions).await?;
-                (Balancer::JniBalancer(balancer), api)
+                    api::jni::start(self.balancer_threads, &config, exec, self.assertions).await?;
+                (Balancer::JniBalancer(balancer), api, None)
             }
         };
+        Ok(self.into_ctx(balancer, api, expiries))
+    }
 
-        Ok(TestCtx {
+    /// Build the test context, restoring the ticket sales system from a snapshot
+    /// written by [`TestCtx::persist`] (only available for the Rust implementation)
+    ///
+    /// The number of tickets is taken from the snapshot, so it should match
+    /// [`TestCtxBuilder::with_tickets`].
+    pub async fn build_from_snapshot(self, path: &Path) -> Result<TestCtx> {
+        let mut config = self.config()?;
+        let RunCfg::RustNative = self.run_cfg else {
+            return Err(eyre!(
+                "Snapshots are only supported for the Rust implementation"
+            ));
+        };
+        let (expiry_sender, expiries) = mpsc::channel();
+        config.expiry_sender = Some(expiry_sender);
+        let (balancer, api) =
+            api::mock::start_from_snapshot(self.mock_options(), config, path.to_owned()).await?;
+        Ok(self.into_ctx(Balancer::MockBalancer(balancer), api, Some(expiries)))
+    }
+
+    /// Build the test context, sharing the given database with the ticket sales
+    /// system (only available for the Rust implementation)
+    ///
+    /// Tests may seed the database beforehand and inspect it afterwards. The number
+    /// of tickets is taken from the database.
+    pub async fn build_with_database(
+        self,
+        database: Arc<parking_lot::Mutex<Database>>,
+    ) -> Result<TestCtx> {
+        let mut config = self.config()?;
+        let RunCfg::RustNative = self.run_cfg else {
+            return Err(eyre!(
+                "Injecting a database is only supported for the Rust implementation"
+            ));
+        };
+        let (expiry_sender, expiries) = mpsc::channel();
+        config.expiry_sender = Some(expiry_sender);
+        let (balancer, api) =
+            api::mock::start_with_database(self.mock_options(), config, database).await;
+        Ok(self.into_ctx(Balancer::MockBalancer(balancer), api, Some(expiries)))
+    }
+
+    fn into_ctx(
+        self,
+        balancer: Balancer,
+        api: Api,
+        expiries: Option<mpsc::Receiver<Uuid>>,
+    ) -> TestCtx {
+        if let Some(seed) = self.seed {
+            api.set_customer_id_source(CustomerIdSource::Seeded(seed));
+        }
+        TestCtx {
             api,
             balancer,
             bonus: self.bonus,
             tickets: self.tickets,
             balancer_threads: self.balancer_threads,
             reservation_timeout: self.reservation_timeout,
-            drop_bomb: DropBomb,
+            seed: self.seed,
+            expiries: expiries.map(|expiries| Arc::new(Mutex::new(expiries))),
+            drop_bomb: DropBomb { seed: self.seed },
+        }
+    }
+}
+
+/// Builder for a [`DualTestCtx`], see [`TestCtxBuilder::with_both`]
+#[derive(Clone)]
+pub struct DualTestCtxBuilder(TestCtxBuilder);
+
+impl DualTestCtxBuilder {
+    /// Build the test contexts of both implementations
+    pub async fn build(self) -> Result<DualTestCtx> {
+        let standard = TestCtxBuilder {
+            bonus: false,
+            ..self.0.clone()
+        };
+        let bonus = TestCtxBuilder {
+            bonus: true,
+            ..self.0
+        };
+        Ok(DualTestCtx {
+            standard: standard.build().await?,
+            bonus: bonus.build().await?,
         })
     }
 }
 
+/// Test contexts of the standard and the bonus implementation sharing the same
+/// configuration, e.g., for differential testing
+pub struct DualTestCtx {
+    /// Test context of the standard implementation
+    pub standard: TestCtx,
+    /// Test context of the bonus implementation
+    pub bonus: TestCtx,
+}
+
+impl DualTestCtx {
+    /// Shut down both ticket sales systems and finish the test
+    pub async fn finish(self) {
+        self.standard.finish().await;
+        self.bonus.finish().await;
+    }
+}
+
 enum Balancer {
     MockBalancer(api::mock::MockBalancer),
     JniBalancer(api::jni::JniBalancer),
@@ -145,26 +597,174 @@ pub struct TestCtx {
     pub balancer_threads: u16,
     /// Reservation timeout
     pub reservation_timeout: u32,
+    /// Seed the ids are generated from, if any
+    pub seed: Option<u64>,
+    /// Customer ids of the expired reservations (only for the Rust implementation)
+    expiries: Option<Arc<Mutex<mpsc::Receiver<Uuid>>>>,
 
     drop_bomb: DropBomb,
 }
 
+const ESTIMATOR_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
 impl TestCtx {
     /// Shut down the ticket sales system and finish the test
+    ///
+    /// For the Rust implementation, this also checks that no tickets got lost.
     pub async fn finish(self) {
+        if let Some(report) = self.finish_with_report().await {
+            assert!(
+                report.is_consistent(),
+                "After shutdown, every ticket must either be available or sold.\n{report:?}",
+            );
+        }
+    }
+
+    /// Shut down the ticket sales system and finish the test, returning the
+    /// reconciliation of the tickets (only available for the Rust implementation)
+    pub async fn finish_with_report(self) -> Option<ShutdownReport> {
+        self.shutdown(None)
+            .await
+            .expect("Shutdown without deadline cannot time out")
+    }
+
+    /// Shut down the ticket sales system and finish the test like [`Self::finish`],
+    /// failing if the shutdown takes longer than `deadline`
+    ///
+    /// This catches shutdowns that do not join all threads, e.g., due to deadlocks.
+    pub async fn finish_within(self, deadline: Duration) -> Result<()> {
+        if let Some(report) = self.shutdown(Some(deadline)).await? {
+            assert!(
+                report.is_consistent(),
+                "After shutdown, every ticket must either be available or sold.\n{report:?}",
+            );
+        }
+        Ok(())
+    }
+
+    /// Shut down the ticket sales system, failing if this takes longer than the
+    /// given deadline
+    async fn shutdown(self, deadline: Option<Duration>) -> Result<Option<ShutdownReport>> {
         std::mem::forget(self.drop_bomb);
         drop(self.api);
         match self.balancer {
-            Balancer::MockBalancer(b) => b.shutdown().await,
-            Balancer::JniBalancer(b) => b.shutdown().await,
+            Balancer::MockBalancer(b) => Ok(Some(b.shutdown(deadline).await?)),
+            Balancer::JniBalancer(b) => {
+                match deadline {
+                    Some(deadline) => {
+                        tokio::time::timeout(deadline, b.shutdown())
+                            .await
+                            .map_err(|_| eyre!("Shutdown did not complete within {deadline:?}"))?
+                    }
+                    None => b.shutdown().await,
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    /// Drain all servers for up to `drain_secs` seconds and shut them down, so
+    /// reserved tickets can still be bought meanwhile (only available for the Rust
+    /// implementation)
+    ///
+    /// Afterwards, every request is refused and the test still has to be finished.
+    pub async fn graceful_shutdown(&self, drain_secs: u32) -> Result<()> {
+        match &self.balancer {
+            Balancer::MockBalancer(b) => {
+                b.graceful_shutdown(drain_secs).await;
+                Ok(())
+            }
+            Balancer::JniBalancer(_) => {
+                Err(eyre!(
+                    "Graceful shutdowns are only supported for the Rust implementation"
+                ))
+            }
         }
     }
+
+    /// Persist the state of the ticket sales system to the file at `path` (only
+    /// available for the Rust implementation)
+    pub async fn persist(&self, path: &Path) -> Result<()> {
+        match &self.balancer {
+            Balancer::MockBalancer(b) => Ok(b.persist(path.to_owned()).await?),
+            Balancer::JniBalancer(_) => {
+                Err(eyre!(
+                    "Snapshots are only supported for the Rust implementation"
+                ))
+            }
+        }
+    }
+
+    /// Get the total number of non-reserved tickets held by the non-terminating
+    /// servers
+    ///
+    /// Unlike [`Api::get_available_tickets`], this sums up the exact counts of the
+    /// servers instead of an estimate. Tickets still in the database are not included.
+    pub async fn total_available(&self) -> Result<u64> {
+        let mut total = 0;
+        for server in self.api.get_servers().await?.result? {
+            let options = RequestOptions {
+                server_id: Some(server),
+                customer_id: None,
+                ..Default::default()
+            };
+            let response = self.api.get_local_available_tickets(&options).await?;
+            if response.server_id != Some(server) {
+                return Err(eyre!("Server {server} did not report its own tickets"));
+            }
+            total += response.result?;
+        }
+        Ok(total)
+    }
+
+    /// Wait for the next reservation to expire and get the id of its customer (only
+    /// available for the Rust implementation)
+    ///
+    /// Servers notice expired reservations when processing a request or when
+    /// contacted by the estimator. Fails if no expiry is noticed within `timeout`.
+    pub async fn next_expiry(&self, timeout: Duration) -> Result<Uuid> {
+        let Some(expiries) = self.expiries.clone() else {
+            return Err(eyre!(
+                "Expiry notifications are only supported for the Rust implementation"
+            ));
+        };
+        let expiry =
+            tokio::task::spawn_blocking(move || expiries.lock().unwrap().recv_timeout(timeout));
+        expiry
+            .await?
+            .map_err(|_| eyre!("No reservation expired within {timeout:?}"))
+    }
+
+    /// Measure the average time the estimator takes for a pass over all servers
+    ///
+    /// Waits for the current pass to complete and then times the next `passes` ones.
+    pub async fn measure_estimator_roundtrip(&self, passes: u64) -> Result<Duration> {
+        let initial = self.api.debug_estimator_passes().await?.result?;
+        let mut current = initial;
+        while current == initial {
+            tokio::time::sleep(ESTIMATOR_POLL_INTERVAL).await;
+            current = self.api.debug_estimator_passes().await?.result?;
+        }
+
+        let start = Instant::now();
+        let target = current + passes;
+        while current < target {
+            tokio::time::sleep(ESTIMATOR_POLL_INTERVAL).await;
+            current = self.api.debug_estimator_passes().await?.result?;
+        }
+        Ok(start.elapsed() / passes as u32)
+    }
 }
 
-struct DropBomb;
+struct DropBomb {
+    seed: Option<u64>,
+}
 
 impl Drop for DropBomb {
     fn drop(&mut self) {
+        if let (Some(seed), true) = (self.seed, std::thread::panicking()) {
+            eprintln!("The test failed with seed {seed}, replay it using `TestCtxBuilder::with_seed({seed})`");
+        }
         eprintln!("@TestAuthor: You should call `ctx.finish().await` to shut the ticket sales system down");
     }
 }
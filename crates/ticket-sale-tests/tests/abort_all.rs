@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_abort_all() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let mut options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    assert!(
+        ctx.api.abort_all(&options).await?.result.is_err(),
+        "Aborting all reservations without a reservation must fail."
+    );
+
+    let response = ctx.api.reserve_multiple(5, &options).await?;
+    options.server_id = response.server_id;
+    let mut reserved = response.result?;
+    let local = ctx
+        .api
+        .get_local_available_tickets(&options)
+        .await?
+        .result?;
+    let mut released = ctx.api.abort_all(&options).await?.result?;
+    reserved.sort_unstable();
+    released.sort_unstable();
+    assert_eq!(released, reserved, "All reserved tickets must be released.");
+    assert_eq!(
+        ctx.api
+            .get_local_available_tickets(&options)
+            .await?
+            .result?,
+        local + 5,
+        "The released tickets must return to the server."
+    );
+    assert_eq!(ctx.api.get_reserved_count().await?.result?, 0);
+
+    assert!(
+        ctx.api.abort_all(&options).await?.result.is_err(),
+        "After aborting all tickets, no reservation is left."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_abort_all_leaves_later_reservation() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_reservation_timeout(2)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let mut options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.reserve_multiple(2, &options).await?;
+    options.server_id = response.server_id;
+    response.result?;
+    ctx.api.abort_all(&options).await?.result?;
+
+    // Reserve again before the aborted reservations would have expired
+    tokio::time::sleep(Duration::from_millis(1_500)).await;
+    let ticket_ids = ctx.api.reserve_multiple(1, &options).await?.result?;
+
+    // Once the aborted reservations would have expired, the new one is still valid
+    tokio::time::sleep(Duration::from_millis(1_700)).await;
+    ctx.api.get_available_tickets(&options).await?.result?;
+    assert!(
+        ctx.api
+            .buy_ticket(ticket_ids[0], &options)
+            .await?
+            .result
+            .is_ok(),
+        "An aborted reservation must not expire a later reservation of the customer."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
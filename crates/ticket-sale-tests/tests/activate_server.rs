@@ -0,0 +1,65 @@
+use std::collections::HashSet;
+
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use util::scale_to;
+use uuid::Uuid;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_activate_terminating_server() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 3).await?;
+
+    // Keep a reservation on every server, so that they do not terminate
+    for &server_id in &servers {
+        let options = RequestOptions {
+            server_id: Some(server_id),
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        ctx.api.reserve_ticket(&options).await?.result?.reserved()?;
+    }
+
+    // Deactivate one of them
+    let remaining = scale_to(&ctx, 2).await?;
+    let deactivated = *servers.difference(&remaining).next().unwrap();
+
+    // Only terminating servers can be activated
+    assert!(
+        ctx.api
+            .activate_server(Uuid::new_v4())
+            .await?
+            .result
+            .is_err(),
+        "Activating an unknown server must fail."
+    );
+    let active = *remaining.iter().next().unwrap();
+    assert!(
+        ctx.api.activate_server(active).await?.result.is_err(),
+        "Activating a non-terminating server must fail."
+    );
+
+    // Activate the deactivated server again
+    assert_eq!(ctx.api.activate_server(deactivated).await?.result?, 3);
+    let servers_after: HashSet<_> = ctx.api.get_servers().await?.result?.into_iter().collect();
+    assert_eq!(servers_after, servers);
+
+    // It serves new customers again
+    let options = RequestOptions {
+        server_id: Some(deactivated),
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.reserve_ticket(&options).await?;
+    response.result?.reserved()?;
+    assert_eq!(response.server_id, Some(deactivated));
+
+    ctx.finish().await;
+    Ok(())
+}
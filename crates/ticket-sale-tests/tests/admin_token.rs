@@ -0,0 +1,50 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_admin_token() -> Result<()> {
+    let mut ctx = TestCtxBuilder::from_env()?
+        .with_admin_token("secret")
+        .build()
+        .await?;
+
+    // Admin requests without the token are rejected
+    assert!(
+        ctx.api.get_num_servers().await?.result.is_err(),
+        "Getting the number of servers without the admin token must fail."
+    );
+    assert!(
+        ctx.api.post_num_servers(3).await?.result.is_err(),
+        "Scaling without the admin token must fail."
+    );
+    assert!(
+        ctx.api.get_servers().await?.result.is_err(),
+        "Getting the servers without the admin token must fail."
+    );
+
+    // User requests stay open
+    let options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    ctx.api.get_available_tickets(&options).await?.result?;
+
+    // A wrong token is rejected as well
+    ctx.api.set_admin_token(Some("wrong".to_owned()));
+    assert!(
+        ctx.api.post_num_servers(3).await?.result.is_err(),
+        "Scaling with a wrong admin token must fail."
+    );
+
+    // Admin requests with the token succeed
+    ctx.api.set_admin_token(Some("secret".to_owned()));
+    assert_eq!(ctx.api.post_num_servers(3).await?.result?, 3);
+    assert_eq!(ctx.api.get_num_servers().await?.result?, 3);
+    assert_eq!(ctx.api.get_servers().await?.result?.len(), 3);
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, Reservation, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_reserve_at_position() -> Result<()> {
+    // With 100 tickets, the first reservation allocates sqrt(100) = 10 tickets,
+    // i.e., the ids 90 to 99 in ascending order
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .build()
+        .await?;
+
+    let options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.debug_reserve_position(3, &options).await?;
+    assert!(
+        matches!(response.result?, Reservation::Reserved(93, _)),
+        "Reserving at position 3 must reserve the fourth allocated ticket."
+    );
+
+    // Another customer on the same server gets the front ticket
+    let options = RequestOptions {
+        server_id: response.server_id,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.debug_reserve_position(0, &options).await?;
+    assert!(
+        matches!(response.result?, Reservation::Reserved(90, _)),
+        "Reserving at position 0 must reserve the front ticket."
+    );
+
+    // Positions beyond the server's tickets are rejected
+    let options = RequestOptions {
+        server_id: response.server_id,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.debug_reserve_position(100, &options).await?;
+    assert!(
+        response.result.is_err(),
+        "Reserving at a position without a ticket must fail."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
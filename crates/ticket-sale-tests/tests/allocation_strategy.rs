@@ -0,0 +1,90 @@
+use eyre::Result;
+use ticket_sale_tests::{AllocationStrategy, RequestOptions, TestCtxBuilder};
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_fixed_single_allocation() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_server_bounds(1, 1)
+        .with_allocation_strategy(AllocationStrategy::Fixed(1))
+        .build()
+        .await?;
+
+    // Every reservation allocates exactly the reserved ticket from the database
+    for _ in 0..5 {
+        let mut session = ctx.api.create_user_session(None);
+        session.reserve_ticket().await?.result?.reserved()?;
+        assert_eq!(
+            ctx.total_available().await?,
+            0,
+            "With a batch size of one, the server must not keep any tickets."
+        );
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_fixed_batch_allocation() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_server_bounds(1, 1)
+        .with_allocation_strategy(AllocationStrategy::Fixed(100))
+        .build()
+        .await?;
+
+    // The first reservation allocates 100 tickets, the following ones use them
+    for reserved in 1..=5 {
+        let mut session = ctx.api.create_user_session(None);
+        session.reserve_ticket().await?.result?.reserved()?;
+        assert_eq!(
+            ctx.total_available().await?,
+            100 - reserved,
+            "The server must reserve from its batch of 100 tickets."
+        );
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_max_batch() -> Result<()> {
+    // Without the cap, the first server would allocate sqrt(1_000_000) = 1000 tickets
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000_000)
+        .with_max_batch(100)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 10).await?;
+
+    for server in servers {
+        let mut session = ctx.api.create_user_session(Some(server));
+        session.reserve_ticket().await?.result?.reserved()?;
+        let options = RequestOptions {
+            server_id: Some(server),
+            customer_id: None,
+            ..Default::default()
+        };
+        let local = ctx
+            .api
+            .get_local_available_tickets(&options)
+            .await?
+            .result?;
+        assert!(
+            local < 100,
+            "A server must not allocate more tickets than the cap, but holds {local}."
+        );
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
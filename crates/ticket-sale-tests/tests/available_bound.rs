@@ -0,0 +1,41 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_available_bounded_while_scaling() -> Result<()> {
+    let tickets = 500;
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(tickets)
+        .with_estimator_roundtrip_time(1)
+        .build()
+        .await?;
+
+    let mut sold = 0;
+    for i in 0..100 {
+        // Scale up and down aggressively so the estimates drift
+        ctx.api
+            .post_num_servers(if i % 2 == 0 { 15 } else { 1 })
+            .await?;
+
+        // Sell one ticket
+        let mut session = ctx.api.create_user_session(None);
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        session.buy_ticket(ticket_id).await?.result?;
+        sold += 1;
+
+        // No server may report more tickets than remain
+        for _ in 0..5 {
+            let mut session = ctx.api.create_user_session(None);
+            let available = session.get_available_tickets().await?.result?;
+            assert!(
+                available <= tickets - sold,
+                "Reported {available} available tickets, but only {} remain.",
+                tickets - sold
+            );
+        }
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,53 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_available_tickets_per_server() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .without_estimator()
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 3).await?;
+
+    // Reserve a ticket on each server, so all of them hold some
+    for server in &servers {
+        let mut session = ctx.api.create_user_session(Some(*server));
+        session.reserve_ticket().await?.result?.reserved()?;
+    }
+
+    let mut local = 0;
+    let mut reported = 0;
+    for server in &servers {
+        let response = ctx.api.get_available_tickets_for(*server).await?;
+        assert_eq!(
+            response.server_id,
+            Some(*server),
+            "The response must carry the id of the queried server."
+        );
+        reported += response.result?;
+        let options = RequestOptions {
+            server_id: Some(*server),
+            ..Default::default()
+        };
+        local += ctx
+            .api
+            .get_local_available_tickets(&options)
+            .await?
+            .result?;
+    }
+
+    // Without the estimator, each server reports its own tickets plus the database
+    let total = ctx.api.get_total_available().await?.result?;
+    assert_eq!(total, 997);
+    let database = total - local;
+    assert_eq!(reported, local + servers.len() as u64 * database);
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use util::scale_to;
+use uuid::Uuid;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_buy_all() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .build()
+        .await?;
+
+    let mut options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    assert!(
+        ctx.api.buy_all(&options).await?.result.is_err(),
+        "Buying all tickets without a reservation must fail."
+    );
+
+    let response = ctx.api.reserve_multiple(5, &options).await?;
+    options.server_id = response.server_id;
+    let mut reserved = response.result?;
+    let mut bought = ctx.api.buy_all(&options).await?.result?;
+    reserved.sort_unstable();
+    bought.sort_unstable();
+    assert_eq!(bought, reserved, "All reserved tickets must be bought.");
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 5);
+
+    assert!(
+        ctx.api.buy_all(&options).await?.result.is_err(),
+        "After buying all tickets, no reservation is left."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_buy_all_terminates_server() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    scale_to(&ctx, 2).await?;
+
+    // The last listed server is the one deactivated when scaling down
+    let server = *ctx.api.get_servers().await?.result?.last().unwrap();
+    let options = RequestOptions {
+        server_id: Some(server),
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    ctx.api.reserve_multiple(3, &options).await?.result?;
+    scale_to(&ctx, 1).await?;
+
+    // Buying the last reservations lets the terminating server terminate, so it
+    // cannot be activated again
+    assert_eq!(ctx.api.buy_all(&options).await?.result?.len(), 3);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(
+        ctx.api.activate_server(server).await?.result.is_err(),
+        "The server must terminate once its last reservation is bought."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
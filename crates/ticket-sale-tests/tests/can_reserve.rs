@@ -0,0 +1,65 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, Reservation, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_can_reserve_until_sold_out() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?.with_tickets(4).build().await?;
+
+    // The first reservation allocates sqrt(4) = 2 tickets, so the server still
+    // holds one afterwards
+    let first = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    assert!(ctx.api.can_reserve(&first).await?.result?);
+    let response = ctx.api.reserve_ticket(&first).await?;
+    let server_id = response.server_id;
+    let Reservation::Reserved(ticket_id, _) = response.result? else {
+        panic!("The first reservation must succeed.");
+    };
+    let first = RequestOptions {
+        server_id,
+        customer_id: first.customer_id,
+        ..Default::default()
+    };
+    assert!(
+        !ctx.api.can_reserve(&first).await?.result?,
+        "A customer with a reservation cannot reserve another ticket."
+    );
+
+    // The probe must not reserve the server's remaining ticket
+    let probe = RequestOptions {
+        server_id,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    assert!(ctx.api.can_reserve(&probe).await?.result?);
+    assert!(ctx.api.can_reserve(&probe).await?.result?);
+    ctx.api.buy_ticket(ticket_id, &first).await?.result?;
+
+    // Sell the remaining tickets
+    loop {
+        let options = RequestOptions {
+            server_id,
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        match ctx.api.reserve_ticket(&options).await?.result? {
+            Reservation::SoldOut => break,
+            Reservation::Reserved(ticket_id, _) => {
+                ctx.api.buy_ticket(ticket_id, &options).await?.result?;
+            }
+        }
+    }
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 4);
+    assert!(
+        !ctx.api.can_reserve(&probe).await?.result?,
+        "After the sell-out, no reservation is possible."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
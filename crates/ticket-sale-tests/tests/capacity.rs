@@ -0,0 +1,30 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_capacity_stays_constant() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .build()
+        .await?;
+    assert_eq!(ctx.api.get_capacity().await?.result?, 100);
+
+    // Neither scaling nor sales change the capacity
+    let servers = scale_to(&ctx, 4).await?;
+    assert_eq!(ctx.api.get_capacity().await?.result?, 100);
+    for server in servers {
+        let mut session = ctx.api.create_user_session(Some(server));
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        session.buy_ticket(ticket_id).await?.result?;
+    }
+    scale_to(&ctx, 2).await?;
+    assert_eq!(ctx.api.get_capacity().await?.result?, 100);
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 4);
+
+    ctx.finish().await;
+    Ok(())
+}
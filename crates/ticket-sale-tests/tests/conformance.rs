@@ -0,0 +1,14 @@
+use eyre::Result;
+use ticket_sale_tests::{conformance, RunCfg, TestCtxBuilder};
+
+#[tokio::test]
+#[ntest::timeout(60_000)] // Test timeout in ms
+async fn test_conformance() -> Result<()> {
+    // Runs against the Java implementation as well if its jar is built
+    for run_cfg in RunCfg::available()? {
+        let mut builder = TestCtxBuilder::from_env()?;
+        builder.run_cfg = run_cfg;
+        conformance::run_all(&builder).await?;
+    }
+    Ok(())
+}
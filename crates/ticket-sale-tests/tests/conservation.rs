@@ -0,0 +1,165 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, Reservation, TestCtxBuilder};
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_no_tickets_orphaned() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .build()
+        .await?;
+    scale_to(&ctx, 5).await?;
+
+    // Buy some tickets, abort some and leave some reserved
+    for i in 0..60 {
+        let mut session = ctx.api.create_user_session(None);
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        match i % 3 {
+            0 => assert!(session.buy_ticket(ticket_id).await?.result.is_ok()),
+            1 => assert!(session.abort_purchase(ticket_id).await?.result.is_ok()),
+            _ => {}
+        }
+        if i == 30 {
+            scale_to(&ctx, 2).await?;
+        }
+    }
+
+    if let Some(report) = ctx.finish_with_report().await {
+        assert_eq!(report.sold, 20, "Exactly the bought tickets must be sold.");
+        assert!(
+            report.is_consistent(),
+            "Every ticket must either be available or sold after shutdown.\n{report:?}"
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_orphaned_ticket_detected() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_debug_orphan()
+        .build()
+        .await?;
+
+    let mut session = ctx.api.create_user_session(None);
+    let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+    assert!(session.buy_ticket(ticket_id).await?.result.is_ok());
+
+    // Lose a ticket on purpose
+    let options = RequestOptions::default();
+    let response = ctx.api.debug_orphan_ticket(&options).await?;
+    assert!(matches!(response.result?, Reservation::Reserved(_, _)));
+
+    if let Some(report) = ctx.finish_with_report().await {
+        assert!(
+            !report.is_consistent(),
+            "The lost ticket must be detected.\n{report:?}"
+        );
+        assert_eq!(report.orphaned(), 1);
+        assert_eq!(report.sold, 1);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_orphan_ticket_disabled_by_default() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .build()
+        .await?;
+
+    let options = RequestOptions::default();
+    assert!(
+        ctx.api.debug_orphan_ticket(&options).await?.result.is_err(),
+        "Losing a ticket must only be allowed if enabled."
+    );
+
+    // No ticket got lost, which `finish` checks
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_sold_tickets_counted() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .build()
+        .await?;
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 0);
+
+    // Only bought tickets count as sold, reserved and aborted ones do not
+    let mut sold = 0;
+    let mut reserved = 0;
+    for i in 0..30 {
+        let mut session = ctx.api.create_user_session(None);
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        match i % 3 {
+            0 => {
+                session.buy_ticket(ticket_id).await?.result?;
+                sold += 1;
+            }
+            1 => {
+                session.abort_purchase(ticket_id).await?.result?;
+            }
+            _ => reserved += 1,
+        }
+        assert_eq!(ctx.api.get_sold_tickets().await?.result?, sold);
+        if i == 15 {
+            scale_to(&ctx, 4).await?;
+        }
+    }
+
+    if let Some(report) = ctx.finish_with_report().await {
+        // Reserved tickets are returned to the database on shutdown
+        assert_eq!(report.sold as u64, sold);
+        assert_eq!(
+            (report.sold + report.available) as u64,
+            100,
+            "Sold, available, and the {reserved} reserved tickets must add up to all tickets."
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_abort_of_sold_ticket() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    scale_to(&ctx, 2).await?;
+
+    let mut session = ctx.api.create_user_session(None);
+    let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+    assert!(session.buy_ticket(ticket_id).await?.result.is_ok());
+
+    // A spurious abort of the sold ticket must not make it available again
+    let available = ctx.total_available().await?;
+    assert!(
+        session.abort_purchase(ticket_id).await?.result.is_err(),
+        "Aborting a sold ticket must fail."
+    );
+    assert_eq!(
+        ctx.total_available().await?,
+        available,
+        "Aborting a sold ticket must not change the available tickets."
+    );
+
+    if let Some(report) = ctx.finish_with_report().await {
+        assert_eq!(report.sold, 1, "Exactly the bought ticket must be sold.");
+        assert!(
+            report.is_consistent(),
+            "Every ticket must either be available or sold after shutdown.\n{report:?}"
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,32 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_reservation_reports_active_server() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 3).await?;
+
+    for _ in 0..20 {
+        let mut session = ctx.api.create_user_session(None);
+        let response = session.reserve_ticket().await?;
+        response.result?.reserved()?;
+        let Some(server) = session.current_server() else {
+            panic!("A successful reservation must report the server holding it.");
+        };
+        assert_eq!(response.server_id, Some(server));
+        assert!(
+            servers.contains(&server),
+            "The server holding a reservation must be an active server."
+        );
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
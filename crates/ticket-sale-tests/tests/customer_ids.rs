@@ -0,0 +1,58 @@
+use eyre::Result;
+use ticket_sale_tests::{CustomerIdSource, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_sequential_and_seeded_customer_ids() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?.build().await?;
+
+    ctx.api.set_customer_id_source(CustomerIdSource::Sequential);
+    let first = ctx.api.create_user_session(None).customer_id;
+    let second = ctx.api.create_user_session(None).customer_id;
+    assert_eq!(first, Uuid::from_u128(1));
+    assert_eq!(second, Uuid::from_u128(2));
+
+    ctx.api.set_customer_id_source(CustomerIdSource::Seeded(42));
+    let first = ctx.api.create_user_session(None).customer_id;
+    ctx.api.set_customer_id_source(CustomerIdSource::Seeded(42));
+    assert_eq!(
+        first,
+        ctx.api.create_user_session(None).customer_id,
+        "The same seed must produce the same customer ids."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_one_reservation_per_colliding_customer() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?.build().await?;
+
+    // Every session gets the same customer id
+    let customer_id = Uuid::new_v4();
+    ctx.api
+        .set_customer_id_source(CustomerIdSource::Fixed(vec![customer_id]));
+
+    let mut session = ctx.api.create_user_session(None);
+    let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+
+    // A second session of the same customer on the same server must not be able
+    // to reserve another ticket
+    let mut other = ctx.api.create_user_session(session.server_id);
+    assert_eq!(other.customer_id, customer_id);
+    assert!(
+        other.reserve_ticket().await?.result.is_err(),
+        "A customer must not hold more than one reservation."
+    );
+
+    // Once the ticket is bought, the customer may reserve again
+    assert!(session.buy_ticket(ticket_id).await?.result.is_ok());
+    let mut other = ctx.api.create_user_session(session.server_id);
+    assert!(other.reserve_ticket().await?.result?.reserved().is_ok());
+
+    ctx.finish().await;
+    Ok(())
+}
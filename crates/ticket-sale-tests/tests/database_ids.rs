@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+use util::hammer_reserve_and_buy;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_database_ids() -> Result<()> {
+    // A single server, so the tickets are sold out once the database is empty
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_server_bounds(1, 1)
+        .build()
+        .await?;
+
+    let ids = ctx.api.debug_db_ids().await?.result?;
+    assert_eq!(
+        HashSet::<u64>::from_iter(ids.iter().copied()),
+        HashSet::from_iter(0..100),
+        "Initially, the database must hold every ticket."
+    );
+
+    // The first reservation allocates sqrt(100) = 10 tickets
+    let mut session = ctx.api.create_user_session(None);
+    let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+    let ids = ctx.api.debug_db_ids().await?.result?;
+    assert_eq!(ids.len(), 90);
+    assert!(
+        !ids.contains(&ticket_id),
+        "A reserved ticket must not be in the database."
+    );
+    assert_eq!(
+        HashSet::<u64>::from_iter(ids.iter().copied()).len(),
+        ids.len(),
+        "No id may be in the database twice."
+    );
+    session.buy_ticket(ticket_id).await?.result?;
+
+    // After selling every ticket, the database is empty
+    assert_eq!(hammer_reserve_and_buy(&ctx, 4, 50).await?, 99);
+    assert!(ctx.api.debug_db_ids().await?.result?.is_empty());
+
+    ctx.finish().await;
+    Ok(())
+}
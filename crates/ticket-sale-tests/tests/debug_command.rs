@@ -0,0 +1,61 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestMethod, TestCtxBuilder};
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_debug_command() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?.build().await?;
+
+    // The statistics list every non-terminating server
+    let stats = ctx
+        .api
+        .debug("/api/debug/server_stats", RequestMethod::Get, None)
+        .await?
+        .result?;
+    for server in ctx.api.get_servers().await?.result? {
+        assert!(
+            stats.contains(&server.to_string()),
+            "The statistics must contain server {server}:\n{stats}"
+        );
+    }
+
+    // Integer responses are rendered as text
+    let passes = ctx
+        .api
+        .debug("/api/debug/estimator_passes", RequestMethod::Get, None)
+        .await?
+        .result?;
+    assert!(passes.parse::<u64>().is_ok());
+
+    // Unknown commands are answered by the balancer
+    let response = ctx
+        .api
+        .debug(
+            "/api/debug/unknown",
+            RequestMethod::Post,
+            Some(b"body".to_vec()),
+        )
+        .await?
+        .result?;
+    assert_eq!(response, "Happy Debugging! 🚫🐛");
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_plain_debug_request() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?.build().await?;
+
+    // A debug request without a specific command reaches the balancer
+    let response = ctx
+        .api
+        .debug("/api/debug", RequestMethod::Get, None)
+        .await?
+        .result?;
+    assert_eq!(response, "Happy Debugging! 🚫🐛");
+
+    ctx.finish().await;
+    Ok(())
+}
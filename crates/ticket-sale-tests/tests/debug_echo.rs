@@ -0,0 +1,22 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_debug_echo_reflects_headers() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?.build().await?;
+
+    let headers = vec![
+        (String::from("X-Frontend-Version"), String::from("1.2.3")),
+        (String::from("X-Trace"), String::from("a: b")),
+    ];
+    assert_eq!(
+        ctx.api.debug_echo(&headers).await?.result?,
+        headers,
+        "The echo command must reflect the headers of the request."
+    );
+    assert!(ctx.api.debug_echo(&[]).await?.result?.is_empty());
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,75 @@
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_debug_sleep_blocks_only_target_server() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_debug_sleep()
+        .build()
+        .await?;
+
+    let servers = ctx.api.get_servers().await?.result?;
+    let options = |server| {
+        RequestOptions {
+            server_id: Some(server),
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        }
+    };
+
+    assert_eq!(
+        ctx.api
+            .debug_sleep(1_000, &options(servers[0]))
+            .await?
+            .result?,
+        1_000
+    );
+
+    // The other server is not affected
+    let start = Instant::now();
+    ctx.api
+        .get_available_tickets(&options(servers[1]))
+        .await?
+        .result?;
+    assert!(
+        start.elapsed() < Duration::from_millis(500),
+        "A server that is not sleeping must respond immediately."
+    );
+
+    // The sleeping server only responds after waking up
+    let start = Instant::now();
+    ctx.api
+        .get_available_tickets(&options(servers[0]))
+        .await?
+        .result?;
+    assert!(
+        start.elapsed() >= Duration::from_millis(500),
+        "A sleeping server must not respond immediately."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_debug_sleep_disallowed_by_default() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?.build().await?;
+
+    let options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    assert!(
+        ctx.api.debug_sleep(1_000, &options).await?.result.is_err(),
+        "Sleeping must be rejected unless allowed in the configuration."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use eyre::Result;
+use ticket_sale_tests::{MockOptions, TestCtxBuilder};
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_deterministic_dispatch_replays_race() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1)
+        .with_reassign_rate(0)
+        .with_mock_options(MockOptions::deterministic())
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 1).await?;
+    let server = servers.iter().next().copied();
+    let mut first = ctx.api.create_user_session(server);
+    let mut second = ctx.api.create_user_session(server);
+
+    // Both customers race for the last ticket, but the request sent first always
+    // wins, no matter how often the race is repeated
+    for round in 0..20 {
+        let (winner, loser) = if round % 2 == 0 {
+            (&mut first, &mut second)
+        } else {
+            (&mut second, &mut first)
+        };
+        let (won, lost) = futures::join!(winner.reserve_ticket(), loser.reserve_ticket());
+        let ticket_id = won?.result?.reserved()?;
+        assert!(
+            lost?.result?.reserved().is_err(),
+            "The request sent second must find the ticket reserved."
+        );
+        winner.abort_purchase(ticket_id).await?.result?;
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_shuffled_dispatch_sells_each_ticket_once() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(10)
+        .with_reassign_rate(0)
+        .with_mock_options(MockOptions {
+            workers: 1,
+            shuffle_seed: Some(42),
+        })
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 1).await?;
+    let server = servers.iter().next().copied();
+
+    // Queue more reservations than there are tickets at once
+    let mut sessions: Vec<_> = (0..20)
+        .map(|_| ctx.api.create_user_session(server))
+        .collect();
+    let results =
+        futures::future::join_all(sessions.iter_mut().map(|session| session.reserve_ticket()))
+            .await;
+    let mut reserved = 0;
+    for result in results {
+        if result?.result?.reserved().is_ok() {
+            reserved += 1;
+        }
+    }
+    assert_eq!(reserved, 10, "Each ticket must be reserved exactly once.");
+
+    ctx.finish().await;
+    Ok(())
+}
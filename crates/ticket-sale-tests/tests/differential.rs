@@ -0,0 +1,32 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+mod util;
+use util::hammer_reserve_and_buy;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_standard_and_bonus_sell_the_same() -> Result<()> {
+    let dual = TestCtxBuilder::from_env()?
+        .with_tickets(300)
+        .with_balancer_threads(4)
+        .with_seed(0x5eed)
+        // With a single server, sold out means all tickets are sold
+        .with_server_bounds(1, 1)
+        .with_both()
+        .build()
+        .await?;
+
+    for ctx in [&dual.standard, &dual.bonus] {
+        let implementation = if ctx.bonus { "bonus" } else { "standard" };
+        let bought = hammer_reserve_and_buy(ctx, 20, 20).await?;
+        assert_eq!(
+            bought, 300,
+            "The {implementation} implementation must sell exactly the configured tickets."
+        );
+        assert_eq!(ctx.api.get_sold_tickets().await?.result?, 300);
+    }
+
+    dual.finish().await;
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_any_spreads_requests() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_balancer_threads(4)
+        .build()
+        .await?;
+
+    // A single API shared by all tasks
+    let api = Arc::new(ctx.api.any());
+    let before = api.requests_per_channel();
+    let tasks: Vec<_> = (0..100)
+        .map(|_| {
+            let api = api.clone();
+            tokio::spawn(async move {
+                for _ in 0..100 {
+                    api.ping().await?;
+                }
+                eyre::Ok(())
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await??;
+    }
+
+    let sent: Vec<u64> = api
+        .requests_per_channel()
+        .iter()
+        .zip(before)
+        .map(|(after, before)| after - before)
+        .collect();
+    assert_eq!(sent.iter().sum::<u64>(), 10_000);
+    assert!(
+        sent.iter().all(|&count| count > 0),
+        "The requests must be spread over all balancer threads, got {sent:?}."
+    );
+
+    // The balancer threads only stop once all clones are dropped
+    drop(api);
+    ctx.finish().await;
+    Ok(())
+}
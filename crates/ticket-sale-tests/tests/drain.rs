@@ -0,0 +1,113 @@
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use ticket_sale_tests::{RequestError, TestCtxBuilder};
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_drain_keeps_serving_reservations() -> Result<()> {
+    // Bonus servers must not hand the refused reservation to another server
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 2).await?;
+    let drained = *servers.iter().next().unwrap();
+
+    // Reserve a ticket on the server before draining it
+    let mut session = ctx.api.create_user_session(Some(drained));
+    let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+
+    assert_eq!(ctx.api.drain_server(drained).await?.result?, 1);
+    assert!(
+        !ctx.api.get_servers().await?.result?.contains(&drained),
+        "A draining server must not be listed as non-terminating."
+    );
+
+    // New reservations are redirected to another server
+    let mut other = ctx.api.create_user_session(Some(drained));
+    other.reserve_ticket().await?.result?.reserved()?;
+    assert_ne!(
+        other.current_server(),
+        Some(drained),
+        "A draining server must refuse new reservations."
+    );
+
+    // The existing reservation can still be bought
+    assert_eq!(session.buy_ticket(ticket_id).await?.result?, ticket_id);
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 1);
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_drain_respects_bounds() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_server_bounds(1, 10)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 1).await?;
+    let server = *servers.iter().next().unwrap();
+
+    assert!(
+        ctx.api.drain_server(server).await?.result.is_err(),
+        "Draining must not go below the minimal number of servers."
+    );
+    assert_eq!(ctx.api.get_num_servers().await?.result?, 1);
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_drain_terminating_versus_removed_server() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 3).await?;
+    let drained = *servers.iter().next().unwrap();
+
+    // Keep the server from terminating with a reservation
+    let mut session = ctx.api.create_user_session(Some(drained));
+    let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+    assert_eq!(ctx.api.drain_server(drained).await?.result?, 2);
+
+    // The terminating server still exists but cannot be drained again
+    let err = ctx.api.drain_server(drained).await?.result.err();
+    assert_eq!(
+        err.map(|err| err.kind()),
+        Some(RequestError::Conflict),
+        "Draining a terminating server must be a conflict."
+    );
+
+    // Once its reservation is aborted, the server terminates and is gone
+    session.abort_purchase(ticket_id).await?.result?;
+    let start = Instant::now();
+    loop {
+        let err = ctx.api.drain_server(drained).await?.result.err();
+        match err.map(|err| err.kind()) {
+            Some(RequestError::ServerGone) => break,
+            Some(RequestError::Conflict) => {}
+            other => panic!("Draining a removed server must fail, got {other:?}."),
+        }
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "The drained server did not terminate."
+        );
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let err = ctx.api.activate_server(drained).await?.result.err();
+    assert_eq!(err.map(|err| err.kind()), Some(RequestError::ServerGone));
+
+    ctx.finish().await;
+    Ok(())
+}
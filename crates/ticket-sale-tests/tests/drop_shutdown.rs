@@ -0,0 +1,25 @@
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+fn test_dropped_balancer_shuts_down() -> Result<()> {
+    let mut config = TestCtxBuilder::from_env()?.config()?;
+    // Every server holds a clone of the sender until it shuts down
+    let (expiry_sender, expiries) = mpsc::channel();
+    config.expiry_sender = Some(expiry_sender);
+    let balancer = ticket_sale_rocket::launch(&config);
+    drop(config);
+
+    // Forget to shut the balancer down
+    drop(balancer);
+    assert_eq!(
+        expiries.recv_timeout(Duration::from_secs(10)),
+        Err(RecvTimeoutError::Disconnected),
+        "Dropping the balancer must shut the servers down."
+    );
+    Ok(())
+}
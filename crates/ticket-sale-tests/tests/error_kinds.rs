@@ -0,0 +1,42 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestError, RequestOptions, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_error_kinds() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    // Without a reservation, there is nothing to buy
+    let mut options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.buy_ticket(0, &options).await?;
+    options.server_id = response.server_id;
+    let kind = response.result.err().map(|err| err.kind());
+    assert_eq!(kind, Some(RequestError::Conflict));
+
+    // A customer cannot hold two reservations
+    ctx.api.reserve_ticket(&options).await?.result?;
+    let response = ctx.api.reserve_ticket(&options).await?;
+    let kind = response.result.err().map(|err| err.kind());
+    assert_eq!(kind, Some(RequestError::Conflict));
+
+    // The client should retry with another server if its server no longer exists
+    options.server_id = Some(Uuid::new_v4());
+    let response = ctx.api.buy_ticket(0, &options).await?;
+    let err = response.result.expect_err("The server does not exist.");
+    assert_eq!(err.kind(), RequestError::ServerGone);
+    assert!(err.kind().is_retryable());
+    assert!(err.redirect().is_some());
+    assert!(err.to_string().starts_with("Error 410"));
+
+    ctx.finish().await;
+    Ok(())
+}
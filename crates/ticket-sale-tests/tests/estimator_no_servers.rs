@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_estimator_without_servers() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_estimator_roundtrip_time(1)
+        .with_server_bounds(0, 1000)
+        .build()
+        .await?;
+
+    // Scale up and then down to zero servers, which terminate right away as they
+    // have no reservations
+    assert_eq!(ctx.api.post_num_servers(5).await?.result?, 5);
+    assert_eq!(ctx.api.post_num_servers(0).await?.result?, 0);
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    // The estimator must keep working, i.e., its passes take about the roundtrip
+    // time without any servers
+    let roundtrip = ctx.measure_estimator_roundtrip(2).await?;
+    assert!(
+        roundtrip > Duration::from_millis(600),
+        "Without servers, the estimator must wait for the roundtrip time, took {roundtrip:?}."
+    );
+
+    // Shutting down must not get stuck on the estimator
+    ctx.finish().await;
+    Ok(())
+}
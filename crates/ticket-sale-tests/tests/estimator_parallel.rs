@@ -0,0 +1,56 @@
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Measure the time after launch until every server reports all tickets as
+/// available, i.e., until the estimator told each of them about the database
+async fn time_until_all_estimated(parallel: bool) -> Result<Duration> {
+    let mut builder = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_estimator_roundtrip_time(4)
+        .with_reassign_rate(0);
+    if parallel {
+        builder = builder.with_parallel_estimator();
+    }
+    let start = Instant::now();
+    let ctx = builder.build().await?;
+
+    for server in ctx.api.get_servers().await?.result? {
+        let options = RequestOptions {
+            server_id: Some(server),
+            customer_id: None,
+            ..Default::default()
+        };
+        while ctx.api.get_available_tickets(&options).await?.result? < 1_000 {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+    let elapsed = start.elapsed();
+
+    ctx.finish().await;
+    Ok(elapsed)
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_parallel_estimator_is_fresher() -> Result<()> {
+    // One after another, the second server is contacted half a roundtrip later
+    let sequential = time_until_all_estimated(false).await?;
+    // At once, both servers are contacted right away
+    let parallel = time_until_all_estimated(true).await?;
+
+    assert!(
+        parallel < Duration::from_secs(1),
+        "The parallel estimator must contact all servers at once, took {parallel:?}."
+    );
+    assert!(
+        parallel < sequential,
+        "The parallel estimator ({parallel:?}) must be fresher than the sequential one \
+         ({sequential:?})."
+    );
+
+    Ok(())
+}
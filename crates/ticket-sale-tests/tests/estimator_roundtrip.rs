@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+/// Check that a pass of the estimator over all servers takes about the configured
+/// roundtrip time, independently of the number of servers
+async fn check_roundtrip(num_servers: usize, parallel: bool) -> Result<()> {
+    let mut builder = TestCtxBuilder::from_env()?.with_estimator_roundtrip_time(1);
+    if parallel {
+        builder = builder.with_parallel_estimator();
+    }
+    let ctx = builder.build().await?;
+
+    let response = ctx.api.post_num_servers(num_servers).await?;
+    assert_eq!(response.result?, num_servers);
+
+    let roundtrip = ctx.measure_estimator_roundtrip(2).await?;
+    let expected = Duration::from_secs(1);
+    assert!(
+        roundtrip > expected.mul_f64(0.6) && roundtrip < expected.mul_f64(1.4),
+        "With {num_servers} servers, the estimator roundtrip took {roundtrip:?} instead of {expected:?}."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_estimator_roundtrip_two_servers() -> Result<()> {
+    check_roundtrip(2, false).await
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_estimator_roundtrip_five_servers() -> Result<()> {
+    check_roundtrip(5, false).await
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_parallel_estimator_roundtrip() -> Result<()> {
+    check_roundtrip(5, true).await
+}
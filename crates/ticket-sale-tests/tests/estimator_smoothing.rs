@@ -0,0 +1,58 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_smoothed_estimate_converges_monotonically() -> Result<()> {
+    // Bonus servers must not hand the requests to the other server
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_estimator_roundtrip_time(1)
+        .with_estimator_smoothing(0.5)
+        .with_server_bounds(2, 2)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = ctx.api.get_servers().await?.result?;
+    let (first, second) = (servers[0], servers[1]);
+
+    // The first server allocates sqrt(1000) = 31 tickets and sells one of them
+    let mut session = ctx.api.create_user_session(Some(first));
+    let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+    session.buy_ticket(ticket_id).await?.result?;
+
+    // The estimate of the second server approaches the 999 remaining tickets pass by
+    // pass instead of jumping there (the pass in progress may have contacted it before
+    // the sale, so it is skipped)
+    let options = RequestOptions {
+        server_id: Some(second),
+        customer_id: None,
+        ..Default::default()
+    };
+    let mut estimates = Vec::new();
+    let mut passes = ctx.api.debug_estimator_passes().await?.result? + 1;
+    while estimates.len() < 6 {
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        let current = ctx.api.debug_estimator_passes().await?.result?;
+        if current > passes {
+            passes = current;
+            estimates.push(ctx.api.get_available_tickets(&options).await?.result?);
+        }
+    }
+
+    assert!(
+        estimates.windows(2).all(|pair| pair[0] <= pair[1]),
+        "The smoothed estimate must not oscillate: {estimates:?}"
+    );
+    assert!(
+        estimates[0] < 995,
+        "The estimate must not jump to the new ticket count at once: {estimates:?}"
+    );
+    assert!(
+        estimates[5] >= 997,
+        "The estimate must converge to the remaining tickets: {estimates:?}"
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
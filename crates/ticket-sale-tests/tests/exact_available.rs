@@ -0,0 +1,35 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_exact_available_tickets_below_server_threshold() -> Result<()> {
+    // The estimator does not complete a roundtrip during the test
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_server_bounds(1, 1)
+        .with_estimator_roundtrip_time(60)
+        .with_exact_below_servers(2)
+        .build()
+        .await?;
+
+    let mut session = ctx.api.create_user_session(None);
+    assert_eq!(session.get_available_tickets().await?.result?, 100);
+    assert!(
+        session.current_server().is_some(),
+        "The exact count must still assign a server."
+    );
+
+    for reserved in 1..=5 {
+        let mut other = ctx.api.create_user_session(session.current_server());
+        other.reserve_ticket().await?.result?.reserved()?;
+        assert_eq!(
+            session.get_available_tickets().await?.result?,
+            100 - reserved,
+            "With a single server, the available tickets must be exact."
+        );
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
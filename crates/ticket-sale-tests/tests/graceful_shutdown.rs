@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use ticket_sale_tests::{RequestError, RequestOptions, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_buy_during_graceful_shutdown() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let mut options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.reserve_ticket(&options).await?;
+    options.server_id = response.server_id;
+    let ticket_id = response.result?.reserved()?;
+
+    let start = Instant::now();
+    let buy = async {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        ctx.api.buy_ticket(ticket_id, &options).await
+    };
+    let (shutdown, bought) = tokio::join!(ctx.graceful_shutdown(5), buy);
+    shutdown?;
+    assert_eq!(
+        bought?.result?, ticket_id,
+        "A reserved ticket must still be bought while draining."
+    );
+    assert!(
+        start.elapsed() < Duration::from_secs(4),
+        "The shutdown must not wait once all reservations are cleared."
+    );
+
+    // Afterwards, the system only turns requests away
+    let response = ctx.api.get_sold_tickets().await?;
+    assert_eq!(
+        response.result.err().map(|err| err.kind()),
+        Some(RequestError::Unavailable),
+        "Requests after a graceful shutdown must be refused."
+    );
+
+    if let Some(report) = ctx.finish_with_report().await {
+        assert_eq!(report.sold, 1);
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_graceful_shutdown_gives_up_after_drain_time() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    ctx.api.reserve_ticket(&options).await?.result?.reserved()?;
+
+    // The reservation is neither bought nor aborted, so draining takes the full time
+    let start = Instant::now();
+    ctx.graceful_shutdown(1).await?;
+    assert!(start.elapsed() >= Duration::from_secs(1));
+
+    // The reserved ticket is returned to the database
+    ctx.finish().await;
+    Ok(())
+}
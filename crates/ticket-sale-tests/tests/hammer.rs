@@ -0,0 +1,28 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+use util::hammer_reserve_and_buy;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_hammer_reserve_and_buy() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(2_000)
+        .build()
+        .await?;
+
+    let bought = hammer_reserve_and_buy(&ctx, 10, 50).await?;
+    assert_eq!(bought, 500, "Every customer must buy all of their tickets.");
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 500);
+
+    // Once sold out, customers stop buying
+    let bought = hammer_reserve_and_buy(&ctx, 10, 1_000).await?;
+    assert!(
+        bought <= 1_500,
+        "No more tickets than available must be sold."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
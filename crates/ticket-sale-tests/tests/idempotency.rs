@@ -0,0 +1,72 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestError, RequestOptions, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_repeated_buy_succeeds() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_idempotency_window(30)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let mut options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.reserve_ticket(&options).await?;
+    options.server_id = response.server_id;
+    let ticket_id = response.result?.reserved()?;
+
+    assert_eq!(
+        ctx.api.buy_ticket(ticket_id, &options).await?.result?,
+        ticket_id
+    );
+    assert_eq!(
+        ctx.api.buy_ticket(ticket_id, &options).await?.result?,
+        ticket_id,
+        "A retried buy must report the original success."
+    );
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 1);
+
+    // Other customers cannot claim the ticket
+    let other = RequestOptions {
+        customer_id: Some(Uuid::new_v4()),
+        ..options
+    };
+    let err = ctx.api.buy_ticket(ticket_id, &other).await?.result.err();
+    assert_eq!(err.map(|err| err.kind()), Some(RequestError::Conflict));
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_repeated_buy_fails_by_default() -> Result<()> {
+    // Remembering buys is opt-in, so a repeated buy fails as before
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let mut options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.reserve_ticket(&options).await?;
+    options.server_id = response.server_id;
+    let ticket_id = response.result?.reserved()?;
+
+    ctx.api.buy_ticket(ticket_id, &options).await?.result?;
+    let err = ctx.api.buy_ticket(ticket_id, &options).await?.result.err();
+    assert_eq!(err.map(|err| err.kind()), Some(RequestError::Conflict));
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use eyre::Result;
+use parking_lot::Mutex;
+use ticket_sale_rocket::Database;
+use ticket_sale_tests::{Reservation, TestCtxBuilder};
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_injected_database_is_shared() -> Result<()> {
+    let database = Arc::new(Mutex::new(Database::new(5, false)));
+    // A single server, so sold out means all tickets are sold
+    let ctx = TestCtxBuilder::from_env()?
+        .with_server_bounds(1, 1)
+        .build_with_database(database.clone())
+        .await?;
+
+    // The system sells the tickets of the given database
+    let mut bought = Vec::new();
+    loop {
+        let mut session = ctx.api.create_user_session(None);
+        match session.reserve_ticket().await?.result? {
+            Reservation::Reserved(ticket_id, _) => {
+                session.buy_ticket(ticket_id).await?.result?;
+                bought.push(ticket_id);
+            }
+            Reservation::SoldOut => break,
+        }
+    }
+    bought.sort_unstable();
+    assert_eq!(bought, vec![0, 1, 2, 3, 4]);
+
+    // The database given to the system holds the result of the sale
+    {
+        let database = database.lock();
+        assert_eq!(database.get_num_available(), 0);
+        assert_eq!(database.get_num_sold(), 5);
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use std::sync::Mutex;
+
+use eyre::Result;
+use ticket_sale_tests::{LaunchInfo, TestCtxBuilder};
+
+/// Summaries reported by the launch callback
+static LAUNCHES: Mutex<Vec<LaunchInfo>> = Mutex::new(Vec::new());
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_launch_info() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(500)
+        .with_reservation_timeout(7)
+        .with_estimator_roundtrip_time(3)
+        .with_on_launch(|info| LAUNCHES.lock().unwrap().push(info.clone()))
+        .build()
+        .await?;
+
+    let launches = LAUNCHES.lock().unwrap().clone();
+    assert_eq!(launches.len(), 1, "The launch must be reported once.");
+    let info = &launches[0];
+    assert_eq!(info.tickets, 500);
+    assert_eq!(info.timeout, 7);
+    assert_eq!(info.estimator_roundtrip_time, 3);
+    assert_eq!(info.bonus, ctx.bonus);
+
+    // The reported servers are the ones the system was launched with
+    let mut servers = ctx.api.get_servers().await?.result?;
+    let mut reported = info.servers.clone();
+    servers.sort();
+    reported.sort();
+    assert_eq!(reported, servers);
+
+    ctx.finish().await;
+    Ok(())
+}
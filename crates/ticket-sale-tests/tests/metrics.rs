@@ -0,0 +1,53 @@
+use eyre::{eyre, Result};
+use ticket_sale_tests::TestCtxBuilder;
+
+/// Get the number of recorded requests of the given kind from a metrics report
+fn count(report: &str, kind: &str) -> Result<u64> {
+    let Some(line) = report
+        .lines()
+        .find(|line| line.split(' ').next() == Some(kind))
+    else {
+        return Ok(0);
+    };
+    line.split(' ')
+        .find_map(|field| field.strip_prefix("count="))
+        .ok_or_else(|| eyre!("No count in line {line:?}"))?
+        .parse()
+        .map_err(Into::into)
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_metrics_count_requests() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .build()
+        .await?;
+
+    // Start from empty histograms
+    ctx.api.debug_metrics(true).await?.result?;
+
+    for _ in 0..50 {
+        let mut session = ctx.api.create_user_session(None);
+        session.reserve_ticket().await?.result?;
+    }
+
+    let report = ctx.api.debug_metrics(false).await?.result?;
+    assert_eq!(
+        count(&report, "ReserveTicket")?,
+        50,
+        "Every reservation must be recorded.\n{report}"
+    );
+    assert!(
+        report.contains("p99<="),
+        "The report must contain latencies."
+    );
+
+    // Reading with reset clears the histograms
+    ctx.api.debug_metrics(true).await?.result?;
+    let report = ctx.api.debug_metrics(false).await?.result?;
+    assert_eq!(count(&report, "ReserveTicket")?, 0);
+
+    ctx.finish().await;
+    Ok(())
+}
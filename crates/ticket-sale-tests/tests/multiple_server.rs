@@ -17,7 +17,7 @@ async fn test_buy_100_tickets_on_scaled_servers() -> Result<()> {
             Reservation::SoldOut => {
                 panic!("It must be possible to reserve a ticket.")
             }
-            Reservation::Reserved(ticket_id) => {
+            Reservation::Reserved(ticket_id, _) => {
                 assert!(
                     session.buy_ticket(ticket_id).await?.result.is_ok(),
                     "It must be possible to buy the ticket that we just reserved.",
@@ -33,7 +33,7 @@ async fn test_buy_100_tickets_on_scaled_servers() -> Result<()> {
             Reservation::SoldOut => {
                 panic!("It must be possible to reserve a ticket.")
             }
-            Reservation::Reserved(ticket_id) => {
+            Reservation::Reserved(ticket_id, _) => {
                 assert!(
                     session.buy_ticket(ticket_id).await?.result.is_ok(),
                     "It must be possible to buy the ticket that we just reserved.",
@@ -0,0 +1,81 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_my_reservations() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let mut options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.get_my_reservations(&options).await?;
+    options.server_id = response.server_id;
+    assert!(response.result?.is_empty(), "No tickets are reserved yet.");
+
+    let mut reserved = ctx.api.reserve_multiple(3, &options).await?.result?;
+    let mut listed = ctx.api.get_my_reservations(&options).await?.result?;
+    reserved.sort_unstable();
+    listed.sort_unstable();
+    assert_eq!(listed, reserved, "All reserved tickets must be listed.");
+
+    // Other customers do not see the reservation
+    let other = RequestOptions {
+        server_id: options.server_id,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    assert!(ctx
+        .api
+        .get_my_reservations(&other)
+        .await?
+        .result?
+        .is_empty());
+
+    ctx.api.buy_all(&options).await?.result?;
+    assert!(
+        ctx.api
+            .get_my_reservations(&options)
+            .await?
+            .result?
+            .is_empty(),
+        "Bought tickets are no longer reserved."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_my_reservations_on_unknown_server() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .build()
+        .await?;
+
+    let options = RequestOptions {
+        server_id: Some(Uuid::new_v4()),
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.get_my_reservations(&options).await?;
+    assert!(
+        response.result.is_err(),
+        "A server that does not exist cannot list reservations."
+    );
+    assert_ne!(
+        response.server_id, options.server_id,
+        "The customer must be assigned a new server."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_available_tickets_without_estimator() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_server_bounds(1, 1)
+        .without_estimator()
+        .build()
+        .await?;
+
+    let mut session = ctx.api.create_user_session(None);
+    assert_eq!(session.get_available_tickets().await?.result?, 100);
+
+    // Reserve three tickets and buy one of them
+    let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+    session.buy_ticket(ticket_id).await?.result?;
+    for _ in 0..2 {
+        let mut other = ctx.api.create_user_session(session.server_id);
+        other.reserve_ticket().await?.result?.reserved()?;
+    }
+
+    // The single server counts exactly, without waiting for an estimator roundtrip
+    assert_eq!(
+        session.get_available_tickets().await?.result?,
+        97,
+        "Without the estimator, the available tickets must be exact."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
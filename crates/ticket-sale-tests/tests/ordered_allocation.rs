@@ -0,0 +1,30 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_ordered_allocation() -> Result<()> {
+    // With 10 tickets, the first allocation takes sqrt(10) = 3 tickets, which must
+    // be the lowest ids in ascending order
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(10)
+        .with_ordered_allocation()
+        .build()
+        .await?;
+
+    let options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let tickets = ctx.api.reserve_multiple(3, &options).await?.result?;
+    assert_eq!(
+        tickets,
+        vec![0, 1, 2],
+        "With ordered allocation, the lowest ids must be allocated first."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
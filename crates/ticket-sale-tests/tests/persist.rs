@@ -0,0 +1,76 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, Reservation, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_persist_and_restore() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("ticket-sale-{}.snapshot", Uuid::new_v4()));
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .build()
+        .await?;
+
+    // Sell one ticket and keep two reservations
+    let buyer = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.reserve_ticket(&buyer).await?;
+    let buyer = RequestOptions {
+        server_id: response.server_id,
+        customer_id: buyer.customer_id,
+        ..Default::default()
+    };
+    let Reservation::Reserved(ticket_id, _) = response.result? else {
+        panic!("The reservation must succeed.");
+    };
+    ctx.api.buy_ticket(ticket_id, &buyer).await?.result?;
+
+    let mut reservations = Vec::new();
+    for _ in 0..2 {
+        let options = RequestOptions {
+            server_id: None,
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        let response = ctx.api.reserve_ticket(&options).await?;
+        let Reservation::Reserved(ticket_id, _) = response.result? else {
+            panic!("The reservation must succeed.");
+        };
+        let options = RequestOptions {
+            server_id: response.server_id,
+            customer_id: options.customer_id,
+            ..Default::default()
+        };
+        reservations.push((options, ticket_id));
+    }
+
+    ctx.persist(&path).await?;
+    ctx.finish().await;
+
+    // Relaunch and check that the sale continues where it stopped
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .build_from_snapshot(&path)
+        .await?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 1);
+
+    // The reservations are still held by their servers
+    let (options, ticket_id) = &reservations[0];
+    let response = ctx.api.reserve_ticket(options).await?;
+    assert!(
+        response.result.is_err(),
+        "A restored reservation must prevent another one."
+    );
+    ctx.api.buy_ticket(*ticket_id, options).await?.result?;
+    let (options, ticket_id) = &reservations[1];
+    ctx.api.abort_purchase(*ticket_id, options).await?.result?;
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 2);
+
+    // No ticket got lost
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use eyre::Result;
+use ticket_sale_tests::{Api, Reservation, TestCtxBuilder};
+use util::scale_to;
+
+mod util;
+
+/// Buy tickets until they are sold out
+async fn buy_until_sold_out(api: Api) -> Result<u64> {
+    let mut bought = 0;
+    loop {
+        let mut session = api.create_user_session(None);
+        match session.reserve_ticket().await?.result {
+            Ok(Reservation::SoldOut) => return Ok(bought),
+            Ok(Reservation::Reserved(ticket_id, _)) => {
+                session.buy_ticket(ticket_id).await?.result?;
+                bought += 1;
+            }
+            // The server may be terminating, just try again
+            Err(_) => {}
+        }
+    }
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_scale_between_sale_phases() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(2_000)
+        .build()
+        .await?;
+
+    let buyers: Vec<_> = (0..4)
+        .map(|_| tokio::spawn(buy_until_sold_out(ctx.api.clone())))
+        .collect();
+
+    // Scale up once the sale is underway and down again near the end
+    ctx.api.wait_for_sold(500, Duration::from_secs(10)).await?;
+    scale_to(&ctx, 5).await?;
+    ctx.api
+        .wait_for_sold(1_500, Duration::from_secs(10))
+        .await?;
+    scale_to(&ctx, 2).await?;
+
+    let mut bought = 0;
+    for buyer in buyers {
+        bought += buyer.await??;
+    }
+    assert_eq!(bought, 2_000, "All tickets must be bought.");
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 2_000);
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_ping() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?.build().await?;
+
+    // The balancer answers even without any server
+    ctx.api.post_num_servers(0).await?;
+    for _ in 0..10 {
+        ctx.api.ping().await?;
+    }
+
+    ctx.api.post_num_servers(2).await?;
+    for _ in 0..10 {
+        ctx.api.ping().await?;
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestError, RequestOptions, Reservation, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_rate_limit_per_customer() -> Result<()> {
+    // Only the bonus balancer limits the requests
+    let mut builder = TestCtxBuilder::from_env()?
+        .with_reassign_rate(0)
+        .with_max_requests_per_customer(3);
+    builder.bonus = true;
+    let ctx = builder.build().await?;
+
+    // A customer without a reservation is limited
+    let options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    for _ in 0..3 {
+        ctx.api.get_available_tickets(&options).await?.result?;
+    }
+    let response = ctx.api.get_available_tickets(&options).await?;
+    assert!(
+        response.result.is_err(),
+        "The fourth request of a customer must be rejected."
+    );
+
+    // Starting and ending a reservation resets the count
+    let options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    ctx.api.get_available_tickets(&options).await?.result?;
+    ctx.api.get_available_tickets(&options).await?.result?;
+    let response = ctx.api.reserve_ticket(&options).await?;
+    let options = RequestOptions {
+        server_id: response.server_id,
+        customer_id: options.customer_id,
+        ..Default::default()
+    };
+    let Reservation::Reserved(ticket_id, _) = response.result? else {
+        panic!("The reservation must succeed.");
+    };
+    ctx.api.buy_ticket(ticket_id, &options).await?.result?;
+    for _ in 0..3 {
+        ctx.api.get_available_tickets(&options).await?.result?;
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_rate_limit_skips_active_sessions() -> Result<()> {
+    // Only the bonus balancer limits the requests
+    let mut builder = TestCtxBuilder::from_env()?
+        .with_reassign_rate(0)
+        .with_max_requests_per_customer(3);
+    builder.bonus = true;
+    let ctx = builder.build().await?;
+
+    let options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.reserve_ticket(&options).await?;
+    let options = RequestOptions {
+        server_id: response.server_id,
+        customer_id: options.customer_id,
+        ..Default::default()
+    };
+    let Reservation::Reserved(ticket_id, _) = response.result? else {
+        panic!("The reservation must succeed.");
+    };
+
+    // A customer polling during the purchase is not limited
+    for _ in 0..10 {
+        ctx.api.get_available_tickets(&options).await?.result?;
+    }
+    ctx.api.buy_ticket(ticket_id, &options).await?.result?;
+
+    // Once the session ended, the customer is limited again
+    for _ in 0..3 {
+        ctx.api.get_available_tickets(&options).await?.result?;
+    }
+    let response = ctx.api.get_available_tickets(&options).await?;
+    assert_eq!(
+        response.result.err().map(|err| err.kind()),
+        Some(RequestError::TooManyRequests),
+        "Requests without an active session must be limited."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
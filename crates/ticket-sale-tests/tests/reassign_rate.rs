@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use util::scale_to;
+use uuid::Uuid;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_no_reassignment_with_zero_rate() -> Result<()> {
+    // Only the bonus servers reassign requests
+    let mut builder = TestCtxBuilder::from_env()?.with_reassign_rate(0);
+    builder.bonus = true;
+    let ctx = builder.build().await?;
+
+    let servers = ctx.api.get_servers().await?.result?;
+    let server_id = servers[0];
+
+    // With the default rate, about one in 66 requests would be reassigned
+    for _ in 0..200 {
+        let options = RequestOptions {
+            server_id: Some(server_id),
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        let response = ctx.api.get_available_tickets(&options).await?;
+        response.result?;
+        assert_eq!(
+            response.server_id,
+            Some(server_id),
+            "With a reassign rate of 0, requests must stay on their server."
+        );
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_customer_keeps_server_until_it_terminates() -> Result<()> {
+    // Neither the balancer nor the servers hand the customer to another server
+    let mut builder = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .with_session_affinity(1.0);
+    builder.bonus = true;
+    let ctx = builder.build().await?;
+    scale_to(&ctx, 2).await?;
+    // Scaling down terminates the server with the highest index, i.e., the last one
+    let servers = ctx.api.get_servers().await?.result?;
+    let (kept, terminated) = (servers[0], servers[1]);
+
+    // Without an active session, the customer still stays on its server
+    let mut session = ctx.api.create_user_session(Some(terminated));
+    for _ in 0..100 {
+        session.get_available_tickets().await?.result?;
+        assert_eq!(session.current_server(), Some(terminated));
+    }
+
+    // Only once the server terminates, the customer moves to the other one
+    scale_to(&ctx, 1).await?;
+    for _ in 0..100 {
+        session.get_available_tickets().await?.result?;
+        assert_eq!(session.current_server(), Some(kept));
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_customers_switched_without_affinity() -> Result<()> {
+    // Only the bonus balancer switches customers
+    let mut builder = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .with_session_affinity(0.0);
+    builder.bonus = true;
+    let ctx = builder.build().await?;
+    let servers = scale_to(&ctx, 2).await?;
+    let first = *servers.iter().next().unwrap();
+
+    // Customers without an active session are handed to random servers, so some of
+    // them end up on the other one
+    let mut moved = false;
+    for _ in 0..20 {
+        let options = RequestOptions {
+            server_id: Some(first),
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        let response = ctx.api.get_available_tickets(&options).await?;
+        response.result?;
+        moved |= response.server_id != Some(first);
+    }
+    assert!(moved, "Customers without a session must be switched.");
+
+    // A customer with an active session stays on its server
+    let mut session = ctx.api.create_user_session(Some(first));
+    let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+    let server = session.current_server();
+    for _ in 0..50 {
+        session.get_available_tickets().await?.result?;
+        assert_eq!(
+            session.current_server(),
+            server,
+            "A customer with an active session must stay on its server."
+        );
+    }
+    session.buy_ticket(ticket_id).await?.result?;
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_busy_customer_keeps_server() -> Result<()> {
+    let mut builder = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(9_999)
+        .with_keep_server_after(5);
+    builder.bonus = true;
+    let ctx = builder.build().await?;
+    let servers = scale_to(&ctx, 2).await?;
+    let first = *servers.iter().next().unwrap();
+
+    // New customers are almost always handed to a random server, so some of them
+    // end up on the other one
+    let mut moved = false;
+    for _ in 0..20 {
+        let options = RequestOptions {
+            server_id: Some(first),
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        let response = ctx.api.get_available_tickets(&options).await?;
+        response.result?;
+        moved |= response.server_id != Some(first);
+    }
+    assert!(moved, "New customers must be handed to random servers.");
+
+    // After more than 5 requests, the customer stays on its server
+    let options = RequestOptions {
+        server_id: Some(first),
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    for i in 0..30 {
+        let response = ctx.api.get_available_tickets(&options).await?;
+        response.result?;
+        if i >= 5 {
+            assert_eq!(
+                response.server_id,
+                Some(first),
+                "A busy customer must stay on its server."
+            );
+        }
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_idle_customers_forgotten() -> Result<()> {
+    let mut builder = TestCtxBuilder::from_env()?
+        .with_reassign_rate(0)
+        .with_request_window(1);
+    builder.bonus = true;
+    let ctx = builder.build().await?;
+    let server_id = ctx.api.get_servers().await?.result?[0];
+
+    for _ in 0..50 {
+        let options = RequestOptions {
+            server_id: Some(server_id),
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        ctx.api.get_available_tickets(&options).await?.result?;
+    }
+    let stats = ctx.api.debug_server_stats().await?.result?;
+    assert_eq!(stats[0].customers, 50);
+
+    // Once the window passed, the server no longer counts the customers
+    tokio::time::sleep(Duration::from_millis(1_500)).await;
+    let stats = ctx.api.debug_server_stats().await?.result?;
+    assert_eq!(
+        stats[0].customers, 0,
+        "Customers without recent requests must be forgotten."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
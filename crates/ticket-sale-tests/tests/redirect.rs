@@ -0,0 +1,96 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestError, RequestOptions, TestCtxBuilder};
+use util::scale_to;
+use uuid::Uuid;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_draining_server_redirects_reservations() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 3).await?;
+    let drained = *servers.iter().next().unwrap();
+
+    // Keep the server from terminating with a reservation
+    let mut holder = ctx.api.create_user_session(Some(drained));
+    holder.reserve_ticket().await?.result?.reserved()?;
+    ctx.api.drain_server(drained).await?.result?;
+
+    // The refusal names the server to retry with
+    let options = RequestOptions {
+        server_id: Some(drained),
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.reserve_ticket(&options).await?;
+    let err = response
+        .result
+        .err()
+        .expect("A draining server must refuse.");
+    assert_eq!(err.kind(), RequestError::ServerGone);
+    let target = err.redirect().expect("The refusal must be a redirect.");
+    assert_ne!(target, drained);
+    assert_eq!(response.server_id, Some(target));
+    assert!(err.to_string().starts_with("Error 410"));
+
+    // A session follows the redirect without surfacing it
+    let mut session = ctx.api.create_user_session(Some(drained));
+    session.reserve_ticket().await?.result?.reserved()?;
+    assert_ne!(session.current_server(), Some(drained));
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_unknown_server_redirects_reservations() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = ctx.api.get_servers().await?.result?;
+
+    let gone = Uuid::new_v4();
+    let mut session = ctx.api.create_user_session(Some(gone));
+    session.reserve_ticket().await?.result?.reserved()?;
+    let current = session.current_server().unwrap();
+    assert!(
+        servers.contains(&current),
+        "A session must be redirected to an existing server."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_session_completes_purchase_after_redirect() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    scale_to(&ctx, 3).await?;
+
+    // Pin the session to a server, then deactivate that server mid-session
+    let mut session = ctx.api.create_user_session(None);
+    session.get_available_tickets().await?.result?;
+    let pinned = session.current_server().unwrap();
+    ctx.api.drain_server(pinned).await?.result?;
+
+    let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+    assert_ne!(session.current_server(), Some(pinned));
+    assert_eq!(session.buy_ticket(ticket_id).await?.result?, ticket_id);
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 1);
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder, TimeoutError};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_request_timeout() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_debug_sleep()
+        .build()
+        .await?;
+
+    let server = ctx.api.get_servers().await?.result?[0];
+    let options = RequestOptions {
+        server_id: Some(server),
+        customer_id: Some(Uuid::new_v4()),
+        timeout: Some(Duration::from_millis(200)),
+        max_retries: 3,
+    };
+
+    // A responsive server answers within the timeout
+    ctx.api.get_available_tickets(&options).await?.result?;
+
+    // A sleeping server makes the request fail fast instead of hanging
+    ctx.api.debug_sleep(2_000, &options).await?.result?;
+    let start = Instant::now();
+    let response = ctx.api.get_available_tickets(&options).await;
+    assert!(
+        matches!(response, Err(err) if err.downcast_ref::<TimeoutError>().is_some()),
+        "A request exceeding its timeout must fail with a timeout error."
+    );
+    assert!(
+        start.elapsed() < Duration::from_millis(1_000),
+        "A request must not wait much longer than its timeout."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::time::Duration;
+
+use eyre::Result;
+use ticket_sale_tests::{Reservation, TestCtxBuilder};
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_expired_ticket_reserved_again() -> Result<()> {
+    // A single server, so the freed ticket goes back to the server both
+    // customers talk to
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(10)
+        .with_reservation_timeout(1)
+        .with_server_bounds(1, 1)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let mut first = ctx.api.create_user_session(None);
+    let ticket_id = first.reserve_then_expire().await?;
+
+    // Another customer now gets the freed ticket
+    let mut second = ctx.api.create_user_session(first.server_id);
+    let response = second.reserve_ticket().await?;
+    assert!(
+        matches!(response.result?, Reservation::Reserved(id, _) if id == ticket_id),
+        "The ticket of an expired reservation must be reserved again."
+    );
+    second.buy_ticket(ticket_id).await?.result?;
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_expiry_notification() -> Result<()> {
+    // The estimator contacts the servers every second, so they notice the
+    // expiry without further requests
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(10)
+        .with_reservation_timeout(1)
+        .with_estimator_roundtrip_time(1)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let mut session = ctx.api.create_user_session(None);
+    session.reserve_ticket().await?.result?.reserved()?;
+    assert_eq!(
+        ctx.next_expiry(Duration::from_secs(5)).await?,
+        session.customer_id,
+        "The expired reservation must be reported with its customer."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_reservation_reports_ttl() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(10)
+        .with_reservation_timeout(7)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let mut session = ctx.api.create_user_session(None);
+    let response = session.reserve_ticket().await?;
+    assert!(
+        matches!(response.result?, Reservation::Reserved(_, Some(7))),
+        "A new reservation must expire after the full timeout."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_reserve_multiple() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .build()
+        .await?;
+
+    let mut options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.reserve_multiple(5, &options).await?;
+    options.server_id = response.server_id;
+    let tickets = response.result?;
+    assert_eq!(tickets.len(), 5, "Five tickets must be reserved.");
+    assert_eq!(
+        tickets.iter().collect::<HashSet<_>>().len(),
+        5,
+        "The reserved tickets must be distinct."
+    );
+
+    // The customer must not reserve anything else while holding the reservation
+    assert!(
+        ctx.api.reserve_multiple(5, &options).await?.result.is_err(),
+        "A second multiple reservation must be rejected."
+    );
+    assert!(
+        ctx.api.reserve_ticket(&options).await?.result.is_err(),
+        "A single reservation must be rejected while holding a multiple one."
+    );
+
+    // Each of the tickets can be bought or aborted individually
+    ctx.api.buy_ticket(tickets[0], &options).await?.result?;
+    ctx.api.abort_purchase(tickets[1], &options).await?.result?;
+    assert!(
+        ctx.api
+            .buy_ticket(tickets[1], &options)
+            .await?
+            .result
+            .is_err(),
+        "An aborted ticket must not be bought."
+    );
+    for &ticket in &tickets[2..] {
+        ctx.api.buy_ticket(ticket, &options).await?.result?;
+    }
+
+    // With all tickets handled, the customer may reserve again
+    ctx.api.reserve_ticket(&options).await?.result?.reserved()?;
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_reserve_multiple_partial() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?.with_tickets(3).build().await?;
+
+    // Only the remaining tickets are reserved
+    let options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let tickets = ctx.api.reserve_multiple(10, &options).await?.result?;
+    assert_eq!(
+        tickets.len(),
+        3,
+        "All three remaining tickets must be reserved."
+    );
+
+    // Afterwards, nothing is left
+    let options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    assert!(
+        ctx.api
+            .reserve_multiple(10, &options)
+            .await?
+            .result?
+            .is_empty(),
+        "Without tickets left, no tickets must be reserved."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, Reservation, SessionState, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_reserve_specific_ticket() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .build()
+        .await?;
+
+    let servers = ctx.api.get_servers().await?.result?;
+    let options = || {
+        RequestOptions {
+            server_id: Some(servers[0]),
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        }
+    };
+
+    let first = options();
+    assert!(
+        matches!(
+            ctx.api.reserve_specific_ticket(42, &first).await?.result?,
+            Reservation::Reserved(42, _)
+        ),
+        "The requested ticket must be reserved."
+    );
+
+    // A reserved ticket cannot be reserved again
+    assert!(
+        ctx.api
+            .reserve_specific_ticket(42, &options())
+            .await?
+            .result
+            .is_err(),
+        "A reserved ticket must not be reserved again."
+    );
+
+    // Neither can a sold one
+    ctx.api.buy_ticket(42, &first).await?.result?;
+    assert!(
+        ctx.api
+            .reserve_specific_ticket(42, &options())
+            .await?
+            .result
+            .is_err(),
+        "A sold ticket must not be reserved again."
+    );
+
+    // Tickets already allocated to the server can be requested as well
+    let aborting = options();
+    let ticket_id = ctx
+        .api
+        .reserve_ticket(&aborting)
+        .await?
+        .result?
+        .reserved()?;
+    ctx.api.abort_purchase(ticket_id, &aborting).await?.result?;
+    assert!(
+        matches!(
+            ctx.api.reserve_specific_ticket(ticket_id, &options()).await?.result?,
+            Reservation::Reserved(id, _) if id == ticket_id
+        ),
+        "An aborted ticket must be reservable by id."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_session_reserve_specific_ticket() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .build()
+        .await?;
+
+    let mut session = ctx.api.create_user_session(None);
+    session.reserve_specific_ticket(17).await?.result?;
+    assert!(
+        matches!(session.state, SessionState::Reserved(17)),
+        "The session must hold the requested ticket."
+    );
+    session.buy_ticket(17).await?.result?;
+
+    // Another session on the same server cannot get the sold ticket
+    let mut other = ctx.api.create_user_session(session.server_id);
+    assert!(
+        other.reserve_specific_ticket(17).await?.result.is_err(),
+        "A sold ticket must not be reserved again."
+    );
+    assert!(matches!(other.state, SessionState::None));
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_reserved_count() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 3).await?;
+    assert_eq!(ctx.api.get_reserved_count().await?.result?, 0);
+
+    // Two reservations on each server
+    let mut sessions = Vec::new();
+    for server in servers.iter().chain(&servers) {
+        let mut session = ctx.api.create_user_session(Some(*server));
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        sessions.push((session, ticket_id));
+    }
+    assert_eq!(ctx.api.get_reserved_count().await?.result?, 6);
+
+    // Terminating servers keep their reservations
+    scale_to(&ctx, 1).await?;
+    assert_eq!(
+        ctx.api.get_reserved_count().await?.result?,
+        6,
+        "Reservations on terminating servers must be counted."
+    );
+
+    // Bought and aborted tickets are no longer reserved
+    let (session, ticket_id) = &mut sessions[1];
+    session.buy_ticket(*ticket_id).await?.result?;
+    let (session, ticket_id) = &mut sessions[2];
+    session.abort_purchase(*ticket_id).await?.result?;
+    assert_eq!(ctx.api.get_reserved_count().await?.result?, 4);
+
+    ctx.finish().await;
+    Ok(())
+}
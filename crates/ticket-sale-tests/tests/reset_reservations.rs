@@ -0,0 +1,65 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_reset_reservations() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 3).await?;
+
+    // Sell one ticket and reserve a bunch more on every server
+    let mut buyer = ctx.api.create_user_session(servers.iter().next().copied());
+    let ticket_id = buyer.reserve_ticket().await?.result?.reserved()?;
+    buyer.buy_ticket(ticket_id).await?.result?;
+    let mut sessions = Vec::new();
+    for server in servers.iter().cycle().take(30) {
+        let mut session = ctx.api.create_user_session(Some(*server));
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        sessions.push((session, ticket_id));
+    }
+    assert_eq!(ctx.api.get_reserved_count().await?.result?, 30);
+
+    assert_eq!(ctx.api.reset_reservations().await?.result?, 30);
+    assert_eq!(ctx.api.get_reserved_count().await?.result?, 0);
+    assert_eq!(
+        ctx.api.get_total_available().await?.result?,
+        999,
+        "All tickets but the sold one must be available after the reset."
+    );
+
+    // The reservations are gone, but their customers may reserve again
+    let (session, ticket_id) = &mut sessions[0];
+    assert!(session.buy_ticket(*ticket_id).await?.result.is_err());
+    session.reserve_ticket().await?.result?.reserved()?;
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 1);
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_reset_reservations_requires_admin_token() -> Result<()> {
+    let mut ctx = TestCtxBuilder::from_env()?
+        .with_admin_token("secret")
+        .build()
+        .await?;
+
+    // Resetting is an admin request
+    assert!(
+        ctx.api.reset_reservations().await?.result.is_err(),
+        "Resetting the reservations without the admin token must fail."
+    );
+    ctx.api.set_admin_token(Some("secret".to_owned()));
+    assert_eq!(ctx.api.reset_reservations().await?.result?, 0);
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, Reservation, TestCtxBuilder};
+use uuid::Uuid;
+
+mod util;
+use util::scale_to;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_reservation_carries_active_server_id() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 4).await?;
+
+    for _ in 0..50 {
+        let mut options = RequestOptions {
+            server_id: None,
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        let response = ctx.api.reserve_ticket(&options).await?;
+        let server = response
+            .server_id
+            .expect("A reservation must carry a server id.");
+        assert!(
+            servers.contains(&server),
+            "The server id of a reservation must be an active server."
+        );
+        let Reservation::Reserved(ticket_id, _) = response.result? else {
+            panic!("Tickets must not be sold out.");
+        };
+
+        // The server holding the reservation must be the one that sells the ticket
+        options.server_id = Some(server);
+        let response = ctx.api.buy_ticket(ticket_id, &options).await?;
+        assert_eq!(response.result?, ticket_id);
+        assert!(servers.contains(&response.server_id.unwrap()));
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
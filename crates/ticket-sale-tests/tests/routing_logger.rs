@@ -0,0 +1,57 @@
+use std::sync::{mpsc, Mutex};
+
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, RoutingEvent, RoutingLogger, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_routing_events() -> Result<()> {
+    let (sender, events) = mpsc::channel();
+    let sender = Mutex::new(sender);
+    let ctx = TestCtxBuilder::from_env()?
+        .with_routing_logger(RoutingLogger::new(move |event| {
+            let _ = sender.lock().unwrap().send(event);
+        }))
+        .build()
+        .await?;
+
+    // A customer without a server is assigned one
+    let customer = Uuid::new_v4();
+    let mut options = RequestOptions {
+        server_id: None,
+        customer_id: Some(customer),
+        ..Default::default()
+    };
+    let response = ctx.api.get_local_available_tickets(&options).await?;
+    let server = response.server_id.unwrap();
+    assert_eq!(
+        events.try_recv()?,
+        RoutingEvent::Assigned { customer, server }
+    );
+
+    // Known servers are used without a routing decision
+    options.server_id = Some(server);
+    ctx.api
+        .get_local_available_tickets(&options)
+        .await?
+        .result?;
+    assert!(events.try_recv().is_err());
+
+    // A server that does not exist is reported as gone
+    let old = Uuid::new_v4();
+    options.server_id = Some(old);
+    assert!(ctx
+        .api
+        .get_local_available_tickets(&options)
+        .await?
+        .result
+        .is_err());
+    assert_eq!(
+        events.try_recv()?,
+        RoutingEvent::ServerGone { customer, old }
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
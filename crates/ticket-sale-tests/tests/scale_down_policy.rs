@@ -0,0 +1,49 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, ScaleDownPolicy, TestCtxBuilder};
+use util::scale_to;
+use uuid::Uuid;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_least_loaded_servers_deactivated_first() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_scale_down_policy(ScaleDownPolicy::LeastLoaded)
+        .build()
+        .await?;
+
+    scale_to(&ctx, 3).await?;
+    let servers = ctx.api.get_servers().await?.result?;
+
+    // Distribute the reservations unevenly: 3, 0, and 1
+    for (server, reservations) in servers.iter().zip([3, 0, 1]) {
+        for _ in 0..reservations {
+            let options = RequestOptions {
+                server_id: Some(*server),
+                customer_id: Some(Uuid::new_v4()),
+                ..Default::default()
+            };
+            let response = ctx.api.reserve_ticket(&options).await?;
+            assert_eq!(response.server_id, Some(*server));
+            response.result?.reserved()?;
+        }
+    }
+
+    // The server without reservations is deactivated first
+    let remaining = scale_to(&ctx, 2).await?;
+    assert!(
+        !remaining.contains(&servers[1]),
+        "The server without reservations must be deactivated first."
+    );
+
+    // Then the one with a single reservation
+    let remaining = scale_to(&ctx, 1).await?;
+    assert!(
+        remaining.contains(&servers[0]),
+        "The server with the most reservations must be deactivated last."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
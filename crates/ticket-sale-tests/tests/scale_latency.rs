@@ -0,0 +1,32 @@
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_requests_during_large_scale_up() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?.build().await?;
+
+    // Scale up on another balancer thread
+    let api = ctx.api.clone();
+    let scaling = tokio::spawn(async move { api.post_num_servers(400).await });
+
+    // Requests needing the coordinator are still answered meanwhile
+    let mut max_latency = Duration::ZERO;
+    while !scaling.is_finished() {
+        let start = Instant::now();
+        ctx.api.get_num_servers().await?.result?;
+        max_latency = max_latency.max(start.elapsed());
+    }
+    assert!(
+        max_latency < Duration::from_millis(500),
+        "Requests must not wait for all servers to be started, but took {max_latency:?}."
+    );
+
+    assert_eq!(scaling.await??.result?, 400);
+    assert_eq!(ctx.api.get_num_servers().await?.result?, 400);
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_scaling_is_clamped_to_bounds() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_server_bounds(1, 10)
+        .build()
+        .await?;
+
+    // Scaling to 0 servers is clamped to the minimum
+    assert_eq!(
+        ctx.api.post_num_servers(0).await?.result?,
+        1,
+        "The response must report the number of servers actually scaled to."
+    );
+    assert_eq!(ctx.api.get_num_servers().await?.result?, 1);
+    assert_eq!(ctx.api.get_servers().await?.result?.len(), 1);
+
+    // Scaling to an absurd number of servers is clamped to the maximum
+    assert_eq!(
+        ctx.api.post_num_servers(u32::MAX as usize).await?.result?,
+        10,
+        "The response must report the number of servers actually scaled to."
+    );
+    assert_eq!(ctx.api.get_num_servers().await?.result?, 10);
+    assert_eq!(ctx.api.get_servers().await?.result?.len(), 10);
+
+    // Requests within the bounds are applied as is
+    assert_eq!(ctx.api.post_num_servers(4).await?.result?, 4);
+    assert_eq!(ctx.api.get_num_servers().await?.result?, 4);
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_scale_settles_within_bounds() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_server_bounds(1, 10)
+        .build()
+        .await?;
+
+    assert_eq!(ctx.api.scale(7).await?, 7);
+    assert_eq!(ctx.api.scale(3).await?, 3);
+
+    // Counts outside the bounds settle at the clamped number
+    assert_eq!(ctx.api.scale(100).await?, 10);
+    assert_eq!(ctx.api.scale(0).await?, 1);
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,52 @@
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_scaling_state_settles() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .build()
+        .await?;
+    scale_to(&ctx, 8).await?;
+    assert_eq!(ctx.api.get_scaling_state().await?.result?, 0);
+
+    // The deactivated servers hold no reservations and terminate right away
+    ctx.api.post_num_servers(2).await?.result?;
+    let start = Instant::now();
+    while ctx.api.get_scaling_state().await?.result? != 0 {
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "Scaling down servers without reservations must settle."
+        );
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_back_to_back_scaling() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .build()
+        .await?;
+    scale_to(&ctx, 8).await?;
+
+    // The second request waits for the first one to settle
+    ctx.api.post_num_servers(2).await?.result?;
+    assert_eq!(ctx.api.post_num_servers(8).await?.result?, 8);
+    assert_eq!(ctx.api.get_scaling_state().await?.result?, 0);
+    assert_eq!(ctx.api.get_num_servers().await?.result?, 8);
+    assert_eq!(ctx.api.get_servers().await?.result?.len(), 8);
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use std::collections::HashSet;
+
+use eyre::Result;
+use ticket_sale_tests::{RunCfg, TestCtxBuilder};
+use uuid::Uuid;
+
+/// Get the server ids and the ids of the first few customers of a run with `seed`
+async fn ids_with_seed(seed: u64) -> Result<(HashSet<Uuid>, Vec<Uuid>)> {
+    let ctx = TestCtxBuilder::from_env()?.with_seed(seed).build().await?;
+    assert_eq!(ctx.seed, Some(seed));
+
+    let servers = HashSet::from_iter(ctx.api.get_servers().await?.result?);
+    let customers = (0..5)
+        .map(|_| ctx.api.create_user_session(None).customer_id)
+        .collect();
+
+    ctx.finish().await;
+    Ok((servers, customers))
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_seed_reproduces_ids() -> Result<()> {
+    let (servers, customers) = ids_with_seed(42).await?;
+    let (servers_again, customers_again) = ids_with_seed(42).await?;
+    assert_eq!(
+        customers, customers_again,
+        "Runs with the same seed must generate the same customer ids."
+    );
+    if let RunCfg::RustNative = TestCtxBuilder::from_env()?.run_cfg {
+        assert_eq!(
+            servers, servers_again,
+            "Runs with the same seed must generate the same server ids."
+        );
+    }
+
+    let (other_servers, other_customers) = ids_with_seed(43).await?;
+    assert_ne!(
+        customers, other_customers,
+        "Runs with different seeds must generate different customer ids."
+    );
+    assert!(
+        servers.is_disjoint(&other_servers),
+        "Runs with different seeds must generate different server ids."
+    );
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_server_load() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers: Vec<_> = scale_to(&ctx, 3).await?.into_iter().collect();
+
+    // Reserve three tickets on the first server, one on the second, and none on
+    // the third
+    for (server_id, count) in [(servers[0], 3), (servers[1], 1)] {
+        for _ in 0..count {
+            let mut session = ctx.api.create_user_session(Some(server_id));
+            session.reserve_ticket().await?.result?.reserved()?;
+        }
+    }
+
+    let mut load = ctx.api.get_server_load().await?.result?;
+    load.sort();
+    let mut expected = vec![(servers[0], 3), (servers[1], 1), (servers[2], 0)];
+    expected.sort();
+    assert_eq!(load, expected);
+
+    ctx.finish().await;
+    Ok(())
+}
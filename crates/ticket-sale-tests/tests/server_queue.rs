@@ -0,0 +1,64 @@
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use ticket_sale_tests::{RequestError, RequestOptions, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_full_server_queue_rejects_without_blocking() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_debug_sleep()
+        .with_server_queue_capacity(4)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let servers = ctx.api.get_servers().await?.result?;
+    let options = |server| {
+        RequestOptions {
+            server_id: Some(server),
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        }
+    };
+
+    // Block the first server and flood it with more requests than it can queue
+    ctx.api
+        .debug_sleep(1_000, &options(servers[0]))
+        .await?
+        .result?;
+    let start = Instant::now();
+    let flood: Vec<_> = (0..20)
+        .map(|_| {
+            let api = ctx.api.clone();
+            let options = options(servers[0]);
+            tokio::spawn(async move { api.get_available_tickets(&options).await })
+        })
+        .collect();
+
+    // The other server is not affected
+    ctx.api
+        .get_available_tickets(&options(servers[1]))
+        .await?
+        .result?;
+    assert!(
+        start.elapsed() < Duration::from_millis(500),
+        "A flooded server must not block requests for other servers."
+    );
+
+    let mut busy = 0;
+    for task in flood {
+        if let Err(err) = task.await??.result {
+            assert_eq!(err.kind(), RequestError::Unavailable);
+            busy += 1;
+        }
+    }
+    assert!(
+        busy >= 16,
+        "Requests exceeding the queue capacity must be rejected as busy, only {busy} were."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, ServerSelection, TestCtxBuilder};
+use util::scale_to;
+use uuid::Uuid;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_least_loaded_server_selection() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(10_000)
+        .with_reassign_rate(0)
+        .with_server_selection(ServerSelection::LeastLoaded)
+        .build()
+        .await?;
+    scale_to(&ctx, 20).await?;
+
+    // Reservations that are kept must be spread evenly over the servers
+    let mut reservations = HashMap::new();
+    for _ in 0..40 {
+        let options = RequestOptions {
+            server_id: None,
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        let response = ctx.api.reserve_ticket(&options).await?;
+        response.result?.reserved()?;
+        *reservations.entry(response.server_id).or_insert(0) += 1;
+    }
+    assert_eq!(reservations.len(), 20);
+    assert!(
+        reservations.values().all(|&count| count == 2),
+        "With least loaded selection, each server must get 2 of the 40 reservations."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_customer_hash_server_selection() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_reassign_rate(0)
+        .with_server_selection(ServerSelection::CustomerHash)
+        .build()
+        .await?;
+    scale_to(&ctx, 4).await?;
+
+    // Customers without a server id always get the same server
+    let mut assignment = HashMap::new();
+    for _ in 0..20 {
+        let customer_id = Uuid::new_v4();
+        let options = RequestOptions {
+            server_id: None,
+            customer_id: Some(customer_id),
+            ..Default::default()
+        };
+        let server_id = ctx.api.get_available_tickets(&options).await?.server_id;
+        for _ in 0..3 {
+            let response = ctx.api.get_available_tickets(&options).await?;
+            assert_eq!(
+                response.server_id, server_id,
+                "With customer hash selection, a customer must keep their server."
+            );
+        }
+        assignment.insert(customer_id, server_id);
+    }
+
+    // After scaling down, only customers of the removed server move
+    scale_to(&ctx, 3).await?;
+    let servers = ctx.api.get_servers().await?.result?;
+    for (customer_id, server_id) in assignment {
+        let options = RequestOptions {
+            server_id: None,
+            customer_id: Some(customer_id),
+            ..Default::default()
+        };
+        let response = ctx.api.get_available_tickets(&options).await?;
+        if servers.contains(&server_id.unwrap()) {
+            assert_eq!(
+                response.server_id, server_id,
+                "Customers of a remaining server must keep their server."
+            );
+        }
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
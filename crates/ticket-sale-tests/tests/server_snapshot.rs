@@ -0,0 +1,42 @@
+use std::collections::HashSet;
+
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_get_servers_while_scaling() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_balancer_threads(4)
+        .build()
+        .await?;
+
+    // Scale up and down in the background
+    let api = ctx.api.clone();
+    let scaler = tokio::spawn(async move {
+        for i in 0..200 {
+            api.post_num_servers(if i % 2 == 0 { 20 } else { 5 })
+                .await?
+                .result?;
+        }
+        eyre::Ok(())
+    });
+
+    // Every server list is a consistent snapshot
+    while !scaler.is_finished() {
+        let servers = ctx.api.get_servers().await?.result?;
+        let unique: HashSet<_> = servers.iter().collect();
+        assert_eq!(unique.len(), servers.len(), "Server ids must be distinct.");
+        assert!(
+            (1..=20).contains(&servers.len()),
+            "The server list must be a snapshot after scaling, got {} servers.",
+            servers.len()
+        );
+        // Responses may be ready immediately, so let the scaler make progress
+        tokio::task::yield_now().await;
+    }
+    scaler.await??;
+
+    ctx.finish().await;
+    Ok(())
+}
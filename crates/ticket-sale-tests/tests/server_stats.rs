@@ -0,0 +1,55 @@
+use std::collections::HashSet;
+
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use util::scale_to;
+use uuid::Uuid;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_server_stats() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 3).await?;
+
+    // Reserve 5 tickets on one of the servers
+    let server_id = *servers.iter().next().unwrap();
+    for _ in 0..5 {
+        let options = RequestOptions {
+            server_id: Some(server_id),
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        ctx.api.reserve_ticket(&options).await?.result?.reserved()?;
+    }
+
+    // Every non-terminating server is reported exactly once
+    let stats = ctx.api.debug_server_stats().await?.result?;
+    let reported: HashSet<_> = stats.iter().map(|stats| stats.server_id).collect();
+    assert_eq!(stats.len(), 3);
+    assert_eq!(reported, servers);
+
+    for stats in &stats {
+        let expected = if stats.server_id == server_id { 5 } else { 0 };
+        assert_eq!(
+            stats.reserved, expected,
+            "The reserved tickets of each server must be reported."
+        );
+    }
+    let held: u64 = stats
+        .iter()
+        .map(|stats| stats.available + stats.reserved)
+        .sum();
+    assert!(
+        held <= 1_000,
+        "Servers must not hold more than all tickets."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use std::time::Duration;
+
+use eyre::Result;
+use ticket_sale_core::RequestError;
+use ticket_sale_tests::TestCtxBuilder;
+use tokio::sync::mpsc;
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_requests_during_shutdown() -> Result<()> {
+    // Only the bonus balancer caches the senders of its servers
+    let mut builder = TestCtxBuilder::from_env()?.with_tickets(1_000);
+    builder.bonus = true;
+    let ctx = builder.build().await?;
+    scale_to(&ctx, 3).await?;
+
+    // Customers keep sending requests until the shutdown turns them away
+    let (started_sender, mut started) = mpsc::channel(8);
+    let tasks: Vec<_> = (0..8)
+        .map(|_| {
+            let api = ctx.api.clone();
+            let started_sender = started_sender.clone();
+            tokio::spawn(async move {
+                let mut session = api.create_user_session(None);
+                loop {
+                    // Every request must be answered, even if with an error
+                    match session.reserve_ticket().await?.result {
+                        Ok(reservation) => {
+                            if let Ok(ticket_id) = reservation.reserved() {
+                                let _ = session.buy_ticket(ticket_id).await?;
+                            }
+                        }
+                        Err(err) if err.kind() == RequestError::Unavailable => break,
+                        Err(_) => {}
+                    }
+                    let _ = started_sender.try_send(());
+                }
+                eyre::Ok(())
+            })
+        })
+        .collect();
+    drop(started_sender);
+
+    // Shut down while the customers are sending requests
+    for _ in 0..8 {
+        started.recv().await;
+    }
+    ctx.finish().await;
+    for task in tasks {
+        task.await??;
+    }
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_shutdown_joins_in_time() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .build()
+        .await?;
+    scale_to(&ctx, 8).await?;
+
+    // Leave requests in flight while shutting down
+    let tasks: Vec<_> = (0..8)
+        .map(|_| {
+            let api = ctx.api.clone();
+            tokio::spawn(async move {
+                let mut session = api.create_user_session(None);
+                for _ in 0..20 {
+                    let _ = session.reserve_ticket().await?;
+                }
+                eyre::Ok(())
+            })
+        })
+        .collect();
+
+    // Joining all threads must not hang
+    ctx.finish_within(Duration::from_secs(5)).await?;
+    for task in tasks {
+        task.await??;
+    }
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use eyre::Result;
+use nanorand::Rng;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_sold_and_available_add_up() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_estimator_roundtrip_time(1)
+        .build()
+        .await?;
+    scale_to(&ctx, 4).await?;
+
+    // Buy a random subset of the reserved tickets and abort the others, scaling in
+    // between
+    let mut rng = nanorand::tls_rng();
+    let mut bought = 0;
+    for i in 0..90 {
+        let mut session = ctx.api.create_user_session(None);
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        if rng.generate::<bool>() {
+            session.buy_ticket(ticket_id).await?.result?;
+            bought += 1;
+        } else {
+            session.abort_purchase(ticket_id).await?.result?;
+        }
+        match i {
+            30 => {
+                scale_to(&ctx, 2).await?;
+            }
+            60 => {
+                scale_to(&ctx, 5).await?;
+            }
+            _ => {}
+        }
+    }
+    let sold = ctx.api.get_sold_tickets().await?.result?;
+    assert_eq!(sold, bought, "Exactly the bought tickets must be sold.");
+
+    // Once the estimator has caught up, every server sums up the tickets of all
+    // servers and the database
+    ctx.measure_estimator_roundtrip(2).await?;
+    for server in ctx.api.get_servers().await?.result? {
+        let options = RequestOptions {
+            server_id: Some(server),
+            customer_id: None,
+            ..Default::default()
+        };
+        let available = ctx.api.get_available_tickets(&options).await?.result?;
+        assert_eq!(
+            sold + available,
+            ctx.tickets,
+            "Without reservations, every ticket must either be sold or available."
+        );
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
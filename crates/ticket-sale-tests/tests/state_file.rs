@@ -0,0 +1,81 @@
+use eyre::Result;
+use ticket_sale_rocket::Database;
+use ticket_sale_tests::{RequestError, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_state_survives_restart() -> Result<()> {
+    // A single server, so it holds every ticket taken from the database
+    let path = std::env::temp_dir().join(format!("ticket-sale-{}.state", Uuid::new_v4()));
+    let builder = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_server_bounds(1, 1)
+        .with_state_path(&path);
+
+    // Sell three tickets and keep one reservation
+    let ctx = builder.clone().build().await?;
+    let mut sold = Vec::new();
+    for _ in 0..3 {
+        let mut session = ctx.api.create_user_session(None);
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        session.buy_ticket(ticket_id).await?.result?;
+        sold.push(ticket_id);
+    }
+    let mut session = ctx.api.create_user_session(None);
+    let reserved = session.reserve_ticket().await?.result?.reserved()?;
+    ctx.finish().await;
+
+    // The sold tickets stay sold, the reservation is lost
+    let ctx = builder.build().await?;
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 3);
+    for ticket_id in sold {
+        let mut session = ctx.api.create_user_session(None);
+        let err = session
+            .reserve_specific_ticket(ticket_id)
+            .await?
+            .result
+            .err();
+        assert_eq!(
+            err.map(|err| err.kind()),
+            Some(RequestError::Conflict),
+            "Ticket {ticket_id} was sold before the restart."
+        );
+    }
+    let mut session = ctx.api.create_user_session(None);
+    let response = session.reserve_specific_ticket(reserved).await?;
+    assert_eq!(
+        response.result?.reserved()?,
+        reserved,
+        "Reservations must not survive a restart."
+    );
+    ctx.finish().await;
+
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_malformed_state_file_reported() -> Result<()> {
+    let path = std::env::temp_dir().join(format!("ticket-sale-{}.state", Uuid::new_v4()));
+    assert!(
+        Database::restore_from(&path, false)?.is_none(),
+        "Without a state file, the database starts fresh."
+    );
+
+    std::fs::write(&path, "tickets 100\nsold three\n")?;
+    assert!(Database::restore_from(&path, false).is_err());
+
+    // Launching is best-effort and starts without the malformed state
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_state_path(&path)
+        .build()
+        .await?;
+    std::fs::remove_file(&path)?;
+    if let Some(report) = ctx.finish_with_report().await {
+        assert_eq!(report.available, 100, "No ticket was sold before.");
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_terminating_servers() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 2).await?;
+    assert!(ctx.api.get_terminating_servers().await?.result?.is_empty());
+    let drained = *servers.iter().next().unwrap();
+
+    // Keep the server from terminating with a reservation
+    let mut session = ctx.api.create_user_session(Some(drained));
+    let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+    ctx.api.drain_server(drained).await?.result?;
+    assert_eq!(
+        ctx.api.get_terminating_servers().await?.result?,
+        vec![drained],
+        "A server holding a reservation must stay terminating."
+    );
+
+    // Once the reservation is bought, the server terminates and is no longer listed
+    session.buy_ticket(ticket_id).await?.result?;
+    let start = Instant::now();
+    while !ctx.api.get_terminating_servers().await?.result?.is_empty() {
+        assert!(
+            start.elapsed() < Duration::from_secs(5),
+            "A terminating server must terminate once its reservations are cleared."
+        );
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(ctx.api.get_sold_tickets().await?.result?, 1);
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! Benchmark comparing the request throughput for different numbers of balancer
+//! threads
+//!
+//! Run it in release mode with
+//! `cargo test -p ticket-sale-tests --release --test thread_scaling -- --ignored
+//! --show-output`.
+
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use ticket_sale_tests::{Api, Reservation, TestCtxBuilder};
+use util::scale_to;
+
+mod util;
+
+/// Numbers of balancer threads to compare
+const THREADS: [u16; 4] = [1, 4, 16, 64];
+/// Number of servers the benchmark runs with
+const SERVERS: usize = 4;
+/// Number of tickets the benchmark runs with
+const TICKETS: u64 = 10_000;
+/// Number of concurrent customers
+const CUSTOMERS: usize = 256;
+/// Duration of each measurement
+const DURATION: Duration = Duration::from_secs(1);
+
+/// Reserve and abort tickets until `deadline`, returning the number of requests
+async fn reserve_until(api: Api, deadline: Instant) -> Result<usize> {
+    let mut requests = 0;
+    while Instant::now() < deadline {
+        let mut session = api.create_user_session(None);
+        let result = session.reserve_ticket().await?.result;
+        requests += 1;
+        // Give the ticket back, so that the pool never runs dry
+        if let Ok(Reservation::Reserved(ticket_id, _)) = result {
+            session.abort_purchase(ticket_id).await?.result?;
+            requests += 1;
+        }
+    }
+    Ok(requests)
+}
+
+#[tokio::test]
+#[ignore = "benchmark, run with --ignored"]
+#[ntest::timeout(120_000)] // Test timeout in ms
+async fn bench_balancer_threads() -> Result<()> {
+    for threads in THREADS {
+        let ctx = TestCtxBuilder::from_env()?
+            .with_tickets(TICKETS)
+            .with_balancer_threads(threads)
+            .build()
+            .await?;
+        scale_to(&ctx, SERVERS).await?;
+
+        // Each clone of the API sends its requests to another balancer thread
+        let deadline = Instant::now() + DURATION;
+        let workers: Vec<_> = (0..CUSTOMERS)
+            .map(|_| tokio::spawn(reserve_until(ctx.api.clone(), deadline)))
+            .collect();
+        let mut requests = 0;
+        for worker in workers {
+            requests += worker.await??;
+        }
+
+        let throughput = requests as f64 / DURATION.as_secs_f64();
+        println!("{threads:>2} threads: {throughput:>9.0} requests/s");
+
+        ctx.finish().await;
+    }
+    Ok(())
+}
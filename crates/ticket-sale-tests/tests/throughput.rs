@@ -0,0 +1,85 @@
+//! Benchmark measuring the maximal sustainable reservation throughput
+//!
+//! Run it in release mode with
+//! `cargo test -p ticket-sale-tests --release --test throughput -- --ignored
+//! --show-output`.
+
+use std::time::{Duration, Instant};
+
+use eyre::Result;
+use ticket_sale_tests::{Api, Reservation, TestCtxBuilder};
+use util::scale_to;
+
+mod util;
+
+/// Number of servers the benchmark runs with
+const SERVERS: usize = 4;
+/// Number of tickets the benchmark runs with
+const TICKETS: u64 = 10_000;
+/// Duration of each concurrency level
+const LEVEL_DURATION: Duration = Duration::from_secs(1);
+/// Maximal 99th percentile of the reservation latency considered sustainable
+const MAX_P99_LATENCY: Duration = Duration::from_millis(1);
+/// Maximal number of concurrent customers
+const MAX_CONCURRENCY: usize = 1024;
+
+/// Reserve and abort tickets until `deadline`, returning the latency of each
+/// reservation
+async fn reserve_until(api: Api, deadline: Instant) -> Result<Vec<Duration>> {
+    let mut latencies = Vec::new();
+    while Instant::now() < deadline {
+        let mut session = api.create_user_session(None);
+        let start = Instant::now();
+        let result = session.reserve_ticket().await?.result;
+        latencies.push(start.elapsed());
+        // Give the ticket back, so that the pool never runs dry
+        if let Ok(Reservation::Reserved(ticket_id, _)) = result {
+            session.abort_purchase(ticket_id).await?.result?;
+        }
+    }
+    Ok(latencies)
+}
+
+#[tokio::test]
+#[ignore = "benchmark, run with --ignored"]
+#[ntest::timeout(120_000)] // Test timeout in ms
+async fn bench_max_sustainable_throughput() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(TICKETS)
+        .build()
+        .await?;
+    scale_to(&ctx, SERVERS).await?;
+
+    // Double the number of concurrent customers until the latency is too high
+    let mut best = 0.0;
+    let mut concurrency = 1;
+    while concurrency <= MAX_CONCURRENCY {
+        let deadline = Instant::now() + LEVEL_DURATION;
+        let workers: Vec<_> = (0..concurrency)
+            .map(|_| tokio::spawn(reserve_until(ctx.api.clone(), deadline)))
+            .collect();
+        let mut latencies = Vec::new();
+        for worker in workers {
+            latencies.extend(worker.await??);
+        }
+
+        latencies.sort();
+        let p99 = latencies[latencies.len() * 99 / 100];
+        let throughput = latencies.len() as f64 / LEVEL_DURATION.as_secs_f64();
+        println!("{concurrency:>4} customers: {throughput:>9.0} reservations/s, p99 {p99:?}");
+        if p99 > MAX_P99_LATENCY {
+            break;
+        }
+        if throughput > best {
+            best = throughput;
+        }
+        concurrency *= 2;
+    }
+    println!(
+        "Max sustainable throughput with {SERVERS} servers and {TICKETS} tickets: \
+         {best:.0} reservations/s (p99 latency at most {MAX_P99_LATENCY:?})"
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
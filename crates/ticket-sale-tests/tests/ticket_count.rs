@@ -0,0 +1,16 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_too_many_tickets_rejected() -> Result<()> {
+    let result = TestCtxBuilder::from_env()?
+        .with_tickets(u32::MAX as u64 + 1)
+        .build()
+        .await;
+    assert!(
+        result.is_err(),
+        "More tickets than the system can count must be rejected instead of truncated."
+    );
+    Ok(())
+}
@@ -0,0 +1,20 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_get_timeout() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_reservation_timeout(42)
+        .build()
+        .await?;
+
+    assert_eq!(
+        ctx.api.get_timeout().await?.result?,
+        42,
+        "The configured reservation timeout must be reported."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use eyre::{eyre, Result};
+use ticket_sale_tests::{Reservation, TestCtxBuilder};
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_timeout_jitter_spreads_expiries() -> Result<()> {
+    // The estimator contacts the single server every second, so it notices the
+    // expiries without further requests
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_reservation_timeout(1)
+        .with_timeout_jitter(3)
+        .with_estimator_roundtrip_time(1)
+        .with_server_bounds(1, 1)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    // Reserve in a burst
+    let mut ttls = HashSet::new();
+    for _ in 0..20 {
+        let mut session = ctx.api.create_user_session(None);
+        let Reservation::Reserved(_, Some(ttl)) = session.reserve_ticket().await?.result? else {
+            return Err(eyre!("A reservation must succeed and report its timeout."));
+        };
+        assert!(
+            (1..=4).contains(&ttl),
+            "A reservation must expire after the timeout plus at most the jitter, not \
+             after {ttl} seconds."
+        );
+        ttls.insert(ttl);
+    }
+    assert!(
+        ttls.len() > 1,
+        "Reservations made together must not all get the same timeout."
+    );
+
+    // The expiries are noticed over several seconds instead of all at once
+    let first = {
+        ctx.next_expiry(Duration::from_secs(5)).await?;
+        Instant::now()
+    };
+    for _ in 1..20 {
+        ctx.next_expiry(Duration::from_secs(5)).await?;
+    }
+    assert!(
+        first.elapsed() >= Duration::from_millis(500),
+        "Reservations with jitter must not all expire at once."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
@@ -0,0 +1,76 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+use util::scale_to;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_total_available() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 3).await?;
+    assert_eq!(
+        ctx.total_available().await?,
+        0,
+        "Servers must not hold tickets before the first reservation."
+    );
+
+    // One reservation on each server allocates sqrt(1000) = 31, sqrt(969) = 31, and
+    // sqrt(938) = 30 tickets, one of which is reserved each
+    let mut sessions = Vec::new();
+    for server in servers {
+        let mut session = ctx.api.create_user_session(Some(server));
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        sessions.push((session, ticket_id));
+    }
+    assert_eq!(ctx.total_available().await?, 89);
+
+    // An aborted ticket returns to its server
+    let (session, ticket_id) = &mut sessions[0];
+    session.abort_purchase(*ticket_id).await?.result?;
+    assert_eq!(ctx.total_available().await?, 90);
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_get_total_available() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 3).await?;
+    assert_eq!(ctx.api.get_total_available().await?.result?, 1_000);
+
+    // Sell tickets on every server, so all of them hold some
+    for server in servers.iter().cycle().take(25) {
+        let mut session = ctx.api.create_user_session(Some(*server));
+        let ticket_id = session.reserve_ticket().await?.result?.reserved()?;
+        session.buy_ticket(ticket_id).await?.result?;
+    }
+    let sold = ctx.api.get_sold_tickets().await?.result?;
+    assert_eq!(sold, 25);
+    assert_eq!(
+        ctx.api.get_total_available().await?.result?,
+        1_000 - sold,
+        "All tickets neither sold nor reserved must be available."
+    );
+
+    // Reserved tickets are not available
+    let mut session = ctx.api.create_user_session(servers.iter().next().copied());
+    session.reserve_ticket().await?.result?.reserved()?;
+    assert_eq!(
+        ctx.api.get_total_available().await?.result?,
+        1_000 - sold - 1
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
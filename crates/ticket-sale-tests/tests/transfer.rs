@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use eyre::Result;
+use ticket_sale_tests::{RequestError, RequestOptions, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_transfer_reservation() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let mut options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.reserve_ticket(&options).await?;
+    options.server_id = response.server_id;
+    let ticket_id = response.result?.reserved()?;
+    let target = RequestOptions {
+        server_id: options.server_id,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+
+    // Only reserved tickets can be transferred
+    let err = ctx
+        .api
+        .transfer_reservation(ticket_id + 1, target.customer_id.unwrap(), &options)
+        .await?
+        .result
+        .err();
+    assert_eq!(err.map(|err| err.kind()), Some(RequestError::Conflict));
+    let err = ctx
+        .api
+        .transfer_reservation(ticket_id, options.customer_id.unwrap(), &target)
+        .await?
+        .result
+        .err();
+    assert_eq!(
+        err.map(|err| err.kind()),
+        Some(RequestError::Conflict),
+        "A customer without a reservation cannot transfer it."
+    );
+
+    let transferred = ctx
+        .api
+        .transfer_reservation(ticket_id, target.customer_id.unwrap(), &options)
+        .await?
+        .result?;
+    assert_eq!(transferred, ticket_id);
+    assert!(ctx
+        .api
+        .get_my_reservations(&options)
+        .await?
+        .result?
+        .is_empty());
+    assert_eq!(
+        ctx.api.get_my_reservations(&target).await?.result?,
+        vec![ticket_id]
+    );
+
+    // The target holds a reservation now and cannot receive another one
+    let second = ctx.api.reserve_ticket(&options).await?.result?.reserved()?;
+    let err = ctx
+        .api
+        .transfer_reservation(second, target.customer_id.unwrap(), &options)
+        .await?
+        .result
+        .err();
+    assert_eq!(err.map(|err| err.kind()), Some(RequestError::Conflict));
+
+    // Only the target can buy the transferred ticket
+    assert!(ctx
+        .api
+        .buy_ticket(ticket_id, &options)
+        .await?
+        .result
+        .is_err());
+    assert_eq!(
+        ctx.api.buy_ticket(ticket_id, &target).await?.result?,
+        ticket_id
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_transfer_keeps_reservation_time() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(100)
+        .with_reservation_timeout(2)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+
+    let mut options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.reserve_ticket(&options).await?;
+    options.server_id = response.server_id;
+    let ticket_id = response.result?.reserved()?;
+
+    // Transfer shortly before the reservation expires
+    tokio::time::sleep(Duration::from_millis(1_500)).await;
+    let target = RequestOptions {
+        server_id: options.server_id,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    ctx.api
+        .transfer_reservation(ticket_id, target.customer_id.unwrap(), &options)
+        .await?
+        .result?;
+
+    // The transfer does not extend the reservation
+    tokio::time::sleep(Duration::from_millis(1_700)).await;
+    assert!(
+        ctx.api
+            .buy_ticket(ticket_id, &target)
+            .await?
+            .result
+            .is_err(),
+        "A transferred reservation must expire at its original time."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
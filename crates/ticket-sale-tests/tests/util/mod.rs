@@ -1,15 +1,15 @@
 use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 
-use eyre::Result;
-use ticket_sale_tests::TestCtx;
+use eyre::{eyre, Result};
+use ticket_sale_tests::{Reservation, TestCtx};
 use uuid::Uuid;
 
 /// Scales the number of servers and tests that scaling works.
 #[allow(unused)]
 pub async fn scale_to(ctx: &TestCtx, num_servers: usize) -> Result<HashSet<Uuid>> {
-    ctx.api.post_num_servers(num_servers).await?;
     assert_eq!(
-        ctx.api.get_num_servers().await?.result?,
+        ctx.api.scale(num_servers).await?,
         num_servers,
         "After scaling to {num_servers} servers, the number of servers must be {num_servers}."
     );
@@ -20,3 +20,49 @@ pub async fn scale_to(ctx: &TestCtx, num_servers: usize) -> Result<HashSet<Uuid>
   );
     Ok(servers)
 }
+
+/// Let `customers` concurrent customers reserve and buy up to `per_customer` tickets
+/// each, returning the number of bought tickets
+///
+/// Fails if a ticket is sold twice. Customers stop once the tickets are sold out.
+#[allow(unused)]
+pub async fn hammer_reserve_and_buy(
+    ctx: &TestCtx,
+    customers: usize,
+    per_customer: usize,
+) -> Result<usize> {
+    let sold = Arc::new(Mutex::new(HashSet::new()));
+    let tasks: Vec<_> = (0..customers)
+        .map(|_| {
+            // Each clone sends its requests to another balancer thread
+            let api = ctx.api.clone();
+            let sold = sold.clone();
+            tokio::spawn(async move {
+                let mut session = api.create_user_session(None);
+                let mut bought = 0;
+                for _ in 0..per_customer {
+                    let ticket_id = match session.reserve_ticket().await?.result {
+                        Ok(Reservation::Reserved(ticket_id, _)) => ticket_id,
+                        Ok(Reservation::SoldOut) => break,
+                        // The server may be terminating, just try again
+                        Err(_) => continue,
+                    };
+                    if session.buy_ticket(ticket_id).await?.result.is_err() {
+                        continue;
+                    }
+                    if !sold.lock().unwrap().insert(ticket_id) {
+                        return Err(eyre!("Ticket {ticket_id} was sold twice."));
+                    }
+                    bought += 1;
+                }
+                Ok(bought)
+            })
+        })
+        .collect();
+
+    let mut bought = 0;
+    for task in tasks {
+        bought += task.await??;
+    }
+    Ok(bought)
+}
@@ -0,0 +1,20 @@
+use eyre::Result;
+use ticket_sale_tests::TestCtxBuilder;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_variant_matches_settings() -> Result<()> {
+    let dual = TestCtxBuilder::from_env()?.with_both().build().await?;
+
+    for ctx in [&dual.standard, &dual.bonus] {
+        let expected = if ctx.bonus { "bonus" } else { "standard" };
+        assert_eq!(
+            ctx.api.variant().await?.result?,
+            expected,
+            "The running implementation must match the test settings."
+        );
+    }
+
+    dual.finish().await;
+    Ok(())
+}
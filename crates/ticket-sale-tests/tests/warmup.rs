@@ -0,0 +1,40 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, TestCtxBuilder};
+use util::scale_to;
+use uuid::Uuid;
+
+mod util;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_warmup_distributes_tickets_evenly() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_tickets(1_000)
+        .with_reassign_rate(0)
+        .build()
+        .await?;
+    let servers = scale_to(&ctx, 10).await?;
+
+    assert_eq!(ctx.api.warmup().await?.result?, 1_000);
+
+    // Each server holds its share before any reservation
+    for server_id in servers {
+        let options = RequestOptions {
+            server_id: Some(server_id),
+            customer_id: Some(Uuid::new_v4()),
+            ..Default::default()
+        };
+        let local = ctx
+            .api
+            .get_local_available_tickets(&options)
+            .await?
+            .result?;
+        assert_eq!(
+            local, 100,
+            "Server {server_id} must hold a tenth of the tickets."
+        );
+    }
+
+    ctx.finish().await;
+    Ok(())
+}
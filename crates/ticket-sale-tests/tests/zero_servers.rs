@@ -0,0 +1,37 @@
+use eyre::Result;
+use ticket_sale_tests::{RequestOptions, Reservation, TestCtxBuilder};
+use uuid::Uuid;
+
+#[tokio::test]
+#[ntest::timeout(20_000)] // Test timeout in ms
+async fn test_reserve_without_servers() -> Result<()> {
+    let ctx = TestCtxBuilder::from_env()?
+        .with_server_bounds(0, 1000)
+        .build()
+        .await?;
+
+    assert_eq!(ctx.api.post_num_servers(0).await?.result?, 0);
+
+    // Without servers, reservations must fail cleanly
+    let options = RequestOptions {
+        server_id: None,
+        customer_id: Some(Uuid::new_v4()),
+        ..Default::default()
+    };
+    let response = ctx.api.reserve_ticket(&options).await?;
+    assert!(
+        response.result.is_err(),
+        "Reserving a ticket without servers must fail."
+    );
+
+    // The balancer must still work after scaling up again
+    assert_eq!(ctx.api.post_num_servers(1).await?.result?, 1);
+    let response = ctx.api.reserve_ticket(&options).await?;
+    assert!(
+        matches!(response.result?, Reservation::Reserved(_, _)),
+        "Reserving a ticket must succeed after scaling up again."
+    );
+
+    ctx.finish().await;
+    Ok(())
+}
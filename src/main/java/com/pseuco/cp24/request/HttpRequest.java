@@ -119,4 +119,14 @@ public class HttpRequest extends Request {
         }
         this.respond(200, serverList.toString());
     }
+
+    @Override
+    public void respondWithIntList(final int[] integers) {
+        final var intList = new StringBuilder();
+        for (final int integer : integers) {
+            intList.append(integer);
+            intList.append('\n');
+        }
+        this.respond(200, intList.toString());
+    }
 }
@@ -34,6 +34,7 @@ public class MockRequest extends Request {
             case 5 -> Method.POST; // BuyTicket
             case 6 -> Method.POST; // AbortPurchase
             case 7 -> Method.POST; // Debug
+            case 10 -> Method.GET; // GetSoldTickets
             default -> throw new RuntimeException("Invalid raw request kind");
         };
     }
@@ -48,6 +49,7 @@ public class MockRequest extends Request {
             case 5 -> Kind.BUY_TICKET;
             case 6 -> Kind.ABORT_PURCHASE;
             case 7 -> Kind.DEBUG;
+            case 10 -> Kind.GET_SOLD_TICKETS;
             default -> throw new RuntimeException("Invalid raw request kind");
         };
     }
@@ -154,6 +156,25 @@ public class MockRequest extends Request {
 
     private static native void respondWithServerIds(long responsePtr, long serverIds[]);
 
+    @Override
+    public void respondWithIntList(final int[] integers) {
+        long serverL = 0;
+        long serverM = 0;
+        if (this.serverId.isPresent()) {
+            final UUID sid = this.serverId.get().getUUID();
+            serverL = sid.getLeastSignificantBits();
+            serverM = sid.getMostSignificantBits();
+        }
+        final UUID cid = this.customerId.getUUID();
+        final long customerL = cid.getLeastSignificantBits();
+        final long customerM = cid.getMostSignificantBits();
+        respondWithIntList(this.responsePtr, integers, this.serverId.isPresent(), serverL, serverM, customerL,
+                customerM);
+    }
+
+    private static native void respondWithIntList(long responsePtr, int ints[], boolean hasServerId, long serverL,
+            long serverM, long customerL, long customerM);
+
     private static boolean checkShutdown() {
         final var current = Thread.currentThread();
         final var group = current.getThreadGroup();
@@ -66,7 +66,7 @@ public abstract class Request {
     }
 
     /**
-     * There are seven kinds of requests.
+     * There are eight kinds of requests.
      */
     public enum Kind {
         /**
@@ -139,7 +139,17 @@ public abstract class Request {
          * 📌 Hint: You can process this request however you like.
          * </p>
          */
-        DEBUG;
+        DEBUG,
+        /**
+         * <p>
+         * Retrieves the number of sold tickets.
+         * </p>
+         *
+         * <p>
+         * 📌 Hint: Should be processed by the load balancer.
+         * </p>
+         */
+        GET_SOLD_TICKETS;
 
         /**
          * Returns a {@link Kind} based on its path.
@@ -160,6 +170,8 @@ public abstract class Request {
                 case "/api/buy_ticket" -> Optional.of(Kind.BUY_TICKET);
                 case "/api/abort_purchase" -> Optional.of(Kind.ABORT_PURCHASE);
 
+                case "/api/sold_tickets" -> Optional.of(Kind.GET_SOLD_TICKETS);
+
                 default -> {
                     if (path.startsWith("/api/debug")) {
                         yield Optional.of(Kind.DEBUG);
@@ -237,6 +249,7 @@ public abstract class Request {
             case BUY_TICKET -> "/api/buy_ticket";
             case ABORT_PURCHASE -> "/api/abort_purchase";
             case DEBUG -> "/api/debug";
+            case GET_SOLD_TICKETS -> "/api/sold_tickets";
         };
     }
 
@@ -368,4 +381,17 @@ public abstract class Request {
      * @param ids An {@link Iterable} of server IDs.
      */
     public abstract void respondWithServerIds(final Iterable<ServerId> ids);
+
+    /**
+     * <p>
+     * Responds with a list of integers, e.g., ticket numbers.
+     * </p>
+     *
+     * <p>
+     * This method blocks until the response has been sent.
+     * </p>
+     *
+     * @param integers Integers to be sent to the client.
+     */
+    public abstract void respondWithIntList(final int[] integers);
 }
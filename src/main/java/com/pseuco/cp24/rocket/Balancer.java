@@ -47,8 +47,8 @@ public class Balancer implements RequestHandler {
         /*
          * Implementation of the load balancer.
          *
-         * Hint: You must handle the `NUM_SERVERS` and `GET_SERVERS` requests here. All
-         * other requests must be redirected to individual servers (expect the `DEBUG`
+         * Hint: You must handle the `NUM_SERVERS`, `GET_SERVERS`, and `GET_SOLD_TICKETS`
+         * requests here. All other requests must be redirected to individual servers (expect the `DEBUG`
          * request which can be handled however you like).
          *
          * For the `NUM_SERVERS` request you have to handle both `GET` and `POST` requests.
@@ -83,6 +83,14 @@ public class Balancer implements RequestHandler {
                  */
                 throw new RuntimeException("Not implemented!");
             }
+            case GET_SOLD_TICKETS -> {
+                /**
+                 * TODO: Determine the number of tickets sold by all servers, e.g., by
+                 * counting them in the database, and send it back to the client using
+                 * `respondWithInt`.
+                 */
+                throw new RuntimeException("Not implemented!");
+            }
 
             case DEBUG -> {
                 /**
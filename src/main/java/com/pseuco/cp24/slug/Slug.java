@@ -35,6 +35,11 @@ public class Slug implements RequestHandler {
      */
     private Map<CustomerId, Reservation> reservations = new HashMap<>();
 
+    /**
+     * Number of sold tickets.
+     */
+    private int numSold = 0;
+
     /**
      * Constructs a new slug.
      *
@@ -98,6 +103,13 @@ public class Slug implements RequestHandler {
                 request.respondWithServerIds(Arrays.asList(ids));
             }
 
+            // This request is handled by the load balancer.
+            case GET_SOLD_TICKETS -> {
+                // In your implementation, you need to respond with the number of tickets
+                // sold by all servers.
+                request.respondWithInt(this.numSold);
+            }
+
             // In your implementation, this request need to be redirected to a server.
             case NUM_AVAILABLE_TICKETS -> {
                 // This request requires us to respond with a server ID.
@@ -174,6 +186,7 @@ public class Slug implements RequestHandler {
                         // Sell the ticket to the customer.
                         final var ticket = reservation.sell();
                         this.reservations.remove(customer);
+                        this.numSold++;
                         // Respond with the ID of the sold ticket.
                         request.respondWithInt(ticket.getId());
                     } else {